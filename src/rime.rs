@@ -8,16 +8,23 @@ pub mod config;
 pub(crate) mod context;
 pub(crate) mod deployer;
 pub mod dict;
+pub(crate) mod engine;
 pub(crate) mod filter;
+pub(crate) mod gear;
 pub mod key_event;
 pub mod key_table;
 pub(crate) mod language;
 pub(crate) mod menu;
 pub(crate) mod messenger;
+pub(crate) mod processor;
 pub(crate) mod registry;
 pub(crate) mod resource;
+pub(crate) mod schema;
 pub(crate) mod segmentation;
+pub(crate) mod segmentor;
 pub(crate) mod service;
+pub(crate) mod spans;
 pub(crate) mod translation;
+pub(crate) mod translator;
 
 pub const RIME_VERSION: &str = "1.11.2";
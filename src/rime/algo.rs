@@ -1,5 +1,6 @@
 pub mod algebra;
 pub mod calculus;
+pub mod dynamics;
 pub(crate) mod encoder;
 pub mod spelling;
 pub mod syllabifier;
@@ -0,0 +1,24 @@
+// Frequency dynamics of user dictionary entries. Time is measured in ticks,
+// the number of commits made to the user dictionary so far.
+
+// Half of the decayed frequency is lost in about 140 ticks.
+const DECAY_TICKS: f64 = 200.0;
+
+// Upper bound of the decayed frequency of an entry committed at every tick.
+const MAX_DEE: f64 = 1.0 / (1.0 - (-1.0 / DECAY_TICKS).exp());
+
+// Decays the frequency `dee` of an entry last updated at `last_tick` to the
+// present `tick`, and adds `delta` for the commits made now.
+pub fn formula_d(delta: f64, tick: f64, dee: f64, last_tick: f64) -> f64 {
+    delta + dee * ((last_tick - tick) / DECAY_TICKS).exp()
+}
+
+// Estimates the probability of an entry to be committed, from its static
+// share `static_p`, its share of all commits `user_p`, the present `tick` and
+// its decayed frequency `dee`. Commits outweigh the static share as the user
+// dictionary grows, and recent commits outweigh both.
+pub fn formula_p(static_p: f64, user_p: f64, tick: f64, dee: f64) -> f64 {
+    let growth = (1.0 - (-tick / 10000.0).exp()).powi(10);
+    let mean = static_p - (static_p - user_p) * growth;
+    mean + (1.0 - mean) * (dee / MAX_DEE).min(1.0)
+}
@@ -140,25 +140,15 @@ impl TableEncoder {
 
         self.process_rules(config);
 
-        config.data.read().ok().and_then(|data| {
-            let item = data
-                .root
-                .as_any()
-                .downcast_ref::<ConfigMap>()?
-                .get("encoder/exclude_patterns")?;
-
-            let seq = &item.as_any().downcast_ref::<ConfigList>()?.seq;
-
+        if let Some(list) = config.get_list("encoder/exclude_patterns") {
             self.exclude_patterns
-                .extend(seq.iter().flatten().filter_map(|pattern| {
+                .extend(list.seq.iter().flatten().filter_map(|pattern| {
                     pattern
                         .as_any()
                         .downcast_ref::<ConfigValue>()
                         .and_then(|pattern| Regex::new(pattern.str()).ok())
                 }));
-
-            Some(())
-        });
+        }
 
         let string = config.get_string("encoder/tail_anchor");
         if !string.is_empty() {
@@ -170,25 +160,15 @@ impl TableEncoder {
     }
 
     fn process_rules(&mut self, config: &Config) {
-        config.data.read().ok().and_then(|data| {
-            let item = data
-                .root
-                .as_any()
-                .downcast_ref::<ConfigMap>()?
-                .get("encoder/rules")?;
-
-            let seq = &item.as_any().downcast_ref::<ConfigList>()?.seq;
-
-            for rule in seq.iter().flatten() {
-                if let Some(rule) = rule.as_any().downcast_ref::<ConfigMap>() {
-                    self.process_rule(rule);
-                }
+        let Some(list) = config.get_list("encoder/rules") else {
+            return;
+        };
+        for rule in list.seq.iter().flatten() {
+            if let Some(rule) = rule.as_any().downcast_ref::<ConfigMap>() {
+                self.process_rule(rule);
             }
-
-            self.max_phrase_length = self.max_phrase_length.min(MAX_PHRASE_LENGTH);
-
-            Some(())
-        });
+        }
+        self.max_phrase_length = self.max_phrase_length.min(MAX_PHRASE_LENGTH);
     }
 
     fn process_rule(&mut self, rule: &ConfigMap) {
@@ -353,13 +333,6 @@ impl TableEncoder {
                               // 'abc def' ~ '(AaZb)Zz' is OK
                 }
 
-                if let Some(char_vec) = code.0.get(c.char_index as usize) {
-                    let bytes = char_vec.as_bytes();
-                    if let Some(byte) = bytes.get(c.code_index as usize) {
-                        result.push(*byte);
-                    }
-                }
-
                 if let Some(byte) = code
                     .get(c.char_index as usize)
                     .and_then(|f| f.as_bytes().get(c.code_index as usize))
@@ -381,7 +354,7 @@ impl TableEncoder {
         None
     }
 
-    fn encode_phrase(&self, phrase: &str, value: &str) -> bool {
+    pub(crate) fn encode_phrase(&self, phrase: &str, value: &str) -> bool {
         let phrase_length = phrase.chars().count();
 
         if phrase_length as i32 > self.max_phrase_length {
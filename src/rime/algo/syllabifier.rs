@@ -116,7 +116,7 @@ pub struct Syllabifier<'a, T: Corrector> {
 }
 
 impl<'a, T: Corrector> Syllabifier<'a, T> {
-    fn _new(delimiters: String, enable_completion: bool, strict_spelling: bool) -> Self {
+    pub fn new(delimiters: String, enable_completion: bool, strict_spelling: bool) -> Self {
         Self {
             delimiters,
             enable_completion,
@@ -3,6 +3,8 @@ use std::cmp::Ordering;
 use std::collections::VecDeque;
 use std::sync::Arc;
 
+use crate::rime::spans::Spans;
+
 pub(crate) type CandidateQueue = VecDeque<Arc<dyn Candidate>>;
 pub(crate) type CandidateList = Vec<Option<Arc<dyn Candidate>>>;
 
@@ -30,6 +32,11 @@ pub(crate) trait Candidate: Any {
         ""
     }
 
+    // Syllable boundaries within the candidate (optional)
+    fn spans(&self) -> Spans {
+        Spans::default()
+    }
+
     fn set_type(&mut self, type_: &str);
 
     fn set_start(&mut self, start: usize);
@@ -71,6 +78,7 @@ impl PartialOrd for dyn Candidate {
     }
 }
 
+#[derive(Clone)]
 pub(crate) struct BaseCandidate {
     type_: String,
     start: usize,
@@ -365,14 +373,14 @@ impl UniquifiedCandidate {
         }
     }
 
-    fn append(&mut self, item: Arc<dyn Candidate>) {
+    pub(crate) fn append(&mut self, item: Arc<dyn Candidate>) {
         if self.quality() < item.quality() {
             self.set_quality(item.quality());
         }
         self.items.push(item);
     }
 
-    fn items(&self) -> &Vec<Arc<dyn Candidate>> {
+    pub(crate) fn items(&self) -> &Vec<Arc<dyn Candidate>> {
         &self.items
     }
 
@@ -7,13 +7,13 @@ use std::collections::VecDeque;
 const MAX_RECORDS: usize = 20;
 
 #[derive(Debug, Clone)]
-struct CommitRecord {
-    type_: String,
-    text: String,
+pub(crate) struct CommitRecord {
+    pub(crate) type_: String,
+    pub(crate) text: String,
 }
 
 impl CommitRecord {
-    fn new(type_: &str, text: &str) -> Self {
+    pub(crate) fn new(type_: &str, text: &str) -> Self {
         Self {
             type_: type_.to_string(),
             text: text.to_string(),
@@ -46,14 +46,14 @@ impl CommitHistory {
         }
     }
 
-    fn push(&mut self, record: CommitRecord) {
+    pub(crate) fn push(&mut self, record: CommitRecord) {
         self.records.push_back(record);
         if self.records.len() > MAX_RECORDS {
             self.records.pop_front();
         }
     }
 
-    fn push_key_event(&mut self, key_event: KeyEvent) {
+    pub(crate) fn push_key_event(&mut self, key_event: KeyEvent) {
         if key_event.modifier() == 0 {
             match key_event.keycode() {
                 XK_BACK_SPACE | XK_RETURN => self.records.clear(),
@@ -63,7 +63,7 @@ impl CommitHistory {
         }
     }
 
-    fn push_composition(&mut self, composition: &Composition, input: &str) {
+    pub(crate) fn push_composition(&mut self, composition: &Composition, input: &str) {
         let mut last: Option<usize> = None;
         let mut end = 0;
 
@@ -103,14 +103,23 @@ impl CommitHistory {
         }
     }
 
-    fn repr(&self) -> String {
+    pub(crate) fn back(&self) -> Option<&CommitRecord> {
+        self.records.back()
+    }
+
+    // From the oldest to the latest.
+    pub(crate) fn iter(&self) -> impl DoubleEndedIterator<Item = &CommitRecord> {
+        self.records.iter()
+    }
+
+    pub(crate) fn repr(&self) -> String {
         self.records
             .iter()
             .map(|record| format!("[{}]{}", record.type_, record.text))
             .collect::<String>()
     }
 
-    fn latest_text(&self) -> String {
+    pub(crate) fn latest_text(&self) -> String {
         self.records
             .back()
             .map_or_else(String::new, |record| record.text.clone())
@@ -1,6 +1,8 @@
 use std::any::Any;
 
+use crate::rime::context::Context;
 use crate::rime::deployer::Deployer;
+use crate::rime::schema::Schema;
 
 pub(crate) trait ComponentBase: Any + Send + Sync {
     fn as_any(&self) -> &dyn Any;
@@ -15,3 +17,54 @@ impl<T: 'static + DeploymentTask> ComponentBase for T {
         self
     }
 }
+
+// Describes which component to create and where it reads its settings from.
+// A prescription "klass@name_space" names the registered class, and the
+// config section the instance is configured with, e.g. "affix_segmentor@alphabet".
+pub(crate) struct Ticket<'a> {
+    pub(crate) schema: &'a Schema,
+    pub(crate) context: &'a Context,
+    pub(crate) name_space: String,
+    pub(crate) klass: String,
+}
+
+impl<'a> Ticket<'a> {
+    pub(crate) fn new(
+        schema: &'a Schema,
+        context: &'a Context,
+        name_space: &str,
+        prescription: &str,
+    ) -> Self {
+        let (klass, name_space) = match prescription.split_once('@') {
+            Some((klass, name_space)) => (klass, name_space),
+            None => (prescription, name_space),
+        };
+        Self {
+            schema,
+            context,
+            name_space: name_space.to_string(),
+            klass: klass.to_string(),
+        }
+    }
+}
+
+// A registered factory of engine components, e.g. processors and translators.
+pub(crate) struct Component<T> {
+    create: fn(&Ticket) -> T,
+}
+
+impl<T> Component<T> {
+    pub(crate) fn new(create: fn(&Ticket) -> T) -> Self {
+        Self { create }
+    }
+
+    pub(crate) fn create(&self, ticket: &Ticket) -> T {
+        (self.create)(ticket)
+    }
+}
+
+impl<T: 'static> ComponentBase for Component<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -201,7 +201,7 @@ impl Composition {
     }
 
     // Returns text of the last segment before the given position.
-    fn get_text_before(&self, pos: usize) -> String {
+    pub(crate) fn get_text_before(&self, pos: usize) -> String {
         for seg in self.0.segments.iter().rev() {
             if seg.end <= pos {
                 if let Some(cand) = seg.get_selected_candidate() {
@@ -3,8 +3,9 @@ use std::sync::{Arc, RwLock};
 
 use log::{error, info};
 
+use crate::rime::common::PathExt;
 use crate::rime::config::config_data::ConfigData;
-use crate::rime::config::config_types::{ConfigItem, ConfigList, ConfigMap, ValueType};
+use crate::rime::config::config_types::{ConfigItem, ConfigList, ConfigMap, ConfigValue, ValueType};
 
 pub(crate) struct Config {
     pub(crate) data: Arc<RwLock<ConfigData>>,
@@ -26,6 +27,22 @@ impl Config {
         }
     }
 
+    pub(crate) fn load_from_file(&mut self, file_path: &PathExt) -> bool {
+        if let Ok(mut data) = self.data.write() {
+            data.load_from_file(file_path, &())
+        } else {
+            error!("Failed to acquire write lock");
+            false
+        }
+    }
+
+    pub(crate) fn file_path(&self) -> Option<PathExt> {
+        self.data
+            .read()
+            .ok()
+            .and_then(|data| data.file_path().cloned())
+    }
+
     pub(crate) fn contains(&self, key: &str) -> bool {
         match self.data.read() {
             Ok(data) => Self::has(data.root.as_any().downcast_ref::<ConfigMap>(), key),
@@ -77,57 +94,104 @@ impl Config {
     }
 
     pub(crate) fn get_string(&self, key: &str) -> String {
-        self.data
-            .read()
-            .ok()
-            .and_then(|data| {
-                data.root
-                    .as_any()
-                    .downcast_ref::<ConfigMap>()?
-                    .get_string(key)
-            })
+        self.get_value(key)
+            .map(|value| value.parse_string())
             .unwrap_or_default()
     }
 
     pub(crate) fn get_bool(&self, key: &str) -> bool {
-        self.data
-            .read()
-            .ok()
-            .and_then(|data| {
-                data.root
-                    .as_any()
-                    .downcast_ref::<ConfigMap>()?
-                    .get_bool(key)
-            })
+        self.get_value(key)
+            .map(|value| value.parse_bool())
             .unwrap_or_default()
     }
 
     pub(crate) fn get_int(&self, key: &str) -> i32 {
-        self.data
-            .read()
-            .ok()
-            .and_then(|data| data.root.as_any().downcast_ref::<ConfigMap>()?.get_int(key))
+        self.get_value(key)
+            .and_then(|value| value.parse_int())
             .unwrap_or_default()
     }
 
     pub(crate) fn get_double(&self, key: &str) -> f64 {
-        self.data
-            .read()
-            .ok()
-            .and_then(|data| {
-                data.root
-                    .as_any()
-                    .downcast_ref::<ConfigMap>()?
-                    .get_double(key)
-            })
+        self.get_value(key)
+            .map(|value| value.parse_double())
             .unwrap_or_default()
     }
 
+    // Keys are slash-separated paths, e.g. "speller/alphabet" or "schema_list/@0/schema".
     pub(crate) fn get_item(&self, key: &str) -> Option<Arc<dyn ConfigItem>> {
-        self.data
-            .read()
-            .ok()
-            .and_then(|data| data.root.as_any().downcast_ref::<ConfigMap>()?.get(key))
+        self.data.read().ok().and_then(|data| data.traverse(key))
+    }
+
+    // Returns a copy of the list at key, sharing its elements.
+    pub(crate) fn get_list(&self, key: &str) -> Option<Arc<ConfigList>> {
+        let item = self.get_item(key)?;
+        let list = item.as_any().downcast_ref::<ConfigList>()?;
+        let mut copy = ConfigList::new();
+        for i in 0..list.size() {
+            copy.append(list.get_at(i));
+        }
+        Some(Arc::new(copy))
+    }
+
+    fn get_value(&self, key: &str) -> Option<ConfigValue> {
+        self.get_item(key).and_then(|item| {
+            item.as_any()
+                .downcast_ref::<ConfigValue>()
+                .map(|value| ConfigValue::from_str(value.str()))
+        })
+    }
+
+    pub(crate) fn set_bool(&self, key: &str, value: bool) -> bool {
+        self.set_item_at(key, Arc::new(ConfigValue::from_bool(value)))
+    }
+
+    // Replaces the maps along the path with modified copies, creating the
+    // missing ones.
+    pub(crate) fn set_item_at(&self, key: &str, item: Arc<dyn ConfigItem>) -> bool {
+        let Ok(mut data) = self.data.write() else {
+            error!("Failed to acquire write lock");
+            return false;
+        };
+        let keys = ConfigData::split_path(key.trim_start_matches('/'));
+        match Self::copy_with_item(Some(data.root.clone()), &keys, item) {
+            Some(root) => {
+                data.root = root;
+                data.set_modified();
+                true
+            }
+            None => {
+                error!("cannot set config item '{}'.", key);
+                false
+            }
+        }
+    }
+
+    fn copy_with_item(
+        node: Option<Arc<dyn ConfigItem>>,
+        keys: &[String],
+        item: Arc<dyn ConfigItem>,
+    ) -> Option<Arc<dyn ConfigItem>> {
+        let Some((key, rest)) = keys.split_first() else {
+            return Some(item);
+        };
+        let mut map = ConfigMap::new();
+        if let Some(node) = node.filter(|node| node.type_() != ValueType::Null) {
+            map.map = node.as_any().downcast_ref::<ConfigMap>()?.map.clone();
+        }
+        let child = Self::copy_with_item(map.get(key), rest, item)?;
+        map.map.insert(key.clone(), child);
+        Some(Arc::new(map))
+    }
+
+    // Writes back to the file it was loaded from, if modified.
+    pub(crate) fn save(&self) -> bool {
+        match self.data.write() {
+            Ok(mut data) => data.save(),
+            Err(_) => {
+                error!("Failed to acquire write lock");
+                false
+            }
+        }
     }
 
     pub(crate) fn get_item_by_path(&self, path: &str) -> Option<Arc<dyn ConfigItem>> {
@@ -185,8 +185,34 @@ impl ConfigData {
     }
 
     fn convert_from_yaml(&self, node: &Yaml) -> Option<Arc<dyn ConfigItem>> {
-        let _ = node;
-        todo!()
+        match node {
+            Yaml::Null | Yaml::BadValue | Yaml::Alias(_) => None,
+            Yaml::String(value) | Yaml::Real(value) => Some(Arc::new(ConfigValue::from_str(value))),
+            Yaml::Integer(value) => Some(Arc::new(ConfigValue::from_str(&value.to_string()))),
+            Yaml::Boolean(value) => Some(Arc::new(ConfigValue::from_str(&value.to_string()))),
+            Yaml::Array(array) => {
+                let mut config_list = ConfigList::new();
+                for item in array {
+                    config_list.append(self.convert_from_yaml(item));
+                }
+                Some(Arc::new(config_list))
+            }
+            Yaml::Hash(hash) => {
+                let mut config_map = ConfigMap::new();
+                for (key, value) in hash {
+                    let key = match key {
+                        Yaml::String(key) | Yaml::Real(key) => key.clone(),
+                        Yaml::Integer(key) => key.to_string(),
+                        Yaml::Boolean(key) => key.to_string(),
+                        _ => continue,
+                    };
+                    if let Some(item) = self.convert_from_yaml(value) {
+                        config_map.map.insert(key, item);
+                    }
+                }
+                Some(Arc::new(config_map))
+            }
+        }
     }
 
     fn convert_to_yaml(&self, node: &Arc<dyn ConfigItem>) -> Option<Yaml> {
@@ -342,8 +368,28 @@ impl ConfigData {
     }
 
     pub(crate) fn traverse(&self, path: &str) -> Option<Arc<dyn ConfigItem>> {
-        let _ = path;
-        todo!()
+        info!("traverse: {}", path);
+        if path.is_empty() || path == "/" {
+            return Some(self.root.clone());
+        }
+        let mut node = self.root.clone();
+        for key in Self::split_path(path.trim_start_matches('/')) {
+            let child = match node.type_() {
+                ValueType::List if Self::is_list_item_reference(&key) => {
+                    let list = node.as_any().downcast_ref::<ConfigList>()?;
+                    let index = if let Some(ListPos::Last) = ListPos::from_str(&key[1..]) {
+                        list.size().checked_sub(1)?
+                    } else {
+                        key[1..].parse::<usize>().ok()?
+                    };
+                    list.get_at(index)
+                }
+                ValueType::Map => node.as_any().downcast_ref::<ConfigMap>()?.get(&key),
+                _ => None,
+            };
+            node = child?;
+        }
+        Some(node)
     }
 
     pub(crate) fn split_path(path: &str) -> Vec<String> {
@@ -69,7 +69,7 @@ impl ConfigValue {
         }
     }
 
-    fn from_bool(value: bool) -> Self {
+    pub(crate) fn from_bool(value: bool) -> Self {
         Self {
             base: BaseConfigItem::new(ValueType::Scalar),
             value: value.to_string(),
@@ -181,7 +181,7 @@ impl ConfigList {
         }
     }
 
-    fn get_at(&self, i: usize) -> Option<Arc<dyn ConfigItem>> {
+    pub(crate) fn get_at(&self, i: usize) -> Option<Arc<dyn ConfigItem>> {
         self.seq.get(i).and_then(|item| item.clone())
     }
 
@@ -257,7 +257,7 @@ impl ConfigMap {
         self.map.get(key).cloned()
     }
 
-    fn get_value(&self, key: &str) -> Option<&ConfigValue> {
+    pub(crate) fn get_value(&self, key: &str) -> Option<&ConfigValue> {
         self.map
             .get(key)
             .and_then(|item| item.as_any().downcast_ref::<ConfigValue>())
@@ -9,13 +9,13 @@ use std::cmp::min;
 use std::collections::BTreeMap;
 use std::sync::Arc;
 
-type Notifier = Signal<(Arc<Context>,)>;
-type OptionUpdateNotifier = Signal<(Arc<Context>, String)>;
-type PropertyUpdateNotifier = Signal<(Arc<Context>, String)>;
-type KeyEventNotifier = Signal<(Arc<Context>, KeyEvent)>;
+pub(crate) type Notifier = Signal<(Arc<Context>,)>;
+pub(crate) type OptionUpdateNotifier = Signal<(Arc<Context>, String)>;
+pub(crate) type PropertyUpdateNotifier = Signal<(Arc<Context>, String)>;
+pub(crate) type KeyEventNotifier = Signal<(Arc<Context>, KeyEvent)>;
 
 #[derive(Clone)]
-struct Context {
+pub(crate) struct Context {
     input: String,
     caret_pos: usize,
     composition: Composition,
@@ -32,7 +32,7 @@ struct Context {
 }
 
 impl Context {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             input: String::new(),
             caret_pos: 0,
@@ -50,42 +50,39 @@ impl Context {
         }
     }
 
-    fn commit(&mut self) -> bool {
+    pub(crate) fn commit(&mut self) -> bool {
         if !self.is_composing() {
             return false;
         }
         let context = Arc::new(self.clone());
         // Notify the engine and interested components
-        {
-            // self.commit_notifier.emit(context.clone());
-            todo!()
-        }
+        self.commit_notifier.emit(context);
         // start over
         self.clear();
         true
     }
 
-    fn get_commit_text(&self) -> String {
+    pub(crate) fn get_commit_text(&self) -> String {
         if self.get_option("dumb") {
             return String::new();
         }
         self.composition.get_commit_text()
     }
 
-    fn get_script_text(&self) -> String {
+    pub(crate) fn get_script_text(&self) -> String {
         self.composition.get_script_text()
     }
 
-    fn get_preedit(&self) -> Preedit {
+    pub(crate) fn get_preedit(&self) -> Preedit {
         self.composition
             .get_preedit(&self.input, self.caret_pos, &self.get_soft_cursor())
     }
 
-    fn is_composing(&self) -> bool {
+    pub(crate) fn is_composing(&self) -> bool {
         !self.input.is_empty() || !self.composition.is_empty()
     }
 
-    fn has_menu(&self) -> bool {
+    pub(crate) fn has_menu(&self) -> bool {
         if self.composition.is_empty() {
             return false;
         }
@@ -96,14 +93,14 @@ impl Context {
         }
     }
 
-    fn get_selected_candidate(&self) -> Option<Arc<dyn Candidate>> {
+    pub(crate) fn get_selected_candidate(&self) -> Option<Arc<dyn Candidate>> {
         if self.composition.is_empty() {
             return None;
         }
         self.composition.segments.last()?.get_selected_candidate()
     }
 
-    fn push_input(&mut self, ch: char) -> bool {
+    pub(crate) fn push_input(&mut self, ch: char) -> bool {
         if self.caret_pos >= self.input.len() {
             self.input.push(ch);
             self.caret_pos = self.input.len();
@@ -111,14 +108,11 @@ impl Context {
             self.input.insert(self.caret_pos, ch);
             self.caret_pos += 1;
         }
-        {
-            // self.update_notifier.emit(self.clone());
-            todo!()
-        }
+        self.update_notifier.emit(Arc::new(self.clone()));
         true
     }
 
-    fn push_input_str(&mut self, str: &str) -> bool {
+    pub(crate) fn push_input_str(&mut self, str: &str) -> bool {
         if self.caret_pos >= self.input.len() {
             self.input.push_str(str);
             self.caret_pos = self.input.len();
@@ -126,50 +120,38 @@ impl Context {
             self.input.insert_str(self.caret_pos, str);
             self.caret_pos += str.len();
         }
-        {
-            // self.update_notifier.emit(self.clone());
-            todo!()
-        }
+        self.update_notifier.emit(Arc::new(self.clone()));
         true
     }
 
-    fn pop_input(&mut self, len: usize) -> bool {
+    pub(crate) fn pop_input(&mut self, len: usize) -> bool {
         if self.caret_pos < len {
             return false;
         }
         self.caret_pos -= len;
         self.input.drain(self.caret_pos..self.caret_pos + len);
-        {
-            //   self.update_notifier.emit(self.clone());
-            todo!()
-        }
+        self.update_notifier.emit(Arc::new(self.clone()));
         true
     }
 
-    fn delete_input(&mut self, len: usize) -> bool {
+    pub(crate) fn delete_input(&mut self, len: usize) -> bool {
         if self.caret_pos + len > self.input.len() {
             return false;
         }
         self.input.drain(self.caret_pos..self.caret_pos + len);
-        {
-            // self.update_notifier.emit(self.clone());
-            todo!()
-        }
+        self.update_notifier.emit(Arc::new(self.clone()));
         true
     }
 
-    fn clear(&mut self) {
+    pub(crate) fn clear(&mut self) {
         self.input.clear();
         self.caret_pos = 0;
         self.composition.segments.clear();
-        {
-            // self.update_notifier.emit(self.clone());
-            todo!()
-        }
+        self.update_notifier.emit(Arc::new(self.clone()));
     }
 
     // Return false if there is no candidate at index
-    fn select(&mut self, index: usize) -> bool {
+    pub(crate) fn select(&mut self, index: usize) -> bool {
         if self.composition.is_empty() {
             return false;
         }
@@ -179,19 +161,15 @@ impl Context {
                 seg.selected_index = index;
                 seg.status = SegmentStatus::Selected;
                 info!("Selected: '{}', index = {}", cand.text(), index);
-                {
-                    // self.select_notifier.emit(self.clone());
-                    todo!()
-                }
+                self.select_notifier.emit(Arc::new(self.clone()));
+                return true;
             }
-            true
-        } else {
-            false
         }
+        false
     }
 
     // Return false if the selected index has not changed
-    fn highlight(&mut self, index: usize) -> bool {
+    pub(crate) fn highlight(&mut self, index: usize) -> bool {
         if self.composition.is_empty() || self.composition.segments.last().unwrap().menu.is_none() {
             return false;
         }
@@ -215,10 +193,7 @@ impl Context {
             return false;
         }
         seg.selected_index = new_index;
-        {
-            // self.update_notifier.emit(self.clone());
-            todo!()
-        }
+        self.update_notifier.emit(Arc::new(self.clone()));
         info!(
             "Selection changed from: {} to: {}",
             previous_index, new_index
@@ -226,7 +201,7 @@ impl Context {
         true
     }
 
-    fn delete_candidate_by_index(&mut self, index: usize) -> bool {
+    pub(crate) fn delete_candidate_by_index(&mut self, index: usize) -> bool {
         if let Some(seg) = self.composition.segments.last() {
             if let Some(candidate) = seg.get_candidate_at(index) {
                 return self.delete_candidate(Some(candidate));
@@ -235,7 +210,7 @@ impl Context {
         false
     }
 
-    fn delete_current_selection(&mut self) -> bool {
+    pub(crate) fn delete_current_selection(&mut self) -> bool {
         if let Some(seg) = self.composition.segments.last() {
             if let Some(candidate) = seg.get_selected_candidate() {
                 return self.delete_candidate(Some(candidate));
@@ -245,7 +220,7 @@ impl Context {
     }
 
     // Return false if there's no candidate for current segment
-    fn confirm_current_selection(&mut self) -> bool {
+    pub(crate) fn confirm_current_selection(&mut self) -> bool {
         if self.composition.is_empty() {
             return false;
         }
@@ -266,14 +241,25 @@ impl Context {
             }
         }
 
-        {
-            // self.select_notifier.emit(self.clone());
-            todo!()
-        }
+        self.select_notifier.emit(Arc::new(self.clone()));
         true
     }
 
-    fn begin_editing(&mut self) {
+    // Return false if there is no selected segment before the current one
+    pub(crate) fn confirm_previous_selection(&mut self) -> bool {
+        for seg in self.composition.segments.iter_mut().rev() {
+            if seg.status > SegmentStatus::Selected {
+                return false;
+            }
+            if seg.status == SegmentStatus::Selected {
+                seg.status = SegmentStatus::Confirmed;
+                return true;
+            }
+        }
+        false
+    }
+
+    pub(crate) fn begin_editing(&mut self) {
         for seg in self.composition.segments.iter_mut().rev() {
             if seg.status > SegmentStatus::Selected {
                 return;
@@ -285,7 +271,7 @@ impl Context {
         }
     }
 
-    fn reopen_previous_segment(&mut self) -> bool {
+    pub(crate) fn reopen_previous_segment(&mut self) -> bool {
         if self.composition.trim() {
             if !self.composition.is_empty() {
                 if let Some(seg) = self.composition.segments.last_mut() {
@@ -294,17 +280,14 @@ impl Context {
                     }
                 }
             }
-            {
-                // self.update_notifier.emit(self.clone());
-                todo!()
-            }
+            self.update_notifier.emit(Arc::new(self.clone()));
             true
         } else {
             false
         }
     }
 
-    fn clear_previous_segment(&mut self) -> bool {
+    pub(crate) fn clear_previous_segment(&mut self) -> bool {
         if let Some(last_segment) = self.composition.segments.last() {
             let where_ = last_segment.start;
             if where_ < self.input.len() {
@@ -315,7 +298,7 @@ impl Context {
         false
     }
 
-    fn reopen_previous_selection(&mut self) -> bool {
+    pub(crate) fn reopen_previous_selection(&mut self) -> bool {
         let len = self.composition.segments.len();
         let mut target_index = None;
 
@@ -338,17 +321,14 @@ impl Context {
         if let Some(index) = target_index {
             self.composition.segments.truncate(index + 1);
             self.composition.segments[index].reopen(self.caret_pos);
-            {
-                // self.update_notifier.emit(self.clone());
-                todo!()
-            }
+            self.update_notifier.emit(Arc::new(self.clone()));
             return true;
         }
 
         false
     }
 
-    fn clear_non_confirmed_composition(&mut self) -> bool {
+    pub(crate) fn clear_non_confirmed_composition(&mut self) -> bool {
         if self.composition.segments.is_empty() {
             return false;
         }
@@ -370,70 +350,108 @@ impl Context {
         true
     }
 
-    fn refresh_non_confirmed_composition(&mut self) -> bool {
+    pub(crate) fn refresh_non_confirmed_composition(&mut self) -> bool {
         if self.clear_non_confirmed_composition() {
-            {
-                // self.update_notifier.emit(self.clone());
-                todo!()
-            }
+            self.update_notifier.emit(Arc::new(self.clone()));
             true
         } else {
             false
         }
     }
 
-    fn set_caret_pos(&mut self, caret_pos: usize) {
+    pub(crate) fn set_caret_pos(&mut self, caret_pos: usize) {
         if caret_pos > self.input.len() {
             self.caret_pos = self.input.len();
         } else {
             self.caret_pos = caret_pos;
         }
-        {
-            // self.update_notifier.emit(self.clone());
-            todo!()
-        }
+        self.update_notifier.emit(Arc::new(self.clone()));
     }
 
-    fn set_composition(&mut self, comp: Composition) {
+    pub(crate) fn set_composition(&mut self, comp: Composition) {
         self.composition = comp;
     }
 
-    fn set_input(&mut self, value: String) {
+    pub(crate) fn input(&self) -> &str {
+        &self.input
+    }
+
+    pub(crate) fn caret_pos(&self) -> usize {
+        self.caret_pos
+    }
+
+    pub(crate) fn composition(&self) -> &Composition {
+        &self.composition
+    }
+
+    pub(crate) fn composition_mut(&mut self) -> &mut Composition {
+        &mut self.composition
+    }
+
+    pub(crate) fn commit_history(&self) -> &CommitHistory {
+        &self.commit_history
+    }
+
+    pub(crate) fn commit_history_mut(&mut self) -> &mut CommitHistory {
+        &mut self.commit_history
+    }
+
+    pub(crate) fn commit_notifier(&self) -> &Notifier {
+        &self.commit_notifier
+    }
+
+    pub(crate) fn select_notifier(&self) -> &Notifier {
+        &self.select_notifier
+    }
+
+    pub(crate) fn update_notifier(&self) -> &Notifier {
+        &self.update_notifier
+    }
+
+    pub(crate) fn delete_notifier(&self) -> &Notifier {
+        &self.delete_notifier
+    }
+
+    pub(crate) fn option_update_notifier(&self) -> &OptionUpdateNotifier {
+        &self.option_update_notifier
+    }
+
+    pub(crate) fn property_update_notifier(&self) -> &PropertyUpdateNotifier {
+        &self.property_update_notifier
+    }
+
+    pub(crate) fn unhandled_key_notifier(&self) -> &KeyEventNotifier {
+        &self.unhandled_key_notifier
+    }
+
+    pub(crate) fn set_input(&mut self, value: String) {
         self.input = value;
         self.caret_pos = self.input.len();
-        {
-            // self.update_notifier.emit(self.clone());
-            todo!()
-        }
+        self.update_notifier.emit(Arc::new(self.clone()));
     }
 
-    fn set_option(&mut self, name: &str, value: bool) {
+    pub(crate) fn set_option(&mut self, name: &str, value: bool) {
         self.options.insert(name.to_string(), value);
         info!("Context::set_option {} = {}", name, value);
-        {
-            /*  self.option_update_notifier.emit(self.clone(), name.to_string()); */
-            todo!()
-        }
+        self.option_update_notifier
+            .emit(Arc::new(self.clone()), name.to_string());
     }
 
-    fn get_option(&self, name: &str) -> bool {
+    pub(crate) fn get_option(&self, name: &str) -> bool {
         *self.options.get(name).unwrap_or(&false)
     }
 
-    fn set_property(&mut self, name: &str, value: &str) {
+    pub(crate) fn set_property(&mut self, name: &str, value: &str) {
         self.properties.insert(name.to_string(), value.to_string());
-        {
-            // self.property_update_notifier
-            //     .emit(self.clone(), name.to_string());
-            todo!()
-        }
+        self.property_update_notifier
+            .emit(Arc::new(self.clone()), name.to_string());
     }
 
-    fn get_property(&self, name: &str) -> String {
+    pub(crate) fn get_property(&self, name: &str) -> String {
         self.properties.get(name).cloned().unwrap_or_default()
     }
 
-    fn clear_transient_options(&mut self) {
+    pub(crate) fn clear_transient_options(&mut self) {
         info!("Context::clear_transient_options");
         let opt_keys = self
             .options
@@ -459,7 +477,7 @@ impl Context {
         }
     }
 
-    fn get_soft_cursor(&self) -> String {
+    pub(crate) fn get_soft_cursor(&self) -> String {
         if self.get_option("soft_cursor") {
             "‸".to_string() // U+2038 ‸ CARET
         } else {
@@ -467,13 +485,10 @@ impl Context {
         }
     }
 
-    fn delete_candidate(&mut self, cand: Option<Arc<dyn Candidate>>) -> bool {
+    pub(crate) fn delete_candidate(&mut self, cand: Option<Arc<dyn Candidate>>) -> bool {
         if let Some(cand) = cand {
             info!("Deleting candidate: {}", cand.text());
-            {
-                // self.delete_notifier.emit(self.clone());
-                todo!()
-            }
+            self.delete_notifier.emit(Arc::new(self.clone()));
             true // CAVEAT: this doesn't mean anything is deleted for sure
         } else {
             false
@@ -1,12 +1,17 @@
 pub mod corrector;
 pub mod db;
 mod db_utils;
+pub mod dict_compiler;
 pub(crate) mod dict_settings;
+pub(crate) mod dictionary;
+pub mod gram_db;
 mod mapped_file;
 pub mod prism;
+pub(crate) mod reverse_lookup_dictionary;
 mod string_table;
 pub mod table;
 pub mod text_db;
 mod tsv;
 pub mod user_db;
+pub(crate) mod user_dictionary;
 pub mod vocabulary;
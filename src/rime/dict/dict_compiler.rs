@@ -0,0 +1,344 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+
+use log::{error, info, warn};
+
+use crate::rime::algo::algebra::{Projection, Script};
+use crate::rime::algo::utilities::{checksum, ChecksumComputer};
+use crate::rime::algo::SyllableId;
+use crate::rime::common::PathExt;
+use crate::rime::config::config_component::Config;
+use crate::rime::dict::dict_settings::DictSettings;
+use crate::rime::dict::prism::Prism;
+use crate::rime::dict::reverse_lookup_dictionary::ReverseDb;
+use crate::rime::dict::table::Table;
+use crate::rime::dict::vocabulary::{
+    Code, ReverseLookupTable, ShortDictEntry, Syllabary, Vocabulary,
+};
+
+// An entry as read from a dict file.
+struct RawDictEntry {
+    text: String,
+    // syllables separated by spaces
+    raw_code: String,
+    weight: f64,
+}
+
+// Compiles "<data_dir>/<dict_name>.dict.yaml" and the tables it imports into
+// the prism, table and reverse db under "<data_dir>/build".
+pub struct DictCompiler {
+    data_dir: PathExt,
+    dict_name: String,
+    prism_name: String,
+}
+
+impl DictCompiler {
+    pub fn new(data_dir: &PathExt, dict_name: &str, prism_name: &str) -> Self {
+        Self {
+            data_dir: data_dir.clone(),
+            dict_name: dict_name.to_string(),
+            prism_name: prism_name.to_string(),
+        }
+    }
+
+    // Spellings in the prism are derived from the syllables by the
+    // speller/algebra of the schema file if given. Files built from the same
+    // sources as they are now are left untouched; the others are replaced as
+    // a whole, leaving engines that have them loaded undisturbed.
+    pub fn compile(&self, schema_file: Option<&PathExt>) -> bool {
+        info!("compiling dictionary '{}'.", self.dict_name);
+        let Some(settings) = self.load_settings() else {
+            return false;
+        };
+        let Some(tables) = settings.get_tables() else {
+            return false;
+        };
+        let dict_files: Vec<PathExt> = (0..tables.seq.len())
+            .filter_map(|i| tables.get_str_at(i))
+            .map(|table| self.dict_file(table))
+            .collect();
+        let mut computer = ChecksumComputer::new(0);
+        for dict_file in &dict_files {
+            if let Err(e) = computer.process_file(dict_file) {
+                error!("error reading dict file '{}': {}", dict_file, e);
+                return false;
+            }
+        }
+        let dict_file_checksum = computer.checksum();
+        let schema_file_checksum = match schema_file.map(checksum) {
+            Some(Ok(schema_file_checksum)) => schema_file_checksum,
+            Some(Err(e)) => {
+                error!("error reading schema file: {}", e);
+                return false;
+            }
+            None => 0,
+        };
+
+        let build_dir = self.data_dir.join("build");
+        if let Err(e) = fs::create_dir_all(&build_dir) {
+            error!("error creating directory '{}': {}", build_dir, e);
+            return false;
+        }
+        let prism_path = build_dir.join(format!("{}.prism.bin", self.prism_name));
+        let table_path = build_dir.join(format!("{}.table.bin", self.dict_name));
+        let reverse_path = build_dir.join(format!("{}.reverse.bin", self.dict_name));
+
+        let rebuild_table = !table_up_to_date(&table_path, dict_file_checksum)
+            || !reverse_db_up_to_date(&reverse_path, dict_file_checksum);
+        // the prism is built from the syllabary of the table
+        let rebuild_prism = rebuild_table
+            || !prism_up_to_date(&prism_path, dict_file_checksum, schema_file_checksum);
+        if !rebuild_prism {
+            info!("dictionary '{}' is up to date.", self.dict_name);
+            return true;
+        }
+
+        let Some(entries) = collect_entries(&settings, &dict_files) else {
+            return false;
+        };
+        let syllabary: Syllabary = entries
+            .iter()
+            .flat_map(|entry| entry.raw_code.split(' '))
+            .map(str::to_string)
+            .collect();
+        info!(
+            "dictionary '{}': {} entries, {} syllables.",
+            self.dict_name,
+            entries.len(),
+            syllabary.len()
+        );
+        if rebuild_table
+            && !build_table(
+                table_path,
+                reverse_path,
+                &syllabary,
+                &entries,
+                dict_file_checksum,
+            )
+        {
+            error!("error building table of '{}'.", self.dict_name);
+            return false;
+        }
+        let script = schema_file.and_then(load_algebra).map(|algebra| {
+            let mut script = Script::new();
+            for syllable in &syllabary {
+                script.add_syllable(syllable);
+            }
+            algebra.apply_script(Some(&mut script));
+            script
+        });
+        let syllables: BTreeSet<&str> = syllabary.iter().map(String::as_str).collect();
+        let mut prism = Prism::new(prism_path);
+        if !prism.build_with_params(
+            &syllables,
+            script.as_ref(),
+            dict_file_checksum,
+            schema_file_checksum,
+        ) || !prism.save()
+        {
+            error!("error building prism '{}'.", self.prism_name);
+            return false;
+        }
+        true
+    }
+
+    fn dict_file(&self, dict_name: &str) -> PathExt {
+        self.data_dir.join(format!("{}.dict.yaml", dict_name))
+    }
+
+    fn load_settings(&self) -> Option<DictSettings> {
+        let dict_file = self.dict_file(&self.dict_name);
+        let file = match File::open(&dict_file) {
+            Ok(file) => file,
+            Err(e) => {
+                error!("error opening dict file '{}': {}", dict_file, e);
+                return None;
+            }
+        };
+        let mut settings = DictSettings::new();
+        if !settings.load_dict_header(&mut BufReader::new(file)) {
+            error!("invalid dict header in '{}'.", dict_file);
+            return None;
+        }
+        Some(settings)
+    }
+}
+
+fn table_up_to_date(table_path: &PathExt, dict_file_checksum: u32) -> bool {
+    if !table_path.exists() {
+        return false;
+    }
+    // the checksum is in the metadata, no need to load the whole table
+    let mut table = Table::new(table_path.clone());
+    table.load_metadata() && table.dict_file_checksum() == dict_file_checksum
+}
+
+fn reverse_db_up_to_date(reverse_path: &PathExt, dict_file_checksum: u32) -> bool {
+    let mut reverse_db = ReverseDb::new(reverse_path.clone());
+    reverse_db.exists()
+        && reverse_db.load()
+        && reverse_db.dict_file_checksum() == dict_file_checksum
+}
+
+fn prism_up_to_date(
+    prism_path: &PathExt,
+    dict_file_checksum: u32,
+    schema_file_checksum: u32,
+) -> bool {
+    if !prism_path.exists() {
+        return false;
+    }
+    let mut prism = Prism::new(prism_path.clone());
+    prism.load()
+        && prism.dict_file_checksum() == dict_file_checksum
+        && prism.schema_file_checksum() == schema_file_checksum
+}
+
+// Reads the entries of the dict files, each of which has its own header
+// telling the order of columns. A weight given in percentage is taken of the
+// total weight of the other entries of the same text, while phrases weighing
+// less than min_phrase_weight are left out.
+fn collect_entries(settings: &DictSettings, dict_files: &[PathExt]) -> Option<Vec<RawDictEntry>> {
+    let mut entries = Vec::new();
+    let mut collected = HashSet::new();
+    let mut percentages = Vec::new();
+    for dict_file in dict_files {
+        info!("collecting entries from '{}'.", dict_file);
+        let mut stream = match File::open(dict_file) {
+            Ok(file) => BufReader::new(file),
+            Err(e) => {
+                error!("error opening dict file '{}': {}", dict_file, e);
+                return None;
+            }
+        };
+        let mut file_settings = DictSettings::new();
+        if !file_settings.load_dict_header(&mut stream) {
+            error!("invalid dict header in '{}'.", dict_file);
+            return None;
+        }
+        let text_column = file_settings.get_column_index("text");
+        let code_column = file_settings.get_column_index("code");
+        let weight_column = file_settings.get_column_index("weight");
+        if text_column < 0 || code_column < 0 {
+            error!("missing text or code column in '{}'.", dict_file);
+            return None;
+        }
+        for line in stream.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    error!("error reading dict file '{}': {}", dict_file, e);
+                    return None;
+                }
+            };
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let row: Vec<&str> = line.split('\t').collect();
+            let column = |index: i32| {
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|i| row.get(i))
+                    .map_or("", |field| field.trim())
+            };
+            let text = column(text_column);
+            let raw_code = column(code_column)
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            if text.is_empty() || raw_code.is_empty() {
+                warn!("invalid entry in '{}': {}", dict_file, line);
+                continue;
+            }
+            if !collected.insert((text.to_string(), raw_code.clone())) {
+                // duplicate entry
+                continue;
+            }
+            let weight_str = column(weight_column);
+            let weight = match weight_str.strip_suffix('%') {
+                Some(percentage) => {
+                    percentages.push((entries.len(), percentage.parse::<f64>().unwrap_or(0.0)));
+                    0.0
+                }
+                None => weight_str.parse().unwrap_or(0.0),
+            };
+            entries.push(RawDictEntry {
+                text: text.to_string(),
+                raw_code,
+                weight,
+            });
+        }
+    }
+
+    let mut total_weights: HashMap<String, f64> = HashMap::new();
+    for entry in &entries {
+        *total_weights.entry(entry.text.clone()).or_default() += entry.weight;
+    }
+    for (i, percentage) in percentages {
+        let entry = &mut entries[i];
+        entry.weight = total_weights[&entry.text] * percentage / 100.0;
+    }
+    let min_phrase_weight = settings.min_phrase_weight();
+    if min_phrase_weight > 0.0 {
+        entries.retain(|entry| !entry.raw_code.contains(' ') || entry.weight >= min_phrase_weight);
+    }
+    Some(entries)
+}
+
+fn build_table(
+    table_path: PathExt,
+    reverse_path: PathExt,
+    syllabary: &Syllabary,
+    entries: &[RawDictEntry],
+    dict_file_checksum: u32,
+) -> bool {
+    let syllable_ids: HashMap<&str, SyllableId> = syllabary
+        .iter()
+        .enumerate()
+        .map(|(id, syllable)| (syllable.as_str(), id as SyllableId))
+        .collect();
+    let mut vocabulary = Vocabulary::default();
+    let mut reverse_table = ReverseLookupTable::new();
+    for entry in entries {
+        let code: Vec<SyllableId> = entry
+            .raw_code
+            .split(' ')
+            .map(|syllable| syllable_ids[syllable])
+            .collect();
+        // weights are stored in log scale
+        let weight = if entry.weight > 0.0 {
+            entry.weight
+        } else {
+            f64::EPSILON
+        };
+        vocabulary
+            .locate_entries(&Code::from(code.clone()))
+            .push(ShortDictEntry::new(&entry.text, code, weight.ln()));
+        reverse_table
+            .entry(entry.text.clone())
+            .or_default()
+            .insert(entry.raw_code.clone());
+    }
+    vocabulary.sort_homophones();
+
+    let mut table = Table::new(table_path);
+    if !table.build(syllabary, &vocabulary, entries.len(), dict_file_checksum) || !table.save() {
+        return false;
+    }
+    let mut reverse_db = ReverseDb::new(reverse_path);
+    reverse_db.build(&reverse_table, dict_file_checksum) && reverse_db.save()
+}
+
+// Loads speller/algebra from the schema file, if any.
+fn load_algebra(schema_file: &PathExt) -> Option<Projection> {
+    let mut config = Config::new();
+    if !config.load_from_file(schema_file) {
+        error!("error loading schema file '{}'.", schema_file);
+        return None;
+    }
+    let mut algebra = Projection::new();
+    algebra
+        .load(config.get_list("speller/algebra"))
+        .then_some(algebra)
+}
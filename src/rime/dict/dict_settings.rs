@@ -20,9 +20,13 @@ impl DictSettings {
     pub fn load_dict_header(&mut self, stream: &mut dyn BufRead) -> bool {
         let mut header = String::new();
         let mut line = String::new();
-        while stream.read_line(&mut line).is_ok() {
-            let line = line.trim_end().to_string();
-            header.push_str(&line);
+        loop {
+            line.clear();
+            if !matches!(stream.read_line(&mut line), Ok(n) if n > 0) {
+                break;
+            }
+            let line = line.trim_end();
+            header.push_str(line);
             header.push('\n');
             if line == "..." {
                 // Yaml doc ending
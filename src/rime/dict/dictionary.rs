@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use log::{error, warn};
+
+use crate::rime::algo::syllabifier::SyllableGraph;
+use crate::rime::common::PathExt;
+use crate::rime::dict::prism::{Match, Prism};
+use crate::rime::dict::table::{EntryList, Table, TableEntryRef};
+use crate::rime::dict::vocabulary::{Code, DictEntry};
+
+// Table weights are normalized so that 1e8 occurrences sum up to zero.
+const WEIGHT_SCALE: f64 = 18.420680743952367; // ln(1e8)
+
+// Homophones sharing the same code, found by a table query. The entries are
+// read from the table as the cursor moves, copying the text only when an
+// entry is taken.
+#[derive(Clone)]
+pub(crate) struct Chunk {
+    table: Arc<Table>,
+    code: Code,
+    entries: EntryList,
+    cursor: usize,
+    credibility: f64,
+    // length of the code beyond the input, for predictive matches
+    remaining_code_length: usize,
+}
+
+impl Chunk {
+    pub(crate) fn new(table: Arc<Table>, code: Code, entries: EntryList, credibility: f64) -> Self {
+        Self {
+            table,
+            code,
+            entries,
+            cursor: 0,
+            credibility,
+            remaining_code_length: 0,
+        }
+    }
+
+    fn head(&self) -> Option<TableEntryRef<'_>> {
+        self.table.entry_at(self.entries, self.cursor)
+    }
+
+    fn head_weight(&self) -> f64 {
+        self.head().map_or(f64::NEG_INFINITY, |entry| {
+            entry.weight as f64 + self.credibility
+        })
+    }
+}
+
+// Iterates over entries from all chunks, exact matches first, then in order
+// of weight.
+#[derive(Clone, Default)]
+pub(crate) struct DictEntryIterator {
+    chunks: Vec<Chunk>,
+    // the chunk holding the next entry
+    chunk_index: usize,
+}
+
+impl DictEntryIterator {
+    pub(crate) fn add_chunk(&mut self, chunk: Chunk) {
+        if chunk.head().is_none() {
+            return;
+        }
+        self.chunks.push(chunk);
+        self.find_next_chunk();
+    }
+
+    pub(crate) fn exhausted(&self) -> bool {
+        self.chunks
+            .get(self.chunk_index)
+            .map_or(true, |chunk| chunk.head().is_none())
+    }
+
+    pub(crate) fn entry_count(&self) -> usize {
+        self.chunks
+            .iter()
+            .map(|chunk| chunk.entries.len().saturating_sub(chunk.cursor))
+            .sum()
+    }
+
+    pub(crate) fn peek(&self) -> Option<Arc<DictEntry>> {
+        let chunk = self.chunks.get(self.chunk_index)?;
+        let entry = chunk.head()?;
+        Some(Arc::new(DictEntry {
+            text: entry.text.to_string(),
+            code: chunk.code.clone(),
+            weight: entry.weight as f64 - WEIGHT_SCALE + chunk.credibility,
+            remaining_code_length: chunk.remaining_code_length as i32,
+            ..Default::default()
+        }))
+    }
+
+    pub(crate) fn next(&mut self) -> bool {
+        let Some(chunk) = self.chunks.get_mut(self.chunk_index) else {
+            return false;
+        };
+        chunk.cursor += 1;
+        self.find_next_chunk();
+        !self.exhausted()
+    }
+
+    // Skips entries, up to num_entries.
+    pub(crate) fn skip(&mut self, num_entries: usize) -> bool {
+        for _ in 0..num_entries {
+            if !self.next() {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn find_next_chunk(&mut self) {
+        self.chunk_index = self
+            .chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| chunk.head().is_some())
+            .max_by(|(_, a), (_, b)| {
+                // exact matches go before completions
+                b.remaining_code_length
+                    .cmp(&a.remaining_code_length)
+                    .then_with(|| a.head_weight().total_cmp(&b.head_weight()))
+            })
+            .map_or(self.chunks.len(), |(i, _)| i);
+    }
+}
+
+// Entries by the end position of their code in the input.
+pub(crate) type DictEntryCollector = BTreeMap<usize, DictEntryIterator>;
+
+pub(crate) struct Dictionary {
+    name: String,
+    prism: Arc<Prism>,
+    tables: Vec<Arc<Table>>,
+}
+
+impl Dictionary {
+    pub(crate) fn new(name: &str, prism: Arc<Prism>, tables: Vec<Arc<Table>>) -> Self {
+        Self {
+            name: name.to_string(),
+            prism,
+            tables,
+        }
+    }
+
+    // Opens the prism and table compiled into "<data_dir>/build".
+    pub(crate) fn create(data_dir: &PathExt, dict_name: &str, prism_name: &str) -> Option<Self> {
+        let build_dir = data_dir.join("build");
+        let prism_path = build_dir.join(format!("{}.prism.bin", prism_name));
+        let table_path = build_dir.join(format!("{}.table.bin", dict_name));
+        if !prism_path.exists() || !table_path.exists() {
+            warn!("dictionary '{}' has not been built.", dict_name);
+            return None;
+        }
+        let mut prism = Prism::new(prism_path);
+        let mut table = Table::new(table_path);
+        if !prism.load() || !table.load() {
+            error!("failed to load dictionary '{}'.", dict_name);
+            return None;
+        }
+        Some(Self::new(dict_name, Arc::new(prism), vec![Arc::new(table)]))
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn prism(&self) -> &Prism {
+        &self.prism
+    }
+
+    pub(crate) fn tables(&self) -> &[Arc<Table>] {
+        &self.tables
+    }
+
+    pub(crate) fn loaded(&self) -> bool {
+        self.prism.trie().is_some()
+            && self
+                .tables
+                .first()
+                .is_some_and(|primary_table| primary_table.is_loaded())
+    }
+
+    // Finds entries spelled along the paths from start_pos in the syllable
+    // graph. With predict_word, phrases whose code goes beyond the input are
+    // taken as well.
+    pub(crate) fn lookup(
+        &self,
+        syllable_graph: &SyllableGraph,
+        start_pos: usize,
+        predict_word: bool,
+        initial_credibility: f64,
+    ) -> Option<DictEntryCollector> {
+        if !self.loaded() {
+            return None;
+        }
+        let mut collector = DictEntryCollector::new();
+        for table in &self.tables {
+            let Some(result) = table.query(syllable_graph, start_pos) else {
+                continue;
+            };
+            for (end_pos, accessors) in result {
+                for mut accessor in accessors {
+                    let credibility = initial_credibility + accessor.credibility();
+                    if !accessor.has_extra_code() {
+                        collector.entry(end_pos).or_default().add_chunk(Chunk::new(
+                            table.clone(),
+                            accessor.code(),
+                            accessor.entries(),
+                            credibility,
+                        ));
+                        continue;
+                    }
+                    while !accessor.exhausted() {
+                        let actual_end_pos = accessor.extra_code().map_or(0, |extra_code| {
+                            match_extra_code(&extra_code, 0, syllable_graph, end_pos, predict_word)
+                        });
+                        if actual_end_pos != 0 {
+                            collector
+                                .entry(actual_end_pos)
+                                .or_default()
+                                .add_chunk(Chunk::new(
+                                    table.clone(),
+                                    accessor.code(),
+                                    accessor.entries().take(1),
+                                    credibility,
+                                ));
+                        }
+                        accessor.next();
+                    }
+                }
+            }
+        }
+        (!collector.is_empty()).then_some(collector)
+    }
+
+    // Entries of the words spelled by the given code, or by any code it is
+    // the prefix of if predictive.
+    pub(crate) fn lookup_words(
+        &self,
+        str_code: &str,
+        predictive: bool,
+        expand_search_limit: usize,
+    ) -> Option<DictEntryIterator> {
+        if !self.loaded() {
+            return None;
+        }
+        let keys = if predictive {
+            self.prism.expand_search(str_code, expand_search_limit)?
+        } else {
+            vec![Match::from((
+                str_code.len(),
+                self.prism.get_value(str_code)?,
+            ))]
+        };
+        let mut iter = DictEntryIterator::default();
+        for key in keys {
+            let remaining_code_length = key.offset() - str_code.len();
+            for table in &self.tables {
+                if let Some(entries) = table.word_entries(key.value()) {
+                    let mut chunk =
+                        Chunk::new(table.clone(), Code::from(vec![key.value()]), entries, 0.0);
+                    chunk.remaining_code_length = remaining_code_length;
+                    iter.add_chunk(chunk);
+                }
+            }
+        }
+        (!iter.exhausted()).then_some(iter)
+    }
+}
+
+// Returns the end position in the input matched by the rest of a long code,
+// or 0 if it doesn't match.
+fn match_extra_code(
+    extra_code: &Code,
+    depth: usize,
+    syllable_graph: &SyllableGraph,
+    current_pos: usize,
+    predict_word: bool,
+) -> usize {
+    if depth >= extra_code.len() {
+        // success
+        return current_pos;
+    }
+    if current_pos >= syllable_graph.interpreted_length() {
+        // predictive (prefix) match
+        return if predict_word { current_pos } else { 0 };
+    }
+    let Some(spellings) = syllable_graph
+        .indices()
+        .get(&current_pos)
+        .and_then(|index| index.get(&extra_code[depth]))
+    else {
+        return 0;
+    };
+    spellings
+        .iter()
+        .map(|props| {
+            match_extra_code(
+                extra_code,
+                depth + 1,
+                syllable_graph,
+                props.end_pos,
+                predict_word,
+            )
+        })
+        .max()
+        .unwrap_or(0)
+}
@@ -0,0 +1,442 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::f64::consts::LN_10;
+use std::fs;
+
+use log::{error, info, warn};
+
+use crate::rime::algo::utilities::checksum;
+use crate::rime::common::PathExt;
+use crate::rime::dict::mapped_file::{replace_file, MappedFile};
+
+// The highest order of n-grams.
+pub const MAX_ORDER: usize = 4;
+
+const FORMAT: &str = "Rime::Grammar/1.0";
+const FORMAT_PREFIX: &str = "Rime::Grammar/";
+const FORMAT_LENGTH: usize = 32;
+// format, corpus_file_checksum, max_order, num_words, num_grams, grams_offset
+const METADATA_SIZE: usize = FORMAT_LENGTH + 5 * 4;
+// word ids, log probability, backoff weight
+const RECORD_SIZE: usize = MAX_ORDER * 4 + 4 + 4;
+// fills the word ids of grams below the max order
+const NO_WORD: u32 = u32::MAX;
+
+const SENTENCE_START: &str = "<s>";
+const SENTENCE_END: &str = "</s>";
+// the weight of words out of the vocabulary, below any known word
+const UNKNOWN_WEIGHT: f64 = -20.0;
+// words in the context longer than this are not recognized
+const MAX_WORD_LENGTH: usize = 8;
+// the backoff weight of grams counted in a corpus, as in "stupid backoff"
+const CORPUS_BACKOFF: f64 = -0.916290731874155; // ln(0.4)
+
+type WordIds = [u32; MAX_ORDER];
+
+// An n-gram language model, of which the log probabilities are looked up in
+// the memory-mapped file.
+//
+// Layout of the file, in little endian:
+//   metadata  the format, checksum of the source file, max order and the
+//             number of words and grams, and where the grams start
+//   words     num_words + 1 offsets into the text that follows, of the words
+//             in sorted order; words are known by their index
+//   grams     records of MAX_ORDER word ids, padded with NO_WORD, a natural
+//             log probability and a backoff weight, sorted by word ids
+pub struct GramDb {
+    mapped_file: MappedFile,
+    metadata: Metadata,
+}
+
+struct Metadata {
+    format: String,
+    corpus_file_checksum: u32,
+    max_order: u32,
+    num_words: u32,
+    num_grams: u32,
+    grams_offset: u32,
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+fn read_f32(bytes: &[u8]) -> f32 {
+    f32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+impl GramDb {
+    pub fn load(file_path: PathExt) -> Option<Self> {
+        info!("loading grammar file: {}", file_path);
+        let mapped_file = match MappedFile::open_read_only(file_path) {
+            Ok(mapped_file) => mapped_file,
+            Err(e) => {
+                error!("error opening grammar file: {}", e);
+                return None;
+            }
+        };
+        let Some(bytes) = mapped_file.read(0, METADATA_SIZE) else {
+            error!("grammar file is too small: {}", mapped_file.file_path());
+            return None;
+        };
+        let format = String::from_utf8_lossy(&bytes[..FORMAT_LENGTH])
+            .trim_end_matches('\0')
+            .to_string();
+        if !format.starts_with(FORMAT_PREFIX) {
+            error!("invalid grammar file format: {}", format);
+            return None;
+        }
+        let fields = &bytes[FORMAT_LENGTH..];
+        let metadata = Metadata {
+            format,
+            corpus_file_checksum: read_u32(&fields[0..]),
+            max_order: read_u32(&fields[4..]),
+            num_words: read_u32(&fields[8..]),
+            num_grams: read_u32(&fields[12..]),
+            grams_offset: read_u32(&fields[16..]),
+        };
+        let words_end = METADATA_SIZE + (metadata.num_words as usize + 1) * 4;
+        let grams_end = metadata.grams_offset as usize + metadata.num_grams as usize * RECORD_SIZE;
+        if metadata.max_order as usize > MAX_ORDER
+            || words_end > metadata.grams_offset as usize
+            || grams_end > mapped_file.size()
+        {
+            error!("grammar file is corrupted: {}", mapped_file.file_path());
+            return None;
+        }
+        info!(
+            "grammar loaded, format: {}, {} words, {} grams.",
+            metadata.format, metadata.num_words, metadata.num_grams
+        );
+        Some(Self {
+            mapped_file,
+            metadata,
+        })
+    }
+
+    pub fn corpus_file_checksum(&self) -> u32 {
+        self.metadata.corpus_file_checksum
+    }
+
+    pub fn max_order(&self) -> usize {
+        self.metadata.max_order as usize
+    }
+
+    fn word(&self, id: u32) -> Option<&str> {
+        let offsets = self.mapped_file.read(METADATA_SIZE + id as usize * 4, 8)?;
+        let text_offset = METADATA_SIZE + (self.metadata.num_words as usize + 1) * 4;
+        let start = read_u32(offsets) as usize;
+        let end = read_u32(&offsets[4..]) as usize;
+        let bytes = self
+            .mapped_file
+            .read(text_offset + start, end.checked_sub(start)?)?;
+        std::str::from_utf8(bytes).ok()
+    }
+
+    fn find_word(&self, word: &str) -> Option<u32> {
+        let (mut low, mut high) = (0, self.metadata.num_words);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.word(mid)?.cmp(word) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Some(mid),
+            }
+        }
+        None
+    }
+
+    fn record(&self, index: u32) -> Option<(WordIds, f64, f64)> {
+        let offset = self.metadata.grams_offset as usize + index as usize * RECORD_SIZE;
+        let bytes = self.mapped_file.read(offset, RECORD_SIZE)?;
+        let mut ids = [NO_WORD; MAX_ORDER];
+        for (i, id) in ids.iter_mut().enumerate() {
+            *id = read_u32(&bytes[i * 4..]);
+        }
+        let log_prob = read_f32(&bytes[MAX_ORDER * 4..]);
+        let backoff = read_f32(&bytes[MAX_ORDER * 4 + 4..]);
+        Some((ids, log_prob as f64, backoff as f64))
+    }
+
+    // Returns the log probability and backoff weight of the n-gram.
+    fn find_gram(&self, words: &[u32]) -> Option<(f64, f64)> {
+        if words.is_empty() || words.len() > MAX_ORDER {
+            return None;
+        }
+        let mut key = [NO_WORD; MAX_ORDER];
+        key[..words.len()].copy_from_slice(words);
+        let (mut low, mut high) = (0, self.metadata.num_grams);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let (ids, log_prob, backoff) = self.record(mid)?;
+            match ids.cmp(&key) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Some((log_prob, backoff)),
+            }
+        }
+        None
+    }
+
+    // Splits the end of the context into known words, by backward maximum
+    // matching, as many as make a history for the highest order.
+    fn history(&self, context: &str) -> Vec<u32> {
+        if self.max_order() < 2 {
+            return Vec::new();
+        }
+        if context.is_empty() {
+            return self.find_word(SENTENCE_START).into_iter().collect();
+        }
+        let chars: Vec<(usize, char)> = context.char_indices().collect();
+        let mut history = Vec::new();
+        let mut end = chars.len();
+        while end > 0 && history.len() + 1 < self.max_order() {
+            let end_byte = chars
+                .get(end)
+                .map_or(context.len(), |&(position, _)| position);
+            let found = (end.saturating_sub(MAX_WORD_LENGTH)..end).find_map(|start| {
+                let word = &context[chars[start].0..end_byte];
+                self.find_word(word).map(|id| (start, id))
+            });
+            let Some((start, id)) = found else {
+                break;
+            };
+            history.insert(0, id);
+            end = start;
+        }
+        history
+    }
+
+    // The log probability of the word following the history, backing off to
+    // shorter histories.
+    fn conditional(&self, history: &[u32], word: u32) -> f64 {
+        let mut backoff = 0.0;
+        for start in 0..=history.len() {
+            let mut gram = history[start..].to_vec();
+            gram.push(word);
+            if let Some((log_prob, _)) = self.find_gram(&gram) {
+                return backoff + log_prob;
+            }
+            if let Some((_, weight)) = self.find_gram(&history[start..]) {
+                backoff += weight;
+            }
+        }
+        UNKNOWN_WEIGHT
+    }
+
+    // The log probability of the word after the context, and of the sentence
+    // ending there if is_rear.
+    pub fn query(&self, context: &str, word: &str, is_rear: bool) -> f64 {
+        let Some(word_id) = self.find_word(word) else {
+            return UNKNOWN_WEIGHT;
+        };
+        let mut history = self.history(context);
+        let mut weight = self.conditional(&history, word_id);
+        if is_rear {
+            if let Some(end_id) = self.find_word(SENTENCE_END) {
+                history.push(word_id);
+                let excess = (history.len() + 1).saturating_sub(self.max_order());
+                weight += self.conditional(&history[excess..], end_id);
+            }
+        }
+        weight
+    }
+}
+
+// Grams read from a corpus or an ARPA file, to be saved as a GramDb.
+pub struct GramBuilder {
+    max_order: usize,
+    // log probability and backoff weight by words
+    grams: BTreeMap<Vec<String>, (f64, f64)>,
+    source_checksum: u32,
+}
+
+impl GramBuilder {
+    fn read_source(file_path: &PathExt) -> Option<(String, u32)> {
+        let text = match fs::read_to_string(file_path) {
+            Ok(text) => text,
+            Err(e) => {
+                error!("error reading '{}': {}", file_path, e);
+                return None;
+            }
+        };
+        let source_checksum = checksum(file_path).unwrap_or_default();
+        Some((text, source_checksum))
+    }
+
+    // Counts n-grams up to max_order in a corpus of sentences, one per line,
+    // of words separated by spaces.
+    pub fn from_corpus(file_path: &PathExt, max_order: usize) -> Option<Self> {
+        let (text, source_checksum) = Self::read_source(file_path)?;
+        let max_order = max_order.clamp(1, MAX_ORDER);
+        let mut counts: HashMap<Vec<&str>, u64> = HashMap::new();
+        let mut total = 0;
+        for line in text.lines() {
+            let mut sentence = vec![SENTENCE_START];
+            sentence.extend(line.split_whitespace());
+            if sentence.len() == 1 {
+                continue;
+            }
+            sentence.push(SENTENCE_END);
+            total += sentence.len() as u64 - 1;
+            for start in 0..sentence.len() {
+                for end in start + 1..=(start + max_order).min(sentence.len()) {
+                    *counts.entry(sentence[start..end].to_vec()).or_default() += 1;
+                }
+            }
+        }
+        if total == 0 {
+            error!("no sentence in corpus '{}'.", file_path);
+            return None;
+        }
+        let mut grams = BTreeMap::new();
+        for (words, &count) in &counts {
+            let history_count = match words.len() {
+                1 => total,
+                n => counts.get(&words[..n - 1]).copied().unwrap_or(count),
+            };
+            let log_prob = (count as f64 / history_count as f64).ln();
+            let words = words.iter().map(|word| word.to_string()).collect();
+            grams.insert(words, (log_prob, CORPUS_BACKOFF));
+        }
+        Some(Self {
+            max_order,
+            grams,
+            source_checksum,
+        })
+    }
+
+    // Reads a language model in the ARPA format, of which the base 10 logs
+    // are converted to natural logs.
+    pub fn from_arpa(file_path: &PathExt) -> Option<Self> {
+        let (text, source_checksum) = Self::read_source(file_path)?;
+        let mut max_order = 0;
+        let mut order = 0;
+        let mut grams = BTreeMap::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line == "\\data\\" || line == "\\end\\" {
+                continue;
+            }
+            if let Some(section) = line
+                .strip_prefix('\\')
+                .and_then(|line| line.strip_suffix("-grams:"))
+            {
+                order = section.parse().unwrap_or(0);
+                if order > MAX_ORDER {
+                    warn!("ignoring {}-grams above the max order.", order);
+                }
+                continue;
+            }
+            if order == 0 || order > MAX_ORDER {
+                // counts in the data section
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < order + 1 {
+                warn!("invalid {}-gram: {}", order, line);
+                continue;
+            }
+            let Ok(log_prob) = fields[0].parse::<f64>() else {
+                warn!("invalid {}-gram: {}", order, line);
+                continue;
+            };
+            let backoff = fields
+                .get(order + 1)
+                .and_then(|backoff| backoff.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            let words = fields[1..=order]
+                .iter()
+                .map(|word| word.to_string())
+                .collect();
+            grams.insert(words, (log_prob * LN_10, backoff * LN_10));
+            max_order = max_order.max(order);
+        }
+        if grams.is_empty() {
+            error!("no n-gram in '{}'.", file_path);
+            return None;
+        }
+        Some(Self {
+            max_order,
+            grams,
+            source_checksum,
+        })
+    }
+
+    pub fn max_order(&self) -> usize {
+        self.max_order
+    }
+
+    pub fn source_checksum(&self) -> u32 {
+        self.source_checksum
+    }
+
+    pub fn save(&self, file_path: PathExt) -> bool {
+        info!("saving grammar file: {}", file_path);
+        let vocabulary: BTreeSet<&str> = self
+            .grams
+            .keys()
+            .flatten()
+            .map(|word| word.as_str())
+            .collect();
+        let word_ids: HashMap<&str, u32> = vocabulary
+            .iter()
+            .enumerate()
+            .map(|(id, &word)| (word, id as u32))
+            .collect();
+
+        let mut bytes = Vec::new();
+        let mut format = FORMAT.as_bytes().to_vec();
+        format.resize(FORMAT_LENGTH, 0);
+        bytes.extend(format);
+        let text_length: usize = vocabulary.iter().map(|word| word.len()).sum();
+        let words_end = METADATA_SIZE + (vocabulary.len() + 1) * 4 + text_length;
+        let grams_offset = words_end.next_multiple_of(4);
+        for field in [
+            self.source_checksum,
+            self.max_order as u32,
+            vocabulary.len() as u32,
+            self.grams.len() as u32,
+            grams_offset as u32,
+        ] {
+            bytes.extend(field.to_le_bytes());
+        }
+
+        let mut offset = 0;
+        bytes.extend((offset as u32).to_le_bytes());
+        for word in &vocabulary {
+            offset += word.len();
+            bytes.extend((offset as u32).to_le_bytes());
+        }
+        for word in &vocabulary {
+            bytes.extend(word.as_bytes());
+        }
+        bytes.resize(grams_offset, 0);
+
+        let mut records: Vec<(WordIds, f64, f64)> = self
+            .grams
+            .iter()
+            .map(|(words, &(log_prob, backoff))| {
+                let mut ids = [NO_WORD; MAX_ORDER];
+                for (id, word) in ids.iter_mut().zip(words) {
+                    *id = word_ids[word.as_str()];
+                }
+                (ids, log_prob, backoff)
+            })
+            .collect();
+        records.sort_by_key(|&(ids, _, _)| ids);
+        for (ids, log_prob, backoff) in records {
+            for id in ids {
+                bytes.extend(id.to_le_bytes());
+            }
+            bytes.extend((log_prob as f32).to_le_bytes());
+            bytes.extend((backoff as f32).to_le_bytes());
+        }
+
+        if let Err(e) = replace_file(&file_path, &bytes) {
+            error!("error saving grammar file: {}", e);
+            return false;
+        }
+        true
+    }
+}
@@ -1,5 +1,5 @@
 use memmap2::{MmapMut, MmapOptions};
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{Error, ErrorKind, Result};
 
 use crate::rime::common::PathExt;
@@ -20,11 +20,26 @@ impl MappedFile {
             .open(&file_path)?;
 
         let mmap = unsafe { MmapOptions::new().map_mut(&file)? };
+        let size = mmap.len();
         Ok(Self {
             file_path,
             file,
             mmap: Some(mmap),
-            size: 0,
+            size,
+        })
+    }
+
+    // Maps an existing file, which is left intact whatever is written to the
+    // mapping.
+    pub(crate) fn open_read_only(file_path: PathExt) -> Result<Self> {
+        let file = File::open(&file_path)?;
+        let mmap = unsafe { MmapOptions::new().map_copy(&file)? };
+        let size = mmap.len();
+        Ok(Self {
+            file_path,
+            file,
+            mmap: Some(mmap),
+            size,
         })
     }
 
@@ -32,26 +47,28 @@ impl MappedFile {
         &self.file_path
     }
 
-    fn size(&self) -> usize {
+    pub(crate) fn size(&self) -> usize {
         self.size
     }
 
-    fn read(&self, offset: usize, length: usize) -> Option<&[u8]> {
+    pub(crate) fn read(&self, offset: usize, length: usize) -> Option<&[u8]> {
         match &self.mmap {
             Some(mmap) if offset + length <= self.size => Some(&mmap[offset..offset + length]),
             _ => None,
         }
     }
 
-    fn write(&mut self, offset: usize, data: &[u8]) -> Result<()> {
-        let size = data.len();
+    // Writes within the file as it is; files are rewritten as a whole by
+    // replace_file.
+    pub(crate) fn write(&mut self, offset: usize, data: &[u8]) -> Result<()> {
+        let end = offset + data.len();
         match &mut self.mmap {
-            Some(mmap) /*if offset + size <= self.size*/ => {
-                mmap/*[offset..offset + data.len()]*/.copy_from_slice(data);
+            Some(mmap) if end <= self.size => {
+                mmap[offset..end].copy_from_slice(data);
                 mmap.flush()?;
                 Ok(())
             }
-            // Some(_) => Err(Error::new(ErrorKind::InvalidInput, "Write out of bounds")),
+            Some(_) => Err(Error::new(ErrorKind::InvalidInput, "Write out of bounds")),
             None => Err(Error::new(
                 ErrorKind::Other,
                 "Memory map is not initialized",
@@ -60,9 +77,57 @@ impl MappedFile {
     }
 }
 
+// Writes the bytes to a temporary file next to the given one, then renames it
+// over the old file. Engines that have the old file mapped keep reading it
+// intact, rather than having it truncated under them.
+pub(crate) fn replace_file(file_path: &PathExt, bytes: &[u8]) -> Result<()> {
+    let temp_path = PathExt::new(format!("{}.tmp", file_path));
+    let result = fs::write(&temp_path, bytes).and_then(|_| fs::rename(&temp_path, file_path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+// Reads little-endian fields one after another from a byte slice.
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    pub(crate) fn take(&mut self, length: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.offset..self.offset + length)?;
+        self.offset += length;
+        Some(bytes)
+    }
+
+    pub(crate) fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    pub(crate) fn i32(&mut self) -> Option<i32> {
+        Some(i32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    pub(crate) fn f32(&mut self) -> Option<f32> {
+        Some(f32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    pub(crate) fn string(&mut self) -> Option<String> {
+        let length = self.u32()? as usize;
+        String::from_utf8(self.take(length)?.to_vec()).ok()
+    }
+}
+
 #[test]
 fn test() -> Result<()> {
     let file_path = PathExt::new("example.txt");
+    replace_file(&file_path, &[0; 12])?;
     // 创建一个映射文件
     let mut mapped_file = MappedFile::new(file_path)?;
 
@@ -10,7 +10,7 @@ use crate::rime::algo::algebra::Script;
 use crate::rime::algo::spelling::{SpellingProperties, SpellingType};
 use crate::rime::algo::SyllableId;
 use crate::rime::common::PathExt;
-use crate::rime::dict::mapped_file::MappedFile;
+use crate::rime::dict::mapped_file::{replace_file, MappedFile, Reader};
 
 static DEFAULT_ALPHABET: LazyLock<[char; 26]> = LazyLock::new(|| {
     "abcdefghijklmnopqrstuvwxyz"
@@ -20,6 +20,10 @@ static DEFAULT_ALPHABET: LazyLock<[char; 26]> = LazyLock::new(|| {
         .unwrap()
 });
 
+const FORMAT: &str = "Rime::Prism/3.0";
+const FORMAT_PREFIX: &str = "Rime::Prism/";
+const FORMAT_LENGTH: usize = 32;
+
 type Credibility = f32;
 
 type SpellingMapItem = Vec<SpellingDescriptor>;
@@ -63,6 +67,8 @@ struct Metadata {
 pub struct Prism {
     mapped_file: MappedFile,
     trie: Option<DoubleArrayTrie>,
+    // keys of the trie in order, by which the trie is rebuilt on loading
+    keys: Option<Vec<String>>,
     metadata: Option<Metadata>,
     spelling_map: Option<SpellingMap>,
     format: f64,
@@ -85,6 +91,7 @@ impl Prism {
         Self {
             mapped_file: MappedFile::new(file_path).unwrap(),
             trie: None,
+            keys: None,
             metadata: None,
             spelling_map: None,
             format: 0.0,
@@ -173,10 +180,91 @@ impl Prism {
             error!("the trie has not been constructed!");
             return false;
         }
+        let (Some(metadata), Some(keys)) = (&self.metadata, &self.keys) else {
+            error!("the prism has not been built!");
+            return false;
+        };
 
-        //self.shrink_to_fit()
+        let mut bytes = Vec::new();
+        let mut format = metadata.format.as_bytes().to_vec();
+        format.resize(FORMAT_LENGTH, 0);
+        bytes.extend(format);
+        for field in [
+            metadata.dict_file_checksum,
+            metadata.schema_file_checksum,
+            metadata.num_syllables,
+            metadata.num_spellings,
+        ] {
+            bytes.extend(field.to_le_bytes());
+        }
+        let alphabet: String = metadata
+            .alphabet
+            .iter()
+            .take_while(|&&c| c != '\0')
+            .collect();
+        write_string(&mut bytes, &alphabet);
+        for key in keys {
+            write_string(&mut bytes, key);
+        }
+        let spelling_map = self.spelling_map.as_deref().unwrap_or_default();
+        bytes.extend((spelling_map.len() as u32).to_le_bytes());
+        for descriptors in spelling_map {
+            bytes.extend((descriptors.len() as u32).to_le_bytes());
+            for desc in descriptors {
+                bytes.extend(desc.syllable_id.to_le_bytes());
+                bytes.extend(desc.type_.to_le_bytes());
+                bytes.extend(desc.credibility.to_le_bytes());
+                write_string(&mut bytes, &desc.tips);
+            }
+        }
 
-        todo!()
+        if let Err(e) = replace_file(self.file_path(), &bytes) {
+            error!("error saving prism file: {}", e);
+            return false;
+        }
+        true
+    }
+
+    // Maps the prism file and rebuilds the trie from the keys saved in it.
+    pub fn load(&mut self) -> bool {
+        info!("loading prism file: {}", self.file_path());
+        match MappedFile::open_read_only(self.file_path().clone()) {
+            Ok(mapped_file) => self.mapped_file = mapped_file,
+            Err(e) => {
+                error!("error opening prism file: {}", e);
+                return false;
+            }
+        }
+        let Some(bytes) = self.mapped_file.read(0, self.mapped_file.size()) else {
+            return false;
+        };
+        let Some((metadata, keys, spelling_map)) = parse(bytes) else {
+            error!("invalid prism file: {}", self.file_path());
+            return false;
+        };
+        self.format = metadata
+            .format
+            .strip_prefix(FORMAT_PREFIX)
+            .and_then(|version| version.parse().ok())
+            .unwrap_or_default();
+        let key_refs: Vec<&str> = keys.iter().map(String::as_str).collect();
+        self.trie = Some(DoubleArrayTrieBuilder::new().build(&key_refs));
+        self.metadata = Some(metadata);
+        self.keys = Some(keys);
+        self.spelling_map = (!spelling_map.is_empty()).then_some(spelling_map);
+        true
+    }
+
+    pub fn dict_file_checksum(&self) -> u32 {
+        self.metadata
+            .as_ref()
+            .map_or(0, |metadata| metadata.dict_file_checksum)
+    }
+
+    pub fn schema_file_checksum(&self) -> u32 {
+        self.metadata
+            .as_ref()
+            .map_or(0, |metadata| metadata.schema_file_checksum)
     }
 
     pub fn has_key(&self, key: &str) -> bool {
@@ -191,7 +279,7 @@ impl Prism {
         self.build_with_params(syllabary, None, 0, 0)
     }
 
-    fn build_with_params(
+    pub(crate) fn build_with_params(
         &mut self,
         syllabary: &BTreeSet<&str>,
         script: Option<&Script>,
@@ -219,6 +307,7 @@ impl Prism {
         }
 
         self.trie = Some(DoubleArrayTrieBuilder::new().build(&keys));
+        self.keys = Some(keys.iter().map(|key| key.to_string()).collect());
 
         // alphabet
         let mut alphabet_set = BTreeSet::new();
@@ -242,7 +331,7 @@ impl Prism {
             num_syllables: num_syllables as u32,
             num_spellings: num_spellings as u32,
             alphabet,
-            format: "Rime::Prism/3.0".to_string(),
+            format: FORMAT.to_string(),
         };
         self.metadata = Some(metadata);
 
@@ -277,6 +366,60 @@ impl Prism {
     }
 }
 
+// Layout of the prism file, in little endian: the format, checksums of the
+// source files, num_syllables and num_spellings, then the alphabet, the keys
+// of the trie in order and the spelling map, each string prefixed with its
+// length.
+fn parse(bytes: &[u8]) -> Option<(Metadata, Vec<String>, SpellingMap)> {
+    let mut reader = Reader::new(bytes);
+    let format = String::from_utf8_lossy(reader.take(FORMAT_LENGTH)?)
+        .trim_end_matches('\0')
+        .to_string();
+    if !format.starts_with(FORMAT_PREFIX) {
+        return None;
+    }
+    let dict_file_checksum = reader.u32()?;
+    let schema_file_checksum = reader.u32()?;
+    let num_syllables = reader.u32()?;
+    let num_spellings = reader.u32()?;
+    let mut alphabet = ['\0'; 256];
+    for (i, c) in reader.string()?.chars().take(alphabet.len()).enumerate() {
+        alphabet[i] = c;
+    }
+    let keys = (0..num_spellings)
+        .map(|_| reader.string())
+        .collect::<Option<Vec<_>>>()?;
+    let spelling_map_size = reader.u32()?;
+    let mut spelling_map = Vec::with_capacity(spelling_map_size as usize);
+    for _ in 0..spelling_map_size {
+        let num_descriptors = reader.u32()?;
+        let mut descriptors = Vec::with_capacity(num_descriptors as usize);
+        for _ in 0..num_descriptors {
+            descriptors.push(SpellingDescriptor {
+                syllable_id: reader.i32()?,
+                type_: reader.i32()?,
+                credibility: reader.f32()?,
+                tips: reader.string()?,
+            });
+        }
+        spelling_map.push(descriptors);
+    }
+    let metadata = Metadata {
+        format,
+        dict_file_checksum,
+        schema_file_checksum,
+        num_syllables,
+        num_spellings,
+        alphabet,
+    };
+    Some((metadata, keys, spelling_map))
+}
+
+fn write_string(bytes: &mut Vec<u8>, s: &str) {
+    bytes.extend((s.len() as u32).to_le_bytes());
+    bytes.extend(s.as_bytes());
+}
+
 #[derive(Debug)]
 pub struct Match {
     pub(crate) value: SyllableId,
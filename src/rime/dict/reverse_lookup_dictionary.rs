@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use itertools::Itertools;
+use log::{error, info};
+
+use crate::rime::common::PathExt;
+use crate::rime::dict::mapped_file::{replace_file, Reader};
+use crate::rime::dict::vocabulary::ReverseLookupTable;
+
+const REVERSE_FORMAT: &[u8; 16] = b"Rime::Reverse/4\0";
+
+// Codes of each text in a dictionary, separated by spaces.
+//
+// Layout: format (16 bytes), dict_file_checksum (u32), num_entries (u32),
+// then for each entry sorted by text: text length (u32), text, codes length
+// (u32), codes. Integers are little-endian.
+pub(crate) struct ReverseDb {
+    file_path: PathExt,
+    dict_file_checksum: u32,
+    entries: BTreeMap<String, String>,
+    loaded: bool,
+}
+
+impl ReverseDb {
+    pub(crate) fn new(file_path: PathExt) -> Self {
+        Self {
+            file_path,
+            dict_file_checksum: 0,
+            entries: BTreeMap::new(),
+            loaded: false,
+        }
+    }
+
+    pub(crate) fn file_path(&self) -> &PathExt {
+        &self.file_path
+    }
+
+    pub(crate) fn exists(&self) -> bool {
+        self.file_path.exists()
+    }
+
+    pub(crate) fn loaded(&self) -> bool {
+        self.loaded
+    }
+
+    pub(crate) fn dict_file_checksum(&self) -> u32 {
+        self.dict_file_checksum
+    }
+
+    pub(crate) fn load(&mut self) -> bool {
+        info!("loading reverse db: {}", self.file_path);
+        let data = match fs::read(&self.file_path) {
+            Ok(data) => data,
+            Err(e) => {
+                error!("error opening reverse db '{}': {}", self.file_path, e);
+                return false;
+            }
+        };
+        match parse(&data) {
+            Some((dict_file_checksum, entries)) => {
+                self.dict_file_checksum = dict_file_checksum;
+                self.entries = entries;
+                self.loaded = true;
+                true
+            }
+            None => {
+                error!("invalid reverse db: {}", self.file_path);
+                false
+            }
+        }
+    }
+
+    pub(crate) fn build(&mut self, table: &ReverseLookupTable, dict_file_checksum: u32) -> bool {
+        self.entries = table
+            .iter()
+            .map(|(text, codes)| (text.clone(), codes.iter().sorted().join(" ")))
+            .collect();
+        self.dict_file_checksum = dict_file_checksum;
+        self.loaded = true;
+        true
+    }
+
+    pub(crate) fn save(&self) -> bool {
+        info!("saving reverse db: {}", self.file_path);
+        let mut data = Vec::new();
+        data.extend_from_slice(REVERSE_FORMAT);
+        data.extend_from_slice(&self.dict_file_checksum.to_le_bytes());
+        data.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for (text, codes) in &self.entries {
+            for field in [text, codes] {
+                data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+                data.extend_from_slice(field.as_bytes());
+            }
+        }
+        match replace_file(&self.file_path, &data) {
+            Ok(()) => true,
+            Err(e) => {
+                error!("error saving reverse db '{}': {}", self.file_path, e);
+                false
+            }
+        }
+    }
+
+    pub(crate) fn lookup(&self, text: &str) -> Option<&str> {
+        self.entries.get(text).map(String::as_str)
+    }
+}
+
+fn parse(data: &[u8]) -> Option<(u32, BTreeMap<String, String>)> {
+    let mut reader = Reader::new(data);
+    if reader.take(REVERSE_FORMAT.len())? != REVERSE_FORMAT {
+        return None;
+    }
+    let dict_file_checksum = reader.u32()?;
+    let num_entries = reader.u32()?;
+    let mut entries = BTreeMap::new();
+    for _ in 0..num_entries {
+        let text = reader.string()?;
+        let codes = reader.string()?;
+        entries.insert(text, codes);
+    }
+    Some((dict_file_checksum, entries))
+}
+
+// Finds the codes of a text in the dictionary it is compiled from.
+pub(crate) struct ReverseLookupDictionary {
+    db: ReverseDb,
+}
+
+impl ReverseLookupDictionary {
+    // Opens "<data_dir>/build/<dict_name>.reverse.bin".
+    pub(crate) fn create(data_dir: &PathExt, dict_name: &str) -> Option<Self> {
+        let file_path = data_dir
+            .join("build")
+            .join(format!("{}.reverse.bin", dict_name));
+        let mut db = ReverseDb::new(file_path);
+        if !db.exists() || !db.load() {
+            error!("reverse db of '{}' not available.", dict_name);
+            return None;
+        }
+        Some(Self { db })
+    }
+
+    pub(crate) fn reverse_lookup(&self, text: &str) -> Option<&str> {
+        self.db.lookup(text)
+    }
+}
@@ -1,99 +1,658 @@
-use log::info;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::File;
+use std::io::Read;
 
+use log::{error, info};
+
+use crate::rime::algo::syllabifier::SyllableGraph;
 use crate::rime::algo::SyllableId;
 use crate::rime::common::PathExt;
-use crate::rime::dict::vocabulary::{Syllabary as SyllSet, Vocabulary};
+use crate::rime::dict::mapped_file::{replace_file, MappedFile};
+use crate::rime::dict::vocabulary::{Code, ShortDictEntryList, Syllabary as SyllSet, Vocabulary};
+
+const FORMAT: &str = "Rime::Table/4.0";
+const FORMAT_PREFIX: &str = "Rime::Table/";
+const FORMAT_LENGTH: usize = 32;
+// format, dict_file_checksum, num_syllables, num_entries, syllabary_offset,
+// index_offset, strings_offset
+const METADATA_SIZE: usize = FORMAT_LENGTH + 6 * 4;
+// text offset and length, weight
+const ENTRY_SIZE: usize = 4 * 3;
+// size and offset of the entries, offset of the next level
+const HEAD_NODE_SIZE: usize = 4 * 3;
+// key, size and offset of the entries, offset of the next level
+const TRUNK_NODE_SIZE: usize = 4 * 4;
+// size and offset of the extra code, the entry
+const LONG_ENTRY_SIZE: usize = 4 * 2 + ENTRY_SIZE;
+
+type Syllabary = Vec<String>;
+
+pub type Weight = f32;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableEntry {
+    pub text: String,
+    pub weight: Weight,
+}
+
+// An entry read in place, its text borrowed from the mapped table file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TableEntryRef<'a> {
+    pub text: &'a str,
+    pub weight: Weight,
+}
+
+#[derive(Clone, Debug)]
+struct LongEntry {
+    extra_code: Code,
+    entry: TableEntry,
+}
+
+// Entries with codes of up to INDEX_CODE_MAX_LENGTH syllables are indexed by
+// syllable, level by level; longer ones are kept in the tail along with the
+// rest of their codes.
+#[derive(Clone, Debug, Default)]
+struct HeadIndexNode {
+    entries: Vec<TableEntry>,
+    next_level: Option<TrunkIndex>,
+}
+
+type HeadIndex = Vec<HeadIndexNode>;
 
 #[derive(Clone, Debug)]
-pub struct StringType {
-    value: i32,
+struct TrunkIndexNode {
+    key: SyllableId,
+    entries: Vec<TableEntry>,
+    next_level: Option<PhraseIndex>,
+}
+
+type TrunkIndex = Vec<TrunkIndexNode>;
+type TailIndex = Vec<LongEntry>;
+
+#[derive(Clone, Debug)]
+enum PhraseIndex {
+    Trunk(TrunkIndex),
+    Tail(TailIndex),
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+// Where a list of entries lies in the table file. Entries of the tail index
+// are spaced out by the extra codes in front of them.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct EntryList {
+    offset: usize,
+    size: usize,
+    stride: usize,
+}
+
+impl EntryList {
+    pub(crate) fn len(&self) -> usize {
+        self.size
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    // The entries after the first n.
+    pub(crate) fn skip(self, n: usize) -> Self {
+        let n = n.min(self.size);
+        Self {
+            offset: self.offset + n * self.stride,
+            size: self.size - n,
+            ..self
+        }
+    }
+
+    // The first n entries.
+    pub(crate) fn take(self, n: usize) -> Self {
+        Self {
+            size: self.size.min(n),
+            ..self
+        }
+    }
+
+    fn offset_of(&self, i: usize) -> Option<usize> {
+        (i < self.size).then(|| self.offset + i * self.stride)
+    }
+
+    fn in_tail(&self) -> bool {
+        self.stride == LONG_ENTRY_SIZE
+    }
+}
+
+// The table file mapped in memory, read in place.
+#[derive(Clone, Copy)]
+struct TableImage<'a> {
+    bytes: &'a [u8],
+    strings_offset: usize,
+}
+
+impl<'a> TableImage<'a> {
+    fn u32_at(&self, offset: usize) -> Option<u32> {
+        read_u32(self.bytes, offset)
+    }
+
+    // The text referred to by its offset and length at the offset.
+    fn string_at(&self, offset: usize) -> Option<&'a str> {
+        let start = self.strings_offset + self.u32_at(offset)? as usize;
+        let length = self.u32_at(offset + 4)? as usize;
+        std::str::from_utf8(self.bytes.get(start..start + length)?).ok()
+    }
+
+    fn entry_at(&self, offset: usize) -> Option<TableEntryRef<'a>> {
+        Some(TableEntryRef {
+            text: self.string_at(offset)?,
+            weight: f32::from_bits(self.u32_at(offset + 8)?),
+        })
+    }
+
+    // The list of entries of which the size and offset are at the offset.
+    fn entries_at(&self, offset: usize) -> EntryList {
+        EntryList {
+            offset: self.u32_at(offset + 4).unwrap_or_default() as usize,
+            size: self.u32_at(offset).unwrap_or_default() as usize,
+            stride: ENTRY_SIZE,
+        }
+    }
+
+    fn entries(self, list: EntryList) -> impl Iterator<Item = TableEntryRef<'a>> {
+        (0..list.size).map_while(move |i| self.entry_at(list.offset_of(i)?))
+    }
+
+    // The rest of the code of a tail entry, stored in front of the entry.
+    fn extra_code_at(&self, entry_offset: usize) -> Option<Code> {
+        let offset = entry_offset.checked_sub(8)?;
+        let code_size = self.u32_at(offset)? as usize;
+        let code_offset = self.u32_at(offset + 4)? as usize;
+        let extra_code = (0..code_size)
+            .map(|j| {
+                self.u32_at(code_offset + j * 4)
+                    .map(|syllable_id| syllable_id as SyllableId)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Code::from(extra_code))
+    }
+
+    fn long_entries(self, list: EntryList) -> impl Iterator<Item = (Code, TableEntryRef<'a>)> {
+        (0..list.size).map_while(move |i| {
+            let offset = list.offset_of(i)?;
+            Some((self.extra_code_at(offset)?, self.entry_at(offset)?))
+        })
+    }
+}
+
+// A node of the head or trunk index.
+#[derive(Clone, Copy)]
+struct IndexNodeView<'a> {
+    image: TableImage<'a>,
+    entries_offset: usize,
+    next_level: usize,
+}
+
+impl<'a> IndexNodeView<'a> {
+    fn entries(&self) -> EntryList {
+        self.image.entries_at(self.entries_offset)
+    }
+
+    fn next_trunk(&self) -> Option<TrunkIndexView<'a>> {
+        (self.next_level != 0).then_some(TrunkIndexView {
+            image: self.image,
+            offset: self.next_level,
+        })
+    }
+
+    fn next_tail(&self) -> Option<TailIndexView<'a>> {
+        (self.next_level != 0).then_some(TailIndexView {
+            image: self.image,
+            offset: self.next_level,
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+struct HeadIndexView<'a> {
+    image: TableImage<'a>,
+    offset: usize,
+    size: usize,
+}
+
+impl<'a> HeadIndexView<'a> {
+    fn node(&self, syllable_id: SyllableId) -> Option<IndexNodeView<'a>> {
+        let i = usize::try_from(syllable_id)
+            .ok()
+            .filter(|&i| i < self.size)?;
+        let offset = self.offset + i * HEAD_NODE_SIZE;
+        Some(IndexNodeView {
+            image: self.image,
+            entries_offset: offset,
+            next_level: self.image.u32_at(offset + 8)? as usize,
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+struct TrunkIndexView<'a> {
+    image: TableImage<'a>,
+    offset: usize,
+}
+
+impl<'a> TrunkIndexView<'a> {
+    fn size(&self) -> usize {
+        self.image.u32_at(self.offset).unwrap_or_default() as usize
+    }
+
+    fn key_at(&self, i: usize) -> Option<SyllableId> {
+        let offset = self.offset + 4 + i * TRUNK_NODE_SIZE;
+        Some(self.image.u32_at(offset)? as SyllableId)
+    }
+
+    fn node_at(&self, i: usize) -> Option<IndexNodeView<'a>> {
+        let offset = self.offset + 4 + i * TRUNK_NODE_SIZE;
+        Some(IndexNodeView {
+            image: self.image,
+            entries_offset: offset + 4,
+            next_level: self.image.u32_at(offset + 12)? as usize,
+        })
+    }
+
+    // Nodes are sorted by key.
+    fn find(&self, key: SyllableId) -> Option<IndexNodeView<'a>> {
+        let (mut low, mut high) = (0, self.size());
+        while low < high {
+            let middle = (low + high) / 2;
+            match self.key_at(middle)?.cmp(&key) {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => return self.node_at(middle),
+            }
+        }
+        None
+    }
+}
+
+#[derive(Clone, Copy)]
+struct TailIndexView<'a> {
+    image: TableImage<'a>,
+    offset: usize,
+}
+
+impl TailIndexView<'_> {
+    fn entries(&self) -> EntryList {
+        EntryList {
+            offset: self.offset + 4 + 8,
+            size: self.image.u32_at(self.offset).unwrap_or_default() as usize,
+            stride: LONG_ENTRY_SIZE,
+        }
+    }
+}
+
+// Entries found by a table query, sharing the index code, read in place as
+// the cursor moves. Entries from the tail index come with the extra code to be
+// matched against the input.
+#[derive(Clone)]
+pub(crate) struct TableAccessor<'a> {
+    image: TableImage<'a>,
+    index_code: Code,
+    entries: EntryList,
+    cursor: usize,
+    credibility: f64,
 }
 
-//impl StringType {
-//    pub fn str(&self) -> String {
-//        self.value.to_string()
-//    }
+impl<'a> TableAccessor<'a> {
+    fn new(image: TableImage<'a>, index_code: Code, entries: EntryList, credibility: f64) -> Self {
+        Self {
+            image,
+            index_code,
+            entries,
+            cursor: 0,
+            credibility,
+        }
+    }
+
+    pub(crate) fn exhausted(&self) -> bool {
+        self.cursor >= self.entries.len()
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.entries.len().saturating_sub(self.cursor)
+    }
+
+    pub(crate) fn entry(&self) -> Option<TableEntryRef<'a>> {
+        self.image.entry_at(self.entries.offset_of(self.cursor)?)
+    }
+
+    // Entries left, starting from the current one.
+    pub(crate) fn entries(&self) -> EntryList {
+        self.entries.skip(self.cursor)
+    }
+
+    pub(crate) fn index_code(&self) -> &Code {
+        &self.index_code
+    }
 
-//    pub fn str_id(&self) -> u32 {
-//        self.value as u32
-//    }
-//}
+    pub(crate) fn has_extra_code(&self) -> bool {
+        self.entries.in_tail()
+    }
 
-type Syllabary = Vec<StringType>;
+    pub(crate) fn extra_code(&self) -> Option<Code> {
+        if !self.has_extra_code() {
+            return None;
+        }
+        self.image
+            .extra_code_at(self.entries.offset_of(self.cursor)?)
+    }
 
-//pub type Code = Vec<SyllableId>;
+    // The full code of the current entry.
+    pub(crate) fn code(&self) -> Code {
+        let mut code = self.index_code.clone();
+        if let Some(extra_code) = self.extra_code() {
+            code.extend(extra_code.iter());
+        }
+        code
+    }
 
-//pub type Weight = f32;
+    pub(crate) fn credibility(&self) -> f64 {
+        self.credibility
+    }
 
-//#[derive(Clone, Debug)]
-//pub struct Entry {
-//    pub text: StringType,
-//    pub weight: Weight,
-//}
+    pub(crate) fn next(&mut self) -> bool {
+        if self.exhausted() {
+            return false;
+        }
+        self.cursor += 1;
+        !self.exhausted()
+    }
+}
 
-//#[derive(Clone, Debug)]
-//pub struct LongEntry {
-//    pub extra_code: Code,
-//    pub entry: Entry,
-//}
+// Accessors found by a table query, by the end position in the input.
+pub(crate) type TableQueryResult<'a> = BTreeMap<usize, Vec<TableAccessor<'a>>>;
 
-//#[derive(Clone, Debug)]
-//pub struct HeadIndexNode {
-//    pub entries: Vec<Entry>,
-//    pub next_level: Option<PhraseIndex>,
-//}
+// Walks down the index levels following the syllables of a code.
+#[derive(Clone)]
+struct TableQuery<'a> {
+    level: usize,
+    index_code: Code,
+    credibility: Vec<f64>,
+    lv1_index: HeadIndexView<'a>,
+    lv2_index: Option<TrunkIndexView<'a>>,
+    lv3_index: Option<TrunkIndexView<'a>>,
+    lv4_index: Option<TailIndexView<'a>>,
+}
 
-//pub type HeadIndex = Vec<HeadIndexNode>;
+impl<'a> TableQuery<'a> {
+    fn new(index: HeadIndexView<'a>) -> Self {
+        Self {
+            level: 0,
+            index_code: Code::default(),
+            credibility: vec![0.0],
+            lv1_index: index,
+            lv2_index: None,
+            lv3_index: None,
+            lv4_index: None,
+        }
+    }
 
-//#[derive(Clone, Debug)]
-//pub struct TrunkIndexNode {
-//    pub key: SyllableId,
-//    pub entries: Vec<Entry>,
-//    pub next_level: Option<PhraseIndex>,
-//}
+    fn access(&self, syllable_id: SyllableId, credibility: f64) -> Option<TableAccessor<'a>> {
+        let credibility = credibility + self.credibility.last().copied().unwrap_or_default();
+        let mut code = self.index_code.clone();
+        code.push(syllable_id);
+        match self.level {
+            0 => {
+                let node = self.lv1_index.node(syllable_id)?;
+                Some(TableAccessor::new(
+                    node.image,
+                    code,
+                    node.entries(),
+                    credibility,
+                ))
+            }
+            1 | 2 => {
+                let index = if self.level == 1 {
+                    self.lv2_index?
+                } else {
+                    self.lv3_index?
+                };
+                let node = index.find(syllable_id)?;
+                Some(TableAccessor::new(
+                    node.image,
+                    code,
+                    node.entries(),
+                    credibility,
+                ))
+            }
+            _ => {
+                let tail = self.lv4_index?;
+                Some(TableAccessor::new(
+                    tail.image,
+                    self.index_code.clone(),
+                    tail.entries(),
+                    credibility,
+                ))
+            }
+        }
+    }
 
-//pub type TrunkIndex = Vec<TrunkIndexNode>;
-//pub type TailIndex = Vec<LongEntry>;
+    fn advance(&mut self, syllable_id: SyllableId, credibility: f64) -> bool {
+        if !self.walk(syllable_id) {
+            return false;
+        }
+        self.level += 1;
+        self.index_code.push(syllable_id);
+        let total = self.credibility.last().copied().unwrap_or_default() + credibility;
+        self.credibility.push(total);
+        true
+    }
 
-//#[derive(Clone, Debug)]
-//pub enum PhraseIndex {
-//    Trunk(TrunkIndex),
-//    Tail(TailIndex),
-//}
+    fn backdate(&mut self) -> bool {
+        if self.level == 0 {
+            return false;
+        }
+        self.level -= 1;
+        self.index_code.truncate(self.level);
+        self.credibility.pop();
+        true
+    }
 
-//pub type Index = HeadIndex;
+    fn walk(&mut self, syllable_id: SyllableId) -> bool {
+        match self.level {
+            0 => {
+                self.lv2_index = self
+                    .lv1_index
+                    .node(syllable_id)
+                    .and_then(|node| node.next_trunk());
+                self.lv2_index.is_some()
+            }
+            1 => {
+                self.lv3_index = self
+                    .lv2_index
+                    .and_then(|index| index.find(syllable_id))
+                    .and_then(|node| node.next_trunk());
+                self.lv3_index.is_some()
+            }
+            2 => {
+                self.lv4_index = self
+                    .lv3_index
+                    .and_then(|index| index.find(syllable_id))
+                    .and_then(|node| node.next_tail());
+                self.lv4_index.is_some()
+            }
+            _ => false,
+        }
+    }
+}
 
 struct Metadata {
     format: String,
     dict_file_checksum: u32,
     num_syllables: u32,
     num_entries: u32,
-    string_table_size: u32,
+    syllabary_offset: u32,
+    index_offset: u32,
+    strings_offset: u32,
+}
+
+impl Metadata {
+    // Whether the parts of the file it points to are within the size.
+    fn fits(&self, size: usize) -> bool {
+        let num_syllables = self.num_syllables as usize;
+        size >= self.index_offset as usize + num_syllables * HEAD_NODE_SIZE
+            && size >= self.syllabary_offset as usize + num_syllables * 8
+            && size >= self.strings_offset as usize
+    }
 }
 
+// A table is built from a vocabulary and saved, then loaded by mapping the
+// file, where queries walk down the index without parsing the rest of it.
 pub struct Table {
     file_path: PathExt,
     metadata: Option<Metadata>,
+    // the syllabary and index built, to be saved
     syllabary: Option<Syllabary>,
-    //string_table_builder: Option,
+    index: Option<HeadIndex>,
+    mapped_file: Option<MappedFile>,
 }
 
-////type Vocabulary = HashMap<usize, Vec<ShortDictEntry>>;
-
 impl Table {
     pub fn new(file_path: PathExt) -> Self {
         Self {
             file_path,
             metadata: None,
             syllabary: None,
+            index: None,
+            mapped_file: None,
         }
     }
 
-    //    //    fn remove(&mut self) {
-    //    //        // 清空内容
-    //    //        self.syllabary.clear();
-    //    //        self.vocabulary.clear();
-    //    //    }
+    pub fn is_loaded(&self) -> bool {
+        self.mapped_file.is_some()
+    }
+
+    fn image(&self) -> Option<TableImage<'_>> {
+        let mapped_file = self.mapped_file.as_ref()?;
+        Some(TableImage {
+            bytes: mapped_file.read(0, mapped_file.size())?,
+            strings_offset: self.metadata.as_ref()?.strings_offset as usize,
+        })
+    }
+
+    fn head_index(&self) -> Option<HeadIndexView<'_>> {
+        let metadata = self.metadata.as_ref()?;
+        Some(HeadIndexView {
+            image: self.image()?,
+            offset: metadata.index_offset as usize,
+            size: metadata.num_syllables as usize,
+        })
+    }
+
+    pub fn get_syllable_by_id(&self, syllable_id: SyllableId) -> Option<&str> {
+        let metadata = self.metadata.as_ref()?;
+        let i = usize::try_from(syllable_id)
+            .ok()
+            .filter(|&i| i < metadata.num_syllables as usize)?;
+        self.image()?
+            .string_at(metadata.syllabary_offset as usize + i * 8)
+    }
+
+    // Entries of a single syllable.
+    pub fn query_words(&self, syllable_id: SyllableId) -> Option<Vec<TableEntryRef<'_>>> {
+        let entries = self.word_entries(syllable_id)?;
+        Some(self.image()?.entries(entries).collect())
+    }
+
+    // Where the entries of a single syllable are, to be read by entry_at.
+    pub(crate) fn word_entries(&self, syllable_id: SyllableId) -> Option<EntryList> {
+        let entries = self.head_index()?.node(syllable_id)?.entries();
+        (!entries.is_empty()).then_some(entries)
+    }
+
+    pub(crate) fn entry_at(&self, entries: EntryList, i: usize) -> Option<TableEntryRef<'_>> {
+        self.image()?.entry_at(entries.offset_of(i)?)
+    }
+
+    // Entries whose code is exactly the given one.
+    pub fn query_phrases(&self, code: &[SyllableId]) -> Option<Vec<TableEntryRef<'_>>> {
+        let mut query = TableQuery::new(self.head_index()?);
+        let index_code_length = code.len().min(Code::INDEX_CODE_MAX_LENGTH);
+        let (&last, index_code) = code[..index_code_length].split_last()?;
+        for &syllable_id in index_code {
+            if !query.advance(syllable_id, 0.0) {
+                return None;
+            }
+        }
+        if code.len() <= Code::INDEX_CODE_MAX_LENGTH {
+            let accessor = query.access(last, 0.0)?;
+            let entries: Vec<_> = self.image()?.entries(accessor.entries()).collect();
+            return (!entries.is_empty()).then_some(entries);
+        }
+        if !query.advance(last, 0.0) {
+            return None;
+        }
+        let mut accessor = query.access(-1, 0.0)?;
+        let extra_code = &code[index_code_length..];
+        let mut entries = Vec::new();
+        while let Some(entry) = accessor.entry() {
+            if accessor
+                .extra_code()
+                .is_some_and(|extra| extra.as_slice() == extra_code)
+            {
+                entries.push(entry);
+            }
+            accessor.next();
+        }
+        (!entries.is_empty()).then_some(entries)
+    }
+
+    // Looks up entries along the paths from start_pos in the syllable graph.
+    pub(crate) fn query(
+        &self,
+        syll_graph: &SyllableGraph,
+        start_pos: usize,
+    ) -> Option<TableQueryResult<'_>> {
+        let index = self.head_index()?;
+        if start_pos >= syll_graph.interpreted_length() {
+            return None;
+        }
+        let mut result = TableQueryResult::new();
+        let mut queue = VecDeque::new();
+        queue.push_back((start_pos, TableQuery::new(index)));
+        while let Some((current_pos, mut query)) = queue.pop_front() {
+            let Some(spellings_index) = syll_graph.indices().get(&current_pos) else {
+                continue;
+            };
+            if query.level == Code::INDEX_CODE_MAX_LENGTH {
+                if let Some(accessor) = query.access(-1, 0.0) {
+                    if !accessor.exhausted() {
+                        result.entry(current_pos).or_default().push(accessor);
+                    }
+                }
+                continue;
+            }
+            for (&syllable_id, spellings) in spellings_index {
+                for props in spellings {
+                    let end_pos = props.end_pos;
+                    if let Some(accessor) = query.access(syllable_id, props.credibility) {
+                        if !accessor.exhausted() {
+                            result.entry(end_pos).or_default().push(accessor);
+                        }
+                    }
+                    if end_pos < syll_graph.interpreted_length()
+                        && query.advance(syllable_id, props.credibility)
+                    {
+                        queue.push_back((end_pos, query.clone()));
+                        query.backdate();
+                    }
+                }
+            }
+        }
+        (!result.is_empty()).then_some(result)
+    }
 
     pub fn build(
         &mut self,
@@ -111,72 +670,367 @@ impl Table {
         info!("creating metadata.");
 
         self.metadata = Some(Metadata {
-            format: String::new(),
+            format: FORMAT.to_string(),
             dict_file_checksum,
             num_syllables: num_syllables as u32,
             num_entries: num_entries as u32,
-            string_table_size: 0,
+            syllabary_offset: 0,
+            index_offset: 0,
+            strings_offset: 0,
         });
 
         info!("creating syllabary.");
+        self.syllabary = Some(syllabary.iter().cloned().collect());
+
+        info!("creating table index.");
+        let mut index = vec![HeadIndexNode::default(); num_syllables];
+        for (&syllable_id, page) in vocabulary.iter() {
+            let Some(node) = usize::try_from(syllable_id)
+                .ok()
+                .and_then(|i| index.get_mut(i))
+            else {
+                continue;
+            };
+            node.entries = build_entries(&page.entries);
+            node.next_level = page
+                .next_level
+                .as_ref()
+                .map(|next_level| build_trunk_index(next_level, 2));
+        }
+        self.index = Some(index);
+        true
+    }
 
-        for syllable in syllabary {}
-        //self.syllabary = Some(syllabary.clone());
-        //if let Some(metadata) = &mut self.metadata {
-        //    metadata.syllabary = Some(syllabary.clone());
-        //}
-
-        //println!("creating table index.");
-        //self.index = Some(self.build_index(vocabulary, num_syllables));
-        //if let Some(metadata) = &mut self.metadata {
-        //    metadata.index = self.index.clone();
-        //}
-
-        //if !self.on_build_finish() {
-        //    return false;
-        //}
-
-        //if let Some(metadata) = &mut self.metadata {
-        //    metadata.format = K_TABLE_FORMAT_LATEST.to_string();
-        //}
-
-        todo!()
-    }
-
-    //    //    fn save(&self) -> bool {
-    //    //        // 假设保存成功
-    //    //        true
-    //    //    }
-
-    //    //    fn load(&mut self) -> bool {
-    //    //        // 假设加载成功
-    //    //        true
-    //    //    }
-
-    //    //    fn close(&mut self) {
-    //    //        // 关闭文件等操作
-    //    //    }
-
-    //    fn get_syllable_by_id(&self, syllable_id: SyllableId) -> Option<&str> {
-    //        if let Some(syllabary) = &self.syllabary {
-    //            if syllable_id < syllabary.len() as SyllableId {
-    //                return Some(syllabary[syllable_id as usize].str_id());
-    //            }
-    //        }
-    //        None
-    //    }
-
-    //    //    fn query_words(&self, code: usize) -> Option<&Vec<ShortDictEntry>> {
-    //    //        self.vocabulary.get(&code)
-    //    //    }
-
-    //    //    fn query_phrases(&self, code: &[usize]) -> Option<Vec<&ShortDictEntry>> {
-    //    //        let mut result = Vec::new();
-    //    //        for c in code {
-    //    //            if let Some(entries) = self.vocabulary.get(c) {
-    //    //                result.extend(entries.iter());
-    //    //            }
-    //    //        }
-    //    //        Some(result)
-    //    //    }
+    pub fn dict_file_checksum(&self) -> u32 {
+        self.metadata
+            .as_ref()
+            .map_or(0, |metadata| metadata.dict_file_checksum)
+    }
+
+    pub fn save(&self) -> bool {
+        info!("saving table file: {}", self.file_path);
+        let (Some(metadata), Some(syllabary), Some(index)) =
+            (&self.metadata, &self.syllabary, &self.index)
+        else {
+            error!("the table has not been built!");
+            return false;
+        };
+
+        let mut writer = TableWriter::default();
+        writer.bytes.resize(METADATA_SIZE, 0);
+        let index_offset = writer.write_head_index(index);
+        let syllabary_offset = writer.bytes.len() as u32;
+        for syllable in syllabary {
+            writer.write_string(syllable);
+        }
+        let strings_offset = writer.bytes.len() as u32;
+        let TableWriter {
+            mut bytes, strings, ..
+        } = writer;
+        bytes.extend(strings);
+
+        let mut header = FORMAT.as_bytes().to_vec();
+        header.resize(FORMAT_LENGTH, 0);
+        for field in [
+            metadata.dict_file_checksum,
+            metadata.num_syllables,
+            metadata.num_entries,
+            syllabary_offset,
+            index_offset,
+            strings_offset,
+        ] {
+            header.extend(field.to_le_bytes());
+        }
+        bytes[..METADATA_SIZE].copy_from_slice(&header);
+
+        if let Err(e) = replace_file(&self.file_path, &bytes) {
+            error!("error saving table file: {}", e);
+            return false;
+        }
+        true
+    }
+
+    pub fn load(&mut self) -> bool {
+        info!("loading table file: {}", self.file_path);
+        let mapped_file = match MappedFile::open_read_only(self.file_path.clone()) {
+            Ok(mapped_file) => mapped_file,
+            Err(e) => {
+                error!("error opening table file: {}", e);
+                return false;
+            }
+        };
+        let Some(metadata) = mapped_file
+            .read(0, mapped_file.size())
+            .and_then(parse_metadata)
+            .filter(|metadata| metadata.fits(mapped_file.size()))
+        else {
+            error!("invalid table file: {}", self.file_path);
+            return false;
+        };
+        info!(
+            "table loaded, format: {}, {} syllables, {} entries.",
+            metadata.format, metadata.num_syllables, metadata.num_entries
+        );
+        self.metadata = Some(metadata);
+        self.syllabary = None;
+        self.index = None;
+        self.mapped_file = Some(mapped_file);
+        true
+    }
+
+    // Reads only the metadata at the start of the file, enough to tell which
+    // sources it was built from, without mapping the rest of it.
+    pub fn load_metadata(&mut self) -> bool {
+        let mut header = [0; METADATA_SIZE];
+        let result = File::open(&self.file_path).and_then(|mut file| file.read_exact(&mut header));
+        if let Err(e) = result {
+            error!("error reading table file '{}': {}", self.file_path, e);
+            return false;
+        }
+        let Some(metadata) = parse_metadata(&header) else {
+            error!("invalid table file: {}", self.file_path);
+            return false;
+        };
+        self.metadata = Some(metadata);
+        true
+    }
+
+    // Entries whose code begins with the given one, by increasing code
+    // length.
+    pub fn query_prefix(&self, code: &[SyllableId]) -> Option<Vec<TableEntryRef<'_>>> {
+        let (&first, rest) = code.split_first()?;
+        let image = self.image()?;
+        let mut node = self.head_index()?.node(first)?;
+        let index_code_length = code.len().min(Code::INDEX_CODE_MAX_LENGTH);
+        for &syllable_id in &rest[..index_code_length - 1] {
+            node = node.next_trunk()?.find(syllable_id)?;
+        }
+        if code.len() > Code::INDEX_CODE_MAX_LENGTH {
+            let extra_code = &code[Code::INDEX_CODE_MAX_LENGTH..];
+            let mut long_entries: Vec<_> = image
+                .long_entries(node.next_tail()?.entries())
+                .filter(|(extra, _)| extra.starts_with(extra_code))
+                .collect();
+            long_entries.sort_by_key(|(extra, _)| extra.len());
+            let entries: Vec<_> = long_entries.into_iter().map(|(_, entry)| entry).collect();
+            return (!entries.is_empty()).then_some(entries);
+        }
+        let mut entries: Vec<_> = image.entries(node.entries()).collect();
+        let mut nodes = vec![node];
+        for _ in index_code_length..Code::INDEX_CODE_MAX_LENGTH {
+            let mut next_nodes = Vec::new();
+            for trunk in nodes.iter().filter_map(|node| node.next_trunk()) {
+                for i in 0..trunk.size() {
+                    if let Some(next_node) = trunk.node_at(i) {
+                        entries.extend(image.entries(next_node.entries()));
+                        next_nodes.push(next_node);
+                    }
+                }
+            }
+            nodes = next_nodes;
+        }
+        let mut long_entries: Vec<_> = nodes
+            .iter()
+            .filter_map(|node| node.next_tail())
+            .flat_map(|tail| image.long_entries(tail.entries()))
+            .collect();
+        long_entries.sort_by_key(|(extra, _)| extra.len());
+        entries.extend(long_entries.into_iter().map(|(_, entry)| entry));
+        (!entries.is_empty()).then_some(entries)
+    }
+}
+
+fn build_entries(entries: &ShortDictEntryList) -> Vec<TableEntry> {
+    entries
+        .iter()
+        .map(|entry| TableEntry {
+            text: entry.text.clone(),
+            weight: entry.weight as Weight,
+        })
+        .collect()
+}
+
+// Builds the index of the 2nd or 3rd syllables, followed by the tail of
+// longer codes after the 3rd.
+fn build_trunk_index(vocabulary: &Vocabulary, level: usize) -> TrunkIndex {
+    vocabulary
+        .iter()
+        .filter(|(&syllable_id, _)| syllable_id >= 0)
+        .map(|(&syllable_id, page)| TrunkIndexNode {
+            key: syllable_id,
+            entries: build_entries(&page.entries),
+            next_level: page.next_level.as_ref().map(|next_level| {
+                if level < Code::INDEX_CODE_MAX_LENGTH {
+                    PhraseIndex::Trunk(build_trunk_index(next_level, level + 1))
+                } else {
+                    PhraseIndex::Tail(build_tail_index(next_level))
+                }
+            }),
+        })
+        .collect()
+}
+
+fn build_tail_index(vocabulary: &Vocabulary) -> TailIndex {
+    let Some(page) = vocabulary.get(&-1) else {
+        return TailIndex::new();
+    };
+    page.entries
+        .iter()
+        .map(|entry| LongEntry {
+            extra_code: Code::from(
+                entry
+                    .code
+                    .get(Code::INDEX_CODE_MAX_LENGTH..)
+                    .unwrap_or_default()
+                    .to_vec(),
+            ),
+            entry: TableEntry {
+                text: entry.text.clone(),
+                weight: entry.weight as Weight,
+            },
+        })
+        .collect()
+}
+
+// Parses the header, leaving it to the caller to check the offsets against
+// the size of the file.
+fn parse_metadata(bytes: &[u8]) -> Option<Metadata> {
+    let format = String::from_utf8_lossy(bytes.get(..FORMAT_LENGTH)?)
+        .trim_end_matches('\0')
+        .to_string();
+    if !format.starts_with(FORMAT_PREFIX) {
+        return None;
+    }
+    Some(Metadata {
+        format,
+        dict_file_checksum: read_u32(bytes, FORMAT_LENGTH)?,
+        num_syllables: read_u32(bytes, FORMAT_LENGTH + 4)?,
+        num_entries: read_u32(bytes, FORMAT_LENGTH + 8)?,
+        syllabary_offset: read_u32(bytes, FORMAT_LENGTH + 12)?,
+        index_offset: read_u32(bytes, FORMAT_LENGTH + 16)?,
+        strings_offset: read_u32(bytes, FORMAT_LENGTH + 20)?,
+    })
+}
+
+// Lays out the table file, in little endian:
+//   metadata   the format, checksum of the source files, num_syllables,
+//              num_entries and where the syllabary, the index and the
+//              strings start
+//   index      levels of nodes, each written before the level above it;
+//              lists are given by their size and offset in the file, and a
+//              missing next level by offset 0
+//   syllabary  the text of each syllable
+//   strings    text referred to by offset from the start of the strings and
+//              length
+#[derive(Default)]
+struct TableWriter {
+    bytes: Vec<u8>,
+    strings: Vec<u8>,
+    string_offsets: HashMap<String, u32>,
+}
+
+impl TableWriter {
+    fn write_u32(&mut self, value: u32) {
+        self.bytes.extend(value.to_le_bytes());
+    }
+
+    fn write_string(&mut self, text: &str) {
+        let offset = match self.string_offsets.get(text) {
+            Some(&offset) => offset,
+            None => {
+                let offset = self.strings.len() as u32;
+                self.strings.extend(text.as_bytes());
+                self.string_offsets.insert(text.to_string(), offset);
+                offset
+            }
+        };
+        self.write_u32(offset);
+        self.write_u32(text.len() as u32);
+    }
+
+    fn write_entry(&mut self, entry: &TableEntry) {
+        self.write_string(&entry.text);
+        self.bytes.extend(entry.weight.to_le_bytes());
+    }
+
+    // Returns the size and offset of the list.
+    fn write_entries(&mut self, entries: &[TableEntry]) -> (u32, u32) {
+        let offset = self.bytes.len() as u32;
+        for entry in entries {
+            self.write_entry(entry);
+        }
+        (entries.len() as u32, offset)
+    }
+
+    fn write_next_level(&mut self, next_level: Option<&PhraseIndex>) -> u32 {
+        match next_level {
+            Some(PhraseIndex::Trunk(trunk)) => self.write_trunk_index(trunk),
+            Some(PhraseIndex::Tail(tail)) => self.write_tail_index(tail),
+            None => 0,
+        }
+    }
+
+    fn write_head_index(&mut self, index: &HeadIndex) -> u32 {
+        let nodes: Vec<_> = index
+            .iter()
+            .map(|node| {
+                let entries = self.write_entries(&node.entries);
+                let next_level = match &node.next_level {
+                    Some(trunk) => self.write_trunk_index(trunk),
+                    None => 0,
+                };
+                (entries, next_level)
+            })
+            .collect();
+        let offset = self.bytes.len() as u32;
+        for ((size, entries_offset), next_level) in nodes {
+            self.write_u32(size);
+            self.write_u32(entries_offset);
+            self.write_u32(next_level);
+        }
+        offset
+    }
+
+    // A trunk index starts with the number of its nodes.
+    fn write_trunk_index(&mut self, index: &TrunkIndex) -> u32 {
+        let nodes: Vec<_> = index
+            .iter()
+            .map(|node| {
+                let entries = self.write_entries(&node.entries);
+                let next_level = self.write_next_level(node.next_level.as_ref());
+                (node.key, entries, next_level)
+            })
+            .collect();
+        let offset = self.bytes.len() as u32;
+        self.write_u32(nodes.len() as u32);
+        for (key, (size, entries_offset), next_level) in nodes {
+            self.bytes.extend(key.to_le_bytes());
+            self.write_u32(size);
+            self.write_u32(entries_offset);
+            self.write_u32(next_level);
+        }
+        offset
+    }
+
+    // A tail index starts with the number of its entries.
+    fn write_tail_index(&mut self, index: &TailIndex) -> u32 {
+        let extra_codes: Vec<_> = index
+            .iter()
+            .map(|long_entry| {
+                let offset = self.bytes.len() as u32;
+                for syllable_id in long_entry.extra_code.iter() {
+                    self.bytes.extend(syllable_id.to_le_bytes());
+                }
+                (long_entry.extra_code.len() as u32, offset)
+            })
+            .collect();
+        let offset = self.bytes.len() as u32;
+        self.write_u32(index.len() as u32);
+        for (long_entry, (size, code_offset)) in index.iter().zip(extra_codes) {
+            self.write_u32(size);
+            self.write_u32(code_offset);
+            self.write_entry(&long_entry.entry);
+        }
+        offset
+    }
 }
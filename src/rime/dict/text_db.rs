@@ -313,6 +313,21 @@ impl<'a> TextDb<'a> {
         }
     }
 
+    // Writes the changes made so far to the file, keeping the db open.
+    pub(crate) fn save(&mut self) -> bool {
+        if !self.loaded() || self.readonly() {
+            return false;
+        }
+        if !self.modified {
+            return true;
+        }
+        if !self.save_to_file(self.file_path().clone()) {
+            return false;
+        }
+        self.modified = false;
+        true
+    }
+
     fn clear(&mut self) {
         self.metadata.clear();
         self.data.clear();
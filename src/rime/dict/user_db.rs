@@ -9,7 +9,7 @@ use crate::rime::dict::db_utils::{DbSink, DbSource};
 use crate::rime::dict::tsv::{TsvFormatter, TsvParser, TsvReader, TsvWriter};
 use crate::rime::service::Service;
 
-type TickCount = u64;
+pub(crate) type TickCount = u64;
 
 static PLAIN_USERDB_EXTENSION: &str = ".userdb.txt";
 
@@ -53,10 +53,10 @@ impl std::fmt::Debug for TextFormat {
 
 /// Properties of a user db entry value.
 #[derive(Default)]
-struct UserDbValue {
-    commits: i32,
-    dee: f64,
-    tick: TickCount,
+pub(crate) struct UserDbValue {
+    pub(crate) commits: i32,
+    pub(crate) dee: f64,
+    pub(crate) tick: TickCount,
 }
 
 impl UserDbValue {
@@ -76,7 +76,7 @@ impl FromStr for UserDbValue {
 }
 
 impl UserDbValue {
-    fn pack(&self) -> String {
+    pub(crate) fn pack(&self) -> String {
         format!("c={} d={} t={}", self.commits, self.dee, self.tick)
     }
 
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use log::{error, info};
+
+use crate::rime::algo::dynamics::{formula_d, formula_p};
+use crate::rime::common::PathExt;
+use crate::rime::dict::db::{Db, DbAccessor};
+use crate::rime::dict::text_db::TextDb;
+use crate::rime::dict::user_db::{TickCount, UserDbValue, UserDbWrapper};
+use crate::rime::dict::vocabulary::DictEntry;
+
+// Words committed by the user, keyed by "<code> \t<text>" like librime's
+// user db, where the code is the input they were typed with.
+pub(crate) struct UserDictionary {
+    db: UserDbWrapper<TextDb<'static>>,
+    // the number of commits made so far, kept in the db metadata
+    tick: TickCount,
+}
+
+impl UserDictionary {
+    // Opens "<data_dir>/<dict_name>.userdb.txt", which is created when first
+    // saved.
+    pub(crate) fn create(data_dir: &PathExt, dict_name: &str) -> Option<Self> {
+        let file_path = data_dir.join(format!("{}.userdb.txt", dict_name));
+        let mut db = UserDbWrapper::<TextDb>::new(file_path, dict_name);
+        if !db.open() {
+            error!("failed to open user dictionary '{}'.", dict_name);
+            return None;
+        }
+        let tick = db
+            .meta_fetch("/tick")
+            .and_then(|tick| tick.parse().ok())
+            .unwrap_or(0);
+        info!("user dictionary '{}' opened at tick {}.", dict_name, tick);
+        Some(Self { db, tick })
+    }
+
+    fn make_key(code: &str, text: &str) -> String {
+        format!("{} \t{}", code, text)
+    }
+
+    // Counts a commit of the text typed with the code at the present tick.
+    pub(crate) fn update_entry(&mut self, code: &str, text: &str) -> bool {
+        let key = Self::make_key(code, text);
+        let mut value = self
+            .db
+            .fetch(&key)
+            .and_then(|value| value.parse::<UserDbValue>().ok())
+            .unwrap_or_default();
+        value.commits += 1;
+        value.dee = formula_d(1.0, self.tick as f64, value.dee, value.tick as f64);
+        value.tick = self.tick;
+        self.db.update(&key, &value.pack())
+    }
+
+    // Adds an entry that has not been committed yet, e.g. a phrase made by
+    // the encoder, unless it is there already.
+    pub(crate) fn add_entry(&mut self, code: &str, text: &str) -> bool {
+        let key = Self::make_key(code, text);
+        if self.db.fetch(&key).is_some() {
+            return false;
+        }
+        let value = UserDbValue {
+            tick: self.tick,
+            ..Default::default()
+        };
+        self.db.update(&key, &value.pack())
+    }
+
+    // Moves on to the next tick, once per commit of the context.
+    pub(crate) fn commit_tick(&mut self) -> bool {
+        self.tick += 1;
+        self.db
+            .meta_update("/tick".to_string(), self.tick.to_string())
+    }
+
+    // Writes the entries updated so far to the file.
+    pub(crate) fn save(&mut self) -> bool {
+        self.db.save()
+    }
+
+    // Returns the entries typed with the code, or with codes beginning with it
+    // if predictive, by increasing remaining code length and then decreasing
+    // weight.
+    pub(crate) fn lookup(&mut self, code: &str, predictive: bool) -> Vec<Arc<DictEntry>> {
+        let present = self.tick.max(1) as f64;
+        let mut entries = Vec::new();
+        let Some(records) = self.db.query(Some(code)) else {
+            return entries;
+        };
+        for (key, value) in records {
+            let Some((entry_code, text)) = key.split_once('\t') else {
+                continue;
+            };
+            let entry_code = entry_code.trim_end();
+            let Some(remaining_code) = entry_code.strip_prefix(code) else {
+                continue;
+            };
+            if !predictive && !remaining_code.is_empty() {
+                continue;
+            }
+            let Ok(value) = value.parse::<UserDbValue>() else {
+                continue;
+            };
+            // deleted entry
+            if value.commits < 0 {
+                continue;
+            }
+            let dee = formula_d(0.0, present, value.dee, value.tick as f64);
+            // entries never committed, e.g. encoded phrases, get the lowest
+            // weight rather than a log of zero.
+            let probability =
+                formula_p(0.0, value.commits as f64 / present, present, dee).max(f64::EPSILON);
+            entries.push(Arc::new(DictEntry {
+                text: text.to_string(),
+                custom_code: entry_code.to_string(),
+                weight: probability.ln(),
+                commit_count: value.commits,
+                remaining_code_length: remaining_code.len() as i32,
+                ..Default::default()
+            }));
+        }
+        entries.sort_by(|a, b| {
+            a.remaining_code_length
+                .cmp(&b.remaining_code_length)
+                .then(b.weight.total_cmp(&a.weight))
+        });
+        entries
+    }
+}
+
+#[test]
+fn update_and_lookup() {
+    let data_dir = PathExt::new("user_dictionary_test");
+    let _ = std::fs::remove_dir_all(&data_dir);
+    std::fs::create_dir_all(&data_dir).unwrap();
+
+    let mut user_dict = UserDictionary::create(&data_dir, "sample").unwrap();
+    assert!(user_dict.update_entry("ni", "泥"));
+    assert!(user_dict.commit_tick());
+    assert!(user_dict.update_entry("ni", "你"));
+    assert!(user_dict.commit_tick());
+    assert!(user_dict.update_entry("ni", "你"));
+    assert!(user_dict.update_entry("nihao", "你好"));
+    assert!(user_dict.commit_tick());
+    assert_eq!(Some("3".to_string()), user_dict.db.meta_fetch("/tick"));
+
+    let texts = |entries: Vec<Arc<DictEntry>>| {
+        entries
+            .iter()
+            .map(|entry| entry.text.clone())
+            .collect::<Vec<_>>()
+    };
+    // the word committed more often and lately comes first
+    assert_eq!(vec!["你", "泥"], texts(user_dict.lookup("ni", false)));
+    let entries = user_dict.lookup("ni", true);
+    assert_eq!(2, entries[0].commit_count);
+    assert_eq!(3, entries[2].remaining_code_length);
+    assert_eq!(vec!["你", "泥", "你好"], texts(entries));
+
+    // an entry added but never committed comes last with a finite weight
+    assert!(user_dict.add_entry("ni", "尼"));
+    assert!(!user_dict.add_entry("ni", "你"));
+    let entries = user_dict.lookup("ni", false);
+    assert_eq!(vec!["你", "泥", "尼"], texts(entries.clone()));
+    assert_eq!(0, entries[2].commit_count);
+    assert!(entries[2].weight.is_finite());
+
+    // the entries and the tick are kept in the file
+    assert!(user_dict.save());
+    drop(user_dict);
+    let mut user_dict = UserDictionary::create(&data_dir, "sample").unwrap();
+    assert_eq!(3, user_dict.tick);
+    assert_eq!(vec!["你", "泥", "尼"], texts(user_dict.lookup("ni", false)));
+}
@@ -25,8 +25,14 @@ impl DerefMut for Code {
     }
 }
 
+impl From<Vec<SyllableId>> for Code {
+    fn from(syllable_ids: Vec<SyllableId>) -> Self {
+        Self(syllable_ids)
+    }
+}
+
 impl Code {
-    const INDEX_CODE_MAX_LENGTH: usize = 3;
+    pub(crate) const INDEX_CODE_MAX_LENGTH: usize = 3;
 
     fn create_index(&self, index_code: Option<&mut Self>) {
         if let Some(index_code) = index_code {
@@ -47,7 +53,7 @@ impl Code {
 pub struct ShortDictEntry {
     pub text: String,
     pub code: Code, // Multi-syllable code from prism
-    weight: f64,
+    pub(crate) weight: f64,
 }
 
 impl ShortDictEntry {
@@ -84,17 +90,17 @@ impl Ord for ShortDictEntry {
     }
 }
 
-#[derive(Debug)]
-struct DictEntry {
-    text: String,
-    comment: String,
-    preedit: String,
-    code: Code,          // multi-syllable code from prism
-    custom_code: String, // user defined code
-    weight: f64,
-    commit_count: i32,
-    remaining_code_length: i32,
-    matching_code_size: i32,
+#[derive(Debug, Default, Clone)]
+pub struct DictEntry {
+    pub(crate) text: String,
+    pub(crate) comment: String,
+    pub(crate) preedit: String,
+    pub(crate) code: Code,          // multi-syllable code from prism
+    pub(crate) custom_code: String, // user defined code
+    pub(crate) weight: f64,
+    pub(crate) commit_count: i32,
+    pub(crate) remaining_code_length: i32,
+    pub(crate) matching_code_size: i32,
 }
 
 impl DictEntry {
@@ -106,11 +112,11 @@ impl DictEntry {
         }
     }
 
-    fn is_exact_match(&self) -> bool {
+    pub(crate) fn is_exact_match(&self) -> bool {
         self.matching_code_size == 0 || self.matching_code_size == self.code.len() as i32
     }
 
-    fn is_predictive_match(&self) -> bool {
+    pub(crate) fn is_predictive_match(&self) -> bool {
         self.matching_code_size != 0 && self.matching_code_size < self.code.len() as i32
     }
 }
@@ -248,47 +254,39 @@ impl DerefMut for Vocabulary {
 }
 
 impl Vocabulary {
-    fn locate_entries(self, code: &Code) -> bool {
-        //let mut vocab = self;
-        //for (i, syllable_id) in code.iter().enumerate() {
-        //    let key = if i < Code::INDEX_CODE_MAX_LENGTH {
-        //        *syllable_id
-        //    } else {
-        //        -1
-        //    };
-
-        //    let mut vocab_ref = vocab.write().unwrap();
-
-        //    let page = vocab_ref
-        //        .entry(key)
-        //        .or_insert_with(|| VocabularyPage::new());
-
-        //    if i == code.len() - 1 || i == Code::INDEX_CODE_MAX_LENGTH {
-        //        Some(Arc::clone(&page.entries));
-        //        return true;
-        //    } else {
-        //        vocab = page
-        //            .next_level
-        //            .get_or_insert_with(|| SharedVocabulary::new());
-        //    }
-        //}
-        //false
-        todo!()
+    // The entries of the code, on the page of its last syllable, or on the
+    // tail page of the level following the index code for longer codes.
+    pub(crate) fn locate_entries(&mut self, code: &Code) -> &mut ShortDictEntryList {
+        let depth = code
+            .len()
+            .saturating_sub(1)
+            .min(Code::INDEX_CODE_MAX_LENGTH);
+        let mut vocabulary = self;
+        for &syllable_id in &code[..depth] {
+            vocabulary = vocabulary
+                .entry(syllable_id)
+                .or_default()
+                .next_level
+                .get_or_insert_with(Vocabulary::default);
+        }
+        let key = match code.get(depth) {
+            Some(&syllable_id) if depth < Code::INDEX_CODE_MAX_LENGTH => syllable_id,
+            _ => -1,
+        };
+        &mut vocabulary.entry(key).or_default().entries
     }
 
-    fn sort_homophones(&mut self) {
-        //for page in self.values_mut() {
-        //    page.entries.sort();
-        //    if let Some(ref mut next_level) = page.next_level.take() {
-        //        Arc::make_mut(next_level).sort_homophones();
-        //    }
-        //}
-
-        todo!()
+    pub(crate) fn sort_homophones(&mut self) {
+        for page in self.values_mut() {
+            page.entries.sort();
+            if let Some(next_level) = &mut page.next_level {
+                next_level.sort_homophones();
+            }
+        }
     }
 }
 
-type ReverseLookupTable = HashMap<String, HashSet<String>>;
+pub(crate) type ReverseLookupTable = HashMap<String, HashSet<String>>;
 
 impl Display for Code {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
@@ -0,0 +1,500 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, RwLock};
+
+use log::{error, info};
+use signals2::{Connect1, Emit1, Emit2, Signal};
+
+use crate::rime::commit_history::{CommitHistory, CommitRecord};
+use crate::rime::component::{Component, Ticket};
+use crate::rime::config::config_types::ConfigList;
+use crate::rime::context::Context;
+use crate::rime::filter::Filter;
+use crate::rime::gear;
+use crate::rime::key_event::KeyEvent;
+use crate::rime::menu::Menu;
+use crate::rime::processor::{ProcessResult, Processor};
+use crate::rime::registry::Registry;
+use crate::rime::schema::Schema;
+use crate::rime::segmentation::{SegmentStatus, Segmentation};
+use crate::rime::segmentor::Segmentor;
+use crate::rime::translator::Translator;
+
+pub(crate) type CommitSink = Signal<(String,)>;
+
+// Context notifications are queued by the notifier slots and handled by the
+// engine once the component that changed the context has returned, since the
+// slots cannot reach the engine while the context is being changed.
+// `process_key` flushes the queue after each processor, so a processor that
+// changes the context and then reads the composition, the menu or the commit
+// it caused must call `flush_context_events` in between.
+enum ContextEvent {
+    Update,
+    Select,
+    Commit {
+        text: String,
+        commit_history: CommitHistory,
+    },
+}
+
+pub(crate) struct Engine {
+    schema: Schema,
+    context: Context,
+    processors: Vec<Arc<RwLock<dyn Processor>>>,
+    segmentors: Vec<Box<dyn Segmentor>>,
+    translators: Vec<Box<dyn Translator>>,
+    filters: Vec<Box<dyn Filter>>,
+    sink: CommitSink,
+    context_events: Arc<Mutex<VecDeque<ContextEvent>>>,
+}
+
+impl Engine {
+    pub(crate) fn new(schema: Schema) -> Self {
+        gear::initialize();
+        let mut engine = Self {
+            schema,
+            context: Context::new(),
+            processors: Vec::new(),
+            segmentors: Vec::new(),
+            translators: Vec::new(),
+            filters: Vec::new(),
+            sink: CommitSink::new(),
+            context_events: Arc::new(Mutex::new(VecDeque::new())),
+        };
+        engine.connect_context_notifiers();
+        engine.initialize_components();
+        engine
+    }
+
+    fn connect_context_notifiers(&mut self) {
+        let events = self.context_events.clone();
+        self.context.update_notifier().connect(move |_: Arc<Context>| {
+            events.lock().unwrap().push_back(ContextEvent::Update);
+        });
+        let events = self.context_events.clone();
+        self.context.select_notifier().connect(move |_: Arc<Context>| {
+            events.lock().unwrap().push_back(ContextEvent::Select);
+        });
+        let events = self.context_events.clone();
+        self.context
+            .commit_notifier()
+            .connect(move |context: Arc<Context>| {
+                let mut commit_history = context.commit_history().clone();
+                commit_history.push_composition(context.composition(), context.input());
+                let text = context.get_commit_text();
+                events.lock().unwrap().push_back(ContextEvent::Commit {
+                    text,
+                    commit_history,
+                });
+            });
+    }
+
+    pub(crate) fn apply_schema(&mut self, schema: Schema) {
+        self.schema = schema;
+        self.context.clear();
+        self.context.clear_transient_options();
+        self.initialize_components();
+        self.flush_context_events();
+    }
+
+    // Switches to another schema found in the directory of the current one.
+    pub(crate) fn select_schema(&mut self, schema_id: &str) -> bool {
+        let Some(data_dir) = self.schema.data_dir() else {
+            error!("cannot locate schema '{}'.", schema_id);
+            return false;
+        };
+        match Schema::load(&data_dir, schema_id) {
+            Some(schema) => {
+                self.apply_schema(schema);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn initialize_components(&mut self) {
+        self.processors = self.create_components("processor");
+        self.segmentors = self.create_components("segmentor");
+        self.translators = self.create_components("translator");
+        self.filters = self.create_components("filter");
+        for entry in self.processors.clone() {
+            if let Ok(mut processor) = entry.write() {
+                processor.on_schema_loaded(self);
+            }
+        }
+    }
+
+    // Instantiates components listed under "engine/<name_space>s" in the schema.
+    fn create_components<T: 'static>(&self, name_space: &str) -> Vec<T> {
+        let mut components = Vec::new();
+        let key = format!("engine/{}s", name_space);
+        let Some(item) = self.schema.config().get_item(&key) else {
+            return components;
+        };
+        let Some(list) = item.as_any().downcast_ref::<ConfigList>() else {
+            error!("'{}' should be a list", key);
+            return components;
+        };
+        for i in 0..list.size() {
+            let Some(prescription) = list.get_str_at(i) else {
+                continue;
+            };
+            let ticket = Ticket::new(&self.schema, &self.context, name_space, prescription);
+            let component = Registry::require(&ticket.klass);
+            match component
+                .as_ref()
+                .and_then(|component| component.as_any().downcast_ref::<Component<T>>())
+            {
+                Some(component) => components.push(component.create(&ticket)),
+                None => error!("error creating {}: '{}'", name_space, prescription),
+            }
+        }
+        components
+    }
+
+    pub(crate) fn process_key(&mut self, key_event: &KeyEvent) -> bool {
+        info!("process key: {}", key_event);
+        let mut result = ProcessResult::Noop;
+        for entry in self.processors.clone() {
+            // A processor feeding keys back to the engine is skipped in the
+            // nested round.
+            let Ok(mut processor) = entry.try_write() else {
+                continue;
+            };
+            result = processor.process_key_event(key_event, self);
+            drop(processor);
+            self.flush_context_events();
+            if result != ProcessResult::Noop {
+                break;
+            }
+        }
+        if result == ProcessResult::Accepted {
+            return true;
+        }
+        // record unhandled keys, eg. spaces, numbers, bksp's.
+        self.context
+            .commit_history_mut()
+            .push_key_event(key_event.clone());
+        // notify interested parties
+        self.context
+            .unhandled_key_notifier()
+            .emit(Arc::new(self.context.clone()), key_event.clone());
+        false
+    }
+
+    fn next_context_event(&self) -> Option<ContextEvent> {
+        self.context_events.lock().unwrap().pop_front()
+    }
+
+    // Handles pending context notifications, including those raised while
+    // handling them. Processors call this when they need the composition to
+    // be up to date before going on.
+    pub(crate) fn flush_context_events(&mut self) {
+        while let Some(event) = self.next_context_event() {
+            match event {
+                ContextEvent::Update => self.compose(),
+                ContextEvent::Select => self.on_select(),
+                ContextEvent::Commit {
+                    text,
+                    commit_history,
+                } => {
+                    *self.context.commit_history_mut() = commit_history;
+                    self.sink.emit(text);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn compose(&mut self) {
+        let input = self.context.input().to_string();
+        let caret_pos = self.context.caret_pos();
+        let composition = self.context.composition_mut();
+        composition.reset(&input[..caret_pos]);
+        if caret_pos < input.len() && caret_pos == composition.get_confirmed_position() {
+            // translate one segment past caret pos.
+            composition.reset(&input);
+        }
+        self.calculate_segmentation(caret_pos);
+        self.translate_segments();
+        info!(
+            "composition: {}",
+            self.context.composition().get_debug_text()
+        );
+    }
+
+    fn calculate_segmentation(&mut self, caret_pos: usize) {
+        let segmentation: &mut Segmentation = self.context.composition_mut();
+        while !segmentation.has_finished_segmentation() {
+            let start_pos = segmentation.get_current_start_position();
+            // recognize a segment by calling the segmentors in turn
+            for segmentor in self.segmentors.iter_mut() {
+                if !segmentor.proceed(segmentation) {
+                    break;
+                }
+            }
+            // no advancement
+            if start_pos == segmentation.get_current_end_position() {
+                break;
+            }
+            // only one segment is allowed past caret pos, which is the segment
+            // immediately after the caret.
+            if start_pos >= caret_pos {
+                break;
+            }
+            // move onto the next segment...
+            if !segmentation.has_finished_segmentation() {
+                segmentation.forward();
+            }
+        }
+        // start an empty segment only at the end of a confirmed composition.
+        segmentation.trim();
+        if segmentation
+            .segments
+            .last()
+            .is_some_and(|segment| segment.status >= SegmentStatus::Selected)
+        {
+            segmentation.forward();
+        }
+    }
+
+    fn translate_segments(&mut self) {
+        for i in 0..self.context.composition().segments.len() {
+            let mut segment = self.context.composition().segments[i].clone();
+            if segment.status >= SegmentStatus::Guess || segment.end == segment.start {
+                continue;
+            }
+            let input = self.context.composition().input()[segment.start..segment.end].to_string();
+            info!("translating segment: {}", input);
+            let mut menu = Menu::new();
+            for translator in self.translators.iter_mut() {
+                let Some(translation) = translator.query(&input, &segment, &self.context) else {
+                    continue;
+                };
+                if translation.read().map_or(true, |t| t.exhausted()) {
+                    info!("made a futile translation: {}", input);
+                    continue;
+                }
+                menu.add_translation(translation);
+            }
+            for filter in &self.filters {
+                if filter.applies_to_segment(&segment) {
+                    menu.add_filter(filter.as_ref(), &self.context);
+                }
+            }
+            segment.status = SegmentStatus::Guess;
+            segment.menu = Some(Arc::new(RwLock::new(menu)));
+            segment.selected_index = 0;
+            self.context.composition_mut().segments[i] = segment;
+        }
+    }
+
+    fn on_select(&mut self) {
+        let input_len = self.context.input().len();
+        let caret_pos = self.context.caret_pos();
+        let Some(segment) = self.context.composition_mut().segments.last_mut() else {
+            return;
+        };
+        segment.close();
+        if segment.end == input_len {
+            // composition has finished
+            segment.status = SegmentStatus::Confirmed;
+            // strategy one: commit directly;
+            // strategy two: continue composing with another empty segment.
+            if self.context.get_option("_auto_commit") {
+                self.context.commit();
+            } else {
+                self.context.composition_mut().forward();
+            }
+        } else {
+            let update_caret = segment.end >= caret_pos;
+            self.context.composition_mut().forward();
+            if update_caret {
+                // finished converting current segment
+                // move caret to the end of input
+                self.context.set_caret_pos(input_len);
+            } else {
+                self.compose();
+            }
+        }
+    }
+
+    pub(crate) fn commit_text(&mut self, text: &str) {
+        self.context
+            .commit_history_mut()
+            .push(CommitRecord::new("raw", text));
+        self.sink.emit(text.to_string());
+    }
+
+    pub(crate) fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub(crate) fn context(&self) -> &Context {
+        &self.context
+    }
+
+    pub(crate) fn context_mut(&mut self) -> &mut Context {
+        &mut self.context
+    }
+
+    pub(crate) fn sink(&self) -> &CommitSink {
+        &self.sink
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn test_engine(yaml: &str) -> (Engine, Arc<Mutex<Vec<String>>>) {
+    use crate::rime::config::config_component::Config;
+
+    let mut config = Config::new();
+    assert!(config.load_from_stream(&mut yaml.as_bytes()));
+    let engine = Engine::new(Schema::new("test", config));
+    let committed = Arc::new(Mutex::new(Vec::new()));
+    let sink = committed.clone();
+    engine.sink().connect(move |text: String| {
+        sink.lock().unwrap().push(text);
+    });
+    (engine, committed)
+}
+
+#[test]
+fn process_keys_to_commit() {
+    use crate::rime::candidate::{Candidate, CandidateList, SimpleCandidate};
+    use crate::rime::filter::FilterComponent;
+    use crate::rime::key_table::XK_SPACE;
+    use crate::rime::processor::ProcessorComponent;
+    use crate::rime::segmentation::Segment;
+    use crate::rime::segmentor::SegmentorComponent;
+    use crate::rime::translation::{FifoTranslation, Translation, UniqueTranslation};
+    use crate::rime::translator::TranslatorComponent;
+
+    // Takes letters as input and commits on space.
+    struct StubProcessor;
+
+    impl Processor for StubProcessor {
+        fn process_key_event(
+            &mut self,
+            key_event: &KeyEvent,
+            engine: &mut Engine,
+        ) -> ProcessResult {
+            let ch = key_event.keycode();
+            if ch == XK_SPACE && engine.context().is_composing() {
+                engine.context_mut().commit();
+                return ProcessResult::Accepted;
+            }
+            match char::from_u32(ch).filter(char::is_ascii_lowercase) {
+                Some(ch) => {
+                    engine.context_mut().push_input(ch);
+                    ProcessResult::Accepted
+                }
+                None => ProcessResult::Noop,
+            }
+        }
+    }
+
+    // Takes the rest of the input as one segment.
+    struct StubSegmentor;
+
+    impl Segmentor for StubSegmentor {
+        fn proceed(&mut self, segmentation: &mut Segmentation) -> bool {
+            let start = segmentation.get_current_start_position();
+            let mut segment = Segment::new(start, segmentation.input().len());
+            segment.tags.insert("stub".to_string());
+            segmentation.add_segment(segment);
+            true
+        }
+    }
+
+    // Echoes the input of segments tagged by the stub segmentor.
+    struct StubTranslator;
+
+    impl Translator for StubTranslator {
+        fn query(
+            &mut self,
+            input: &str,
+            segment: &Segment,
+            _context: &Context,
+        ) -> Option<Arc<RwLock<dyn Translation>>> {
+            if !segment.has_tag("stub") {
+                return None;
+            }
+            let candidate = SimpleCandidate::new(
+                "raw".to_string(),
+                segment.start,
+                segment.end,
+                input.to_string(),
+                None,
+                None,
+            );
+            Some(Arc::new(RwLock::new(UniqueTranslation::new(Some(
+                Arc::new(candidate),
+            )))))
+        }
+    }
+
+    // Turns candidate texts into upper case.
+    struct StubFilter;
+
+    impl Filter for StubFilter {
+        fn apply(
+            &self,
+            translation: Arc<RwLock<dyn Translation>>,
+            _candidates: Arc<RwLock<CandidateList>>,
+            _context: &Context,
+        ) -> Arc<RwLock<dyn Translation>> {
+            let mut result = FifoTranslation::new();
+            while let Some(cand) = translation.write().unwrap().next() {
+                let upper: Arc<dyn Candidate> = Arc::new(SimpleCandidate::new(
+                    cand.type_().to_string(),
+                    cand.start(),
+                    cand.end(),
+                    cand.text().to_uppercase(),
+                    None,
+                    None,
+                ));
+                result.append(Some(upper));
+            }
+            Arc::new(RwLock::new(result))
+        }
+    }
+
+    let registry = Registry::instance();
+    registry.register(
+        "stub_processor",
+        Arc::new(ProcessorComponent::new(|_| {
+            Arc::new(RwLock::new(StubProcessor))
+        })),
+    );
+    registry.register(
+        "stub_segmentor",
+        Arc::new(SegmentorComponent::new(|_| Box::new(StubSegmentor))),
+    );
+    registry.register(
+        "stub_translator",
+        Arc::new(TranslatorComponent::new(|_| Box::new(StubTranslator))),
+    );
+    registry.register(
+        "stub_filter",
+        Arc::new(FilterComponent::new(|_| Box::new(StubFilter))),
+    );
+    let (mut engine, committed) = test_engine(
+        "engine:
+  processors: [stub_processor]
+  segmentors: [stub_segmentor]
+  translators: [stub_translator]
+  filters: [stub_filter]
+",
+    );
+    for ch in "ab".chars() {
+        assert!(engine.process_key(&KeyEvent::new(ch as u32, 0)));
+    }
+    assert_eq!("ab", engine.context().input());
+    let cand = engine.context().get_selected_candidate().unwrap();
+    assert_eq!("AB", cand.text());
+    assert!(committed.lock().unwrap().is_empty());
+    // keys left alone by the processors are not consumed.
+    assert!(!engine.process_key(&KeyEvent::new('1' as u32, 0)));
+
+    assert!(engine.process_key(&KeyEvent::new(XK_SPACE, 0)));
+    assert_eq!(vec!["AB"], *committed.lock().unwrap());
+    assert!(!engine.context().is_composing());
+}
@@ -1,6 +1,22 @@
-use crate::rime::{candidate::CandidateList, translation::Translation};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+
+use crate::rime::candidate::CandidateList;
+use crate::rime::component::Component;
+use crate::rime::context::Context;
+use crate::rime::segmentation::Segment;
+use crate::rime::translation::Translation;
 
 pub(crate) trait Filter {
-    fn apply(&self, translation: Arc<dyn Translation>, candidates: &mut CandidateList);
+    fn apply(
+        &self,
+        translation: Arc<RwLock<dyn Translation>>,
+        candidates: Arc<RwLock<CandidateList>>,
+        context: &Context,
+    ) -> Arc<RwLock<dyn Translation>>;
+
+    fn applies_to_segment(&self, _segment: &Segment) -> bool {
+        true
+    }
 }
+
+pub(crate) type FilterComponent = Component<Box<dyn Filter>>;
@@ -0,0 +1,222 @@
+pub(crate) mod abc_segmentor;
+pub(crate) mod affix_segmentor;
+pub(crate) mod ascii_composer;
+pub(crate) mod charset_filter;
+pub(crate) mod chord_composer;
+pub(crate) mod echo_translator;
+pub(crate) mod editor;
+pub(crate) mod fallback_segmentor;
+pub(crate) mod grammar;
+pub(crate) mod history_translator;
+pub(crate) mod key_binder;
+pub(crate) mod navigator;
+pub(crate) mod poet;
+pub(crate) mod punctuator;
+pub(crate) mod recognizer;
+pub(crate) mod reverse_lookup_filter;
+pub(crate) mod reverse_lookup_translator;
+pub(crate) mod script_translator;
+pub(crate) mod selector;
+pub(crate) mod simplifier;
+pub(crate) mod single_char_filter;
+pub(crate) mod speller;
+pub(crate) mod switcher;
+pub(crate) mod table_translator;
+pub(crate) mod translator_commons;
+pub(crate) mod uniquifier;
+
+use std::sync::{Arc, Once, RwLock};
+
+use crate::rime::gear::abc_segmentor::AbcSegmentor;
+use crate::rime::gear::affix_segmentor::AffixSegmentor;
+use crate::rime::gear::ascii_composer::AsciiComposer;
+use crate::rime::gear::charset_filter::CharsetFilter;
+use crate::rime::gear::chord_composer::ChordComposer;
+use crate::rime::gear::echo_translator::EchoTranslator;
+use crate::rime::gear::editor::Editor;
+use crate::rime::gear::fallback_segmentor::FallbackSegmentor;
+use crate::rime::gear::history_translator::HistoryTranslator;
+use crate::rime::gear::key_binder::KeyBinder;
+use crate::rime::gear::navigator::Navigator;
+use crate::rime::gear::punctuator::{PunctSegmentor, PunctTranslator, Punctuator};
+use crate::rime::gear::recognizer::{Matcher, Recognizer};
+use crate::rime::gear::reverse_lookup_filter::ReverseLookupFilter;
+use crate::rime::gear::reverse_lookup_translator::ReverseLookupTranslator;
+use crate::rime::gear::script_translator::ScriptTranslator;
+use crate::rime::gear::selector::Selector;
+use crate::rime::gear::simplifier::Simplifier;
+use crate::rime::gear::single_char_filter::SingleCharFilter;
+use crate::rime::gear::speller::Speller;
+use crate::rime::gear::switcher::Switcher;
+use crate::rime::gear::table_translator::TableTranslator;
+use crate::rime::gear::uniquifier::Uniquifier;
+use crate::rime::filter::FilterComponent;
+use crate::rime::processor::ProcessorComponent;
+use crate::rime::registry::Registry;
+use crate::rime::segmentor::SegmentorComponent;
+use crate::rime::translator::TranslatorComponent;
+
+static INIT: Once = Once::new();
+
+// Registers the built-in engine components.
+pub(crate) fn initialize() {
+    INIT.call_once(|| {
+        let registry = Registry::instance();
+        registry.register(
+            "switcher",
+            Arc::new(ProcessorComponent::new(|ticket| {
+                Arc::new(RwLock::new(Switcher::new(ticket)))
+            })),
+        );
+        registry.register(
+            "ascii_composer",
+            Arc::new(ProcessorComponent::new(|ticket| {
+                Arc::new(RwLock::new(AsciiComposer::new(ticket)))
+            })),
+        );
+        registry.register(
+            "chord_composer",
+            Arc::new(ProcessorComponent::new(|ticket| {
+                Arc::new(RwLock::new(ChordComposer::new(ticket)))
+            })),
+        );
+        registry.register(
+            "recognizer",
+            Arc::new(ProcessorComponent::new(|ticket| {
+                Arc::new(RwLock::new(Recognizer::new(ticket)))
+            })),
+        );
+        registry.register(
+            "key_binder",
+            Arc::new(ProcessorComponent::new(|ticket| {
+                Arc::new(RwLock::new(KeyBinder::new(ticket)))
+            })),
+        );
+        registry.register(
+            "speller",
+            Arc::new(ProcessorComponent::new(|ticket| {
+                Arc::new(RwLock::new(Speller::new(ticket)))
+            })),
+        );
+        registry.register(
+            "selector",
+            Arc::new(ProcessorComponent::new(|ticket| {
+                Arc::new(RwLock::new(Selector::new(ticket)))
+            })),
+        );
+        registry.register(
+            "punctuator",
+            Arc::new(ProcessorComponent::new(|ticket| {
+                Arc::new(RwLock::new(Punctuator::new(ticket)))
+            })),
+        );
+        registry.register(
+            "navigator",
+            Arc::new(ProcessorComponent::new(|ticket| {
+                Arc::new(RwLock::new(Navigator::new(ticket)))
+            })),
+        );
+        registry.register(
+            "express_editor",
+            Arc::new(ProcessorComponent::new(|ticket| {
+                Arc::new(RwLock::new(Editor::express(ticket)))
+            })),
+        );
+        registry.register(
+            "fluid_editor",
+            Arc::new(ProcessorComponent::new(|ticket| {
+                Arc::new(RwLock::new(Editor::fluid(ticket)))
+            })),
+        );
+        registry.register(
+            "abc_segmentor",
+            Arc::new(SegmentorComponent::new(|ticket| {
+                Box::new(AbcSegmentor::new(ticket))
+            })),
+        );
+        registry.register(
+            "affix_segmentor",
+            Arc::new(SegmentorComponent::new(|ticket| {
+                Box::new(AffixSegmentor::new(ticket))
+            })),
+        );
+        registry.register(
+            "fallback_segmentor",
+            Arc::new(SegmentorComponent::new(|ticket| {
+                Box::new(FallbackSegmentor::new(ticket))
+            })),
+        );
+        registry.register(
+            "matcher",
+            Arc::new(SegmentorComponent::new(|ticket| Box::new(Matcher::new(ticket)))),
+        );
+        registry.register(
+            "punct_segmentor",
+            Arc::new(SegmentorComponent::new(|ticket| {
+                Box::new(PunctSegmentor::new(ticket))
+            })),
+        );
+        registry.register(
+            "punct_translator",
+            Arc::new(TranslatorComponent::new(|ticket| {
+                Box::new(PunctTranslator::new(ticket))
+            })),
+        );
+        registry.register(
+            "script_translator",
+            Arc::new(TranslatorComponent::new(|ticket| {
+                Box::new(ScriptTranslator::new(ticket))
+            })),
+        );
+        registry.register(
+            "table_translator",
+            Arc::new(TranslatorComponent::new(|ticket| {
+                Box::new(TableTranslator::new(ticket))
+            })),
+        );
+        registry.register(
+            "echo_translator",
+            Arc::new(TranslatorComponent::new(|ticket| {
+                Box::new(EchoTranslator::new(ticket))
+            })),
+        );
+        registry.register(
+            "history_translator",
+            Arc::new(TranslatorComponent::new(|ticket| {
+                Box::new(HistoryTranslator::new(ticket))
+            })),
+        );
+        registry.register(
+            "reverse_lookup_translator",
+            Arc::new(TranslatorComponent::new(|ticket| {
+                Box::new(ReverseLookupTranslator::new(ticket))
+            })),
+        );
+        registry.register(
+            "reverse_lookup_filter",
+            Arc::new(FilterComponent::new(|ticket| {
+                Box::new(ReverseLookupFilter::new(ticket))
+            })),
+        );
+        registry.register(
+            "uniquifier",
+            Arc::new(FilterComponent::new(|ticket| Box::new(Uniquifier::new(ticket)))),
+        );
+        registry.register(
+            "single_char_filter",
+            Arc::new(FilterComponent::new(|ticket| {
+                Box::new(SingleCharFilter::new(ticket))
+            })),
+        );
+        registry.register(
+            "charset_filter",
+            Arc::new(FilterComponent::new(|ticket| {
+                Box::new(CharsetFilter::new(ticket))
+            })),
+        );
+        registry.register(
+            "simplifier",
+            Arc::new(FilterComponent::new(|ticket| Box::new(Simplifier::new(ticket)))),
+        );
+    });
+}
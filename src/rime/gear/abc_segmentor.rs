@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+
+use log::info;
+
+use crate::rime::component::Ticket;
+use crate::rime::gear::speller::RIME_ALPHABET;
+use crate::rime::segmentation::{Segment, Segmentation};
+use crate::rime::segmentor::Segmentor;
+
+const TAG: &str = "abc";
+
+// Tags runs of spelling characters and delimiters as `abc`.
+pub(crate) struct AbcSegmentor {
+    alphabet: String,
+    delimiter: String,
+    initials: String,
+    finals: String,
+    extra_tags: HashSet<String>,
+}
+
+impl AbcSegmentor {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        let config = ticket.schema.config();
+        let mut alphabet = config.get_string("speller/alphabet");
+        if alphabet.is_empty() {
+            alphabet = RIME_ALPHABET.to_string();
+        }
+        let mut initials = config.get_string("speller/initials");
+        if initials.is_empty() {
+            initials = alphabet.clone();
+        }
+        let mut extra_tags = HashSet::new();
+        if let Some(list) = config.get_list("abc_segmentor/extra_tags") {
+            for i in 0..list.size() {
+                if let Some(tag) = list.get_str_at(i) {
+                    extra_tags.insert(tag.to_string());
+                }
+            }
+        }
+        Self {
+            alphabet,
+            delimiter: config.get_string("speller/delimiter"),
+            initials,
+            finals: config.get_string("speller/finals"),
+            extra_tags,
+        }
+    }
+}
+
+impl Segmentor for AbcSegmentor {
+    fn proceed(&mut self, segmentation: &mut Segmentation) -> bool {
+        let input = segmentation.input();
+        info!("abc_segmentor: {}", input);
+        let j = segmentation.get_current_start_position();
+        let mut k = j;
+        let mut expecting_an_initial = true;
+        for ch in input[j..].chars() {
+            let is_letter = self.alphabet.contains(ch);
+            let is_delimiter = k != j && self.delimiter.contains(ch);
+            if !is_letter && !is_delimiter {
+                break;
+            }
+            let is_initial = self.initials.contains(ch);
+            let is_final = self.finals.contains(ch);
+            if expecting_an_initial && !is_initial && !is_final {
+                // not a valid spelling.
+                break;
+            }
+            expecting_an_initial = is_delimiter || is_final;
+            k += ch.len_utf8();
+        }
+        info!("[{}, {})", j, k);
+        if j < k {
+            let mut segment = Segment::new(j, k);
+            segment.tags.insert(TAG.to_string());
+            segment.tags.extend(self.extra_tags.iter().cloned());
+            segmentation.add_segment(segment);
+        }
+        // continue this round
+        true
+    }
+}
+
+#[test]
+fn segment_spellings_and_fall_back_to_raw() {
+    use crate::rime::engine::test_engine;
+
+    let (mut engine, _) = test_engine(
+        "speller:
+  alphabet: abcdefghijklmnopqrstuvwxyz
+  delimiter: \"'\"
+engine:
+  segmentors: [abc_segmentor, fallback_segmentor]
+",
+    );
+    let mut segment = |input: &str| {
+        engine.context_mut().set_input(input.to_string());
+        engine.flush_context_events();
+        engine
+            .context()
+            .composition()
+            .segments
+            .iter()
+            .map(|segment| {
+                let tag = if segment.has_tag(TAG) { TAG } else { "raw" };
+                (segment.start, segment.end, tag)
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(vec![(0, 6, "abc"), (6, 8, "raw")], segment("ni'hao1!"));
+    // a delimiter does not start a spelling
+    assert_eq!(vec![(0, 1, "raw"), (1, 3, "abc")], segment("'ni"));
+}
@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+
+use log::info;
+
+use crate::rime::component::Ticket;
+use crate::rime::segmentation::{Segment, SegmentStatus, Segmentation};
+use crate::rime::segmentor::Segmentor;
+
+// Splits a segment tagged by a previous segmentor into prefix, code and
+// suffix, e.g. "`abc'" for reverse lookup. Only the code segment is
+// translated.
+pub(crate) struct AffixSegmentor {
+    tag: String,
+    prefix: String,
+    suffix: String,
+    tips: String,
+    closing_tips: String,
+    extra_tags: HashSet<String>,
+}
+
+impl AffixSegmentor {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        let config = ticket.schema.config();
+        let name_space = &ticket.name_space;
+        let mut tag = config.get_string(&format!("{}/tag", name_space));
+        if tag.is_empty() {
+            tag = "abc".to_string();
+        }
+        let mut extra_tags = HashSet::new();
+        if let Some(list) = config.get_list(&format!("{}/extra_tags", name_space)) {
+            for i in 0..list.size() {
+                if let Some(tag) = list.get_str_at(i) {
+                    extra_tags.insert(tag.to_string());
+                }
+            }
+        }
+        Self {
+            tag,
+            prefix: config.get_string(&format!("{}/prefix", name_space)),
+            suffix: config.get_string(&format!("{}/suffix", name_space)),
+            tips: config.get_string(&format!("{}/tips", name_space)),
+            closing_tips: config.get_string(&format!("{}/closing_tips", name_space)),
+            extra_tags,
+        }
+    }
+}
+
+impl Segmentor for AffixSegmentor {
+    fn proceed(&mut self, segmentation: &mut Segmentation) -> bool {
+        if !segmentation
+            .segments
+            .last()
+            .is_some_and(|segment| segment.has_tag(&self.tag))
+        {
+            return true;
+        }
+        let j = segmentation.get_current_start_position();
+        let k = segmentation.get_current_end_position();
+        let active_input = &segmentation.input()[j..k];
+        if !active_input.starts_with(&self.prefix) {
+            return true;
+        }
+        let code_start = j + self.prefix.len();
+        let mut code_end = k;
+        if !self.suffix.is_empty() && active_input[self.prefix.len()..].ends_with(&self.suffix) {
+            code_end -= self.suffix.len();
+        }
+        info!(
+            "affix segmentor: [{}, {}) [{}, {}) [{}, {})",
+            j, code_start, code_start, code_end, code_end, k
+        );
+        let Some(last) = segmentation.segments.last_mut() else {
+            return true;
+        };
+        if code_start > j {
+            let mut prefix_segment = Segment::new(j, code_start);
+            prefix_segment.status = SegmentStatus::Guess;
+            prefix_segment.tags.insert(self.tag.clone());
+            prefix_segment.prompt = self.tips.clone();
+            *last = prefix_segment;
+            if code_start == code_end {
+                // waiting for the code
+                return false;
+            }
+            segmentation.forward();
+        }
+        let mut code_segment = Segment::new(code_start, code_end);
+        code_segment.tags.insert(self.tag.clone());
+        code_segment.tags.extend(self.extra_tags.iter().cloned());
+        code_segment.prompt = self.tips.clone();
+        if code_start > j {
+            segmentation.add_segment(code_segment);
+        } else if let Some(last) = segmentation.segments.last_mut() {
+            *last = code_segment;
+        }
+        if code_end < k {
+            segmentation.forward();
+            let mut suffix_segment = Segment::new(code_end, k);
+            suffix_segment.status = SegmentStatus::Guess;
+            suffix_segment.tags.insert(self.tag.clone());
+            suffix_segment.prompt = self.closing_tips.clone();
+            segmentation.add_segment(suffix_segment);
+        }
+        // exclusive
+        false
+    }
+}
+
+#[test]
+fn split_prefix_code_and_suffix() {
+    use crate::rime::engine::test_engine;
+
+    let (mut engine, _) = test_engine(
+        "reverse_lookup:
+  tag: reverse_lookup
+  prefix: \"`\"
+  suffix: \"'\"
+  tips: lookup
+recognizer:
+  patterns:
+    reverse_lookup: \"^`[a-z]*'?$\"
+engine:
+  segmentors: [matcher, affix_segmentor@reverse_lookup]
+",
+    );
+    let mut segment = |input: &str| {
+        engine.context_mut().set_input(input.to_string());
+        engine.flush_context_events();
+        engine
+            .context()
+            .composition()
+            .segments
+            .iter()
+            .map(|segment| (segment.start, segment.end, segment.prompt.clone()))
+            .collect::<Vec<_>>()
+    };
+    // waiting for the code after the prefix
+    assert_eq!(vec![(0, 1, "lookup".to_string())], segment("`"));
+    assert_eq!(
+        vec![
+            (0, 1, "lookup".to_string()),
+            (1, 4, "lookup".to_string()),
+            (4, 5, String::new()),
+        ],
+        segment("`abc'")
+    );
+    let segments = &engine.context().composition().segments;
+    assert_eq!(SegmentStatus::Guess, segments[0].status);
+    assert!(segments[1].has_tag("reverse_lookup"));
+}
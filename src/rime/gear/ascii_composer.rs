@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+
+use crate::rime::component::Ticket;
+use crate::rime::config::config_types::{ConfigMap, ConfigValue};
+use crate::rime::engine::Engine;
+use crate::rime::key_event::KeyEvent;
+use crate::rime::key_table::{
+    XK_CAPS_LOCK, XK_CONTROL_L, XK_CONTROL_R, XK_EISU_TOGGLE, XK_SHIFT_L, XK_SHIFT_R, XK_SPACE,
+};
+use crate::rime::processor::{ProcessResult, Processor};
+
+// What becomes of the composition when ascii mode is switched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AsciiModeSwitchStyle {
+    Noop,
+    Inline,
+    CommitText,
+    CommitCode,
+    Clear,
+}
+
+impl AsciiModeSwitchStyle {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "inline_ascii" => AsciiModeSwitchStyle::Inline,
+            "commit_text" => AsciiModeSwitchStyle::CommitText,
+            "commit_code" => AsciiModeSwitchStyle::CommitCode,
+            "clear" => AsciiModeSwitchStyle::Clear,
+            _ => AsciiModeSwitchStyle::Noop,
+        }
+    }
+}
+
+pub(crate) struct AsciiComposer {
+    bindings: HashMap<u32, AsciiModeSwitchStyle>,
+    caps_lock_switch_style: AsciiModeSwitchStyle,
+    good_old_caps_lock: bool,
+    toggle_with_caps: bool,
+    shift_key_pressed: bool,
+    ctrl_key_pressed: bool,
+    toggle_expired: Instant,
+    // ascii mode was turned on temporarily for the current composition
+    inline_ascii: bool,
+}
+
+impl AsciiComposer {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        let mut ascii_composer = Self {
+            bindings: HashMap::new(),
+            caps_lock_switch_style: AsciiModeSwitchStyle::Noop,
+            good_old_caps_lock: false,
+            toggle_with_caps: false,
+            shift_key_pressed: false,
+            ctrl_key_pressed: false,
+            toggle_expired: Instant::now(),
+            inline_ascii: false,
+        };
+        ascii_composer.load_config(ticket);
+        ascii_composer
+    }
+
+    fn load_config(&mut self, ticket: &Ticket) {
+        let config = ticket.schema.config();
+        self.good_old_caps_lock = config.get_bool("ascii_composer/good_old_caps_lock");
+        if let Some(item) = config.get_item("ascii_composer/switch_key") {
+            match item.as_any().downcast_ref::<ConfigMap>() {
+                Some(bindings) => self.load_bindings(bindings),
+                None => warn!("ascii_composer/switch_key should be a map."),
+            }
+        }
+        if let Some(&style) = self.bindings.get(&XK_CAPS_LOCK) {
+            self.caps_lock_switch_style = match style {
+                // can't do that
+                AsciiModeSwitchStyle::Inline => AsciiModeSwitchStyle::Clear,
+                style => style,
+            };
+        }
+    }
+
+    // Switch keys map key names to style names, e.g. `Shift_L: inline_ascii`.
+    fn load_bindings(&mut self, bindings: &ConfigMap) {
+        for (key, value) in &bindings.map {
+            let Some(value) = value.as_any().downcast_ref::<ConfigValue>() else {
+                continue;
+            };
+            let style = AsciiModeSwitchStyle::from_name(value.str());
+            if style == AsciiModeSwitchStyle::Noop {
+                continue;
+            }
+            let mut key_event = KeyEvent::default();
+            if !key_event.parse(key) || key_event.modifier() != 0 {
+                warn!("invalid ascii mode switch key: {}", key);
+                continue;
+            }
+            self.bindings.insert(key_event.keycode(), style);
+        }
+    }
+
+    fn process_caps_lock(&mut self, key_event: &KeyEvent, engine: &mut Engine) -> ProcessResult {
+        let ch = key_event.keycode();
+        if ch == XK_CAPS_LOCK {
+            if key_event.release() {
+                return ProcessResult::Rejected;
+            }
+            self.shift_key_pressed = false;
+            self.ctrl_key_pressed = false;
+            // temporarily disable good-old (uppercase) Caps Lock as mode switch
+            // key in case the user switched to ascii mode with other keys.
+            if self.good_old_caps_lock
+                && !self.toggle_with_caps
+                && engine.context().get_option("ascii_mode")
+            {
+                return ProcessResult::Rejected;
+            }
+            // the Caps Lock modifier is clear when we are about to turn it on.
+            self.toggle_with_caps = !key_event.caps();
+            self.switch_ascii_mode(!key_event.caps(), self.caps_lock_switch_style, engine);
+            return ProcessResult::Accepted;
+        }
+        if key_event.caps() {
+            let is_alphabetic = char::from_u32(ch).is_some_and(|ch| ch.is_ascii_alphabetic());
+            if !self.good_old_caps_lock && !key_event.release() && !key_event.ctrl() && is_alphabetic
+            {
+                // output ascii characters ignoring Caps Lock
+                let ch = ch as u8 as char;
+                let ch = if ch.is_ascii_lowercase() {
+                    ch.to_ascii_uppercase()
+                } else {
+                    ch.to_ascii_lowercase()
+                };
+                engine.commit_text(&ch.to_string());
+                return ProcessResult::Accepted;
+            }
+            return ProcessResult::Rejected;
+        }
+        ProcessResult::Noop
+    }
+
+    fn toggle_ascii_mode_with_key(&mut self, keycode: u32, engine: &mut Engine) -> bool {
+        let Some(&style) = self.bindings.get(&keycode) else {
+            return false;
+        };
+        let ascii_mode = !engine.context().get_option("ascii_mode");
+        self.switch_ascii_mode(ascii_mode, style, engine);
+        self.toggle_with_caps = keycode == XK_CAPS_LOCK;
+        true
+    }
+
+    fn switch_ascii_mode(
+        &mut self,
+        ascii_mode: bool,
+        style: AsciiModeSwitchStyle,
+        engine: &mut Engine,
+    ) {
+        info!("ascii mode: {}, switch style: {:?}", ascii_mode, style);
+        let context = engine.context_mut();
+        if context.is_composing() {
+            self.inline_ascii = false;
+            match style {
+                AsciiModeSwitchStyle::Inline => {
+                    info!(
+                        "converting current composition to {} mode.",
+                        if ascii_mode { "ascii" } else { "non-ascii" }
+                    );
+                    self.inline_ascii = ascii_mode;
+                }
+                AsciiModeSwitchStyle::CommitText => {
+                    context.confirm_current_selection();
+                }
+                AsciiModeSwitchStyle::CommitCode => {
+                    context.clear_non_confirmed_composition();
+                    context.commit();
+                }
+                AsciiModeSwitchStyle::Clear => {
+                    context.clear();
+                }
+                AsciiModeSwitchStyle::Noop => {}
+            }
+        }
+        // refresh non-confirmed composition with new mode
+        engine.context_mut().set_option("ascii_mode", ascii_mode);
+    }
+
+    // Quits the temporary ascii mode once the composition is gone. This is
+    // checked on the next key, since components can't watch the context.
+    fn check_inline_ascii(&mut self, engine: &mut Engine) {
+        if self.inline_ascii && !engine.context().is_composing() {
+            self.inline_ascii = false;
+            engine.context_mut().set_option("ascii_mode", false);
+        }
+    }
+}
+
+impl Processor for AsciiComposer {
+    fn process_key_event(&mut self, key_event: &KeyEvent, engine: &mut Engine) -> ProcessResult {
+        self.check_inline_ascii(engine);
+        if (key_event.shift() && key_event.ctrl()) || key_event.alt() || key_event.super_() {
+            self.shift_key_pressed = false;
+            self.ctrl_key_pressed = false;
+            return ProcessResult::Noop;
+        }
+        if self.caps_lock_switch_style != AsciiModeSwitchStyle::Noop {
+            let result = self.process_caps_lock(key_event, engine);
+            if result != ProcessResult::Noop {
+                return result;
+            }
+        }
+        let ch = key_event.keycode();
+        if ch == XK_EISU_TOGGLE {
+            // Alphanumeric toggle
+            if key_event.release() {
+                return ProcessResult::Rejected;
+            }
+            self.shift_key_pressed = false;
+            self.ctrl_key_pressed = false;
+            self.toggle_ascii_mode_with_key(ch, engine);
+            return ProcessResult::Accepted;
+        }
+        let is_shift = ch == XK_SHIFT_L || ch == XK_SHIFT_R;
+        let is_ctrl = ch == XK_CONTROL_L || ch == XK_CONTROL_R;
+        if is_shift || is_ctrl {
+            if key_event.release() {
+                if self.shift_key_pressed || self.ctrl_key_pressed {
+                    if ((is_shift && self.shift_key_pressed) || (is_ctrl && self.ctrl_key_pressed))
+                        && Instant::now() < self.toggle_expired
+                    {
+                        self.toggle_ascii_mode_with_key(ch, engine);
+                    }
+                    self.shift_key_pressed = false;
+                    self.ctrl_key_pressed = false;
+                }
+            } else if !(self.shift_key_pressed || self.ctrl_key_pressed) {
+                // first key down
+                if is_shift {
+                    self.shift_key_pressed = true;
+                } else {
+                    self.ctrl_key_pressed = true;
+                }
+                // will not toggle unless the toggle key is released shortly
+                self.toggle_expired = Instant::now() + Duration::from_millis(500);
+            }
+            return ProcessResult::Noop;
+        }
+        // other keys
+        self.shift_key_pressed = false;
+        self.ctrl_key_pressed = false;
+        // possible key binding: Control+x, Shift+space
+        if key_event.ctrl() || (key_event.shift() && ch == XK_SPACE) {
+            return ProcessResult::Noop;
+        }
+        let context = engine.context_mut();
+        if context.get_option("ascii_mode") {
+            if !context.is_composing() {
+                // direct commit
+                return ProcessResult::Rejected;
+            }
+            // edit inline ascii string
+            if !key_event.release() && (0x20..0x80).contains(&ch) {
+                context.push_input(ch as u8 as char);
+                return ProcessResult::Accepted;
+            }
+        }
+        ProcessResult::Noop
+    }
+}
+
+#[cfg(test)]
+fn key(repr: &str) -> KeyEvent {
+    repr.parse().unwrap()
+}
+
+#[test]
+fn toggle_ascii_mode_by_shift_tap() {
+    use crate::rime::engine::test_engine;
+
+    let (mut engine, _) = test_engine(
+        "ascii_composer:
+  switch_key:
+    Shift_L: commit_code
+engine:
+  processors: [ascii_composer]
+",
+    );
+    assert!(!engine.process_key(&key("Shift_L")));
+    assert!(!engine.process_key(&key("Shift+Release+Shift_L")));
+    assert!(engine.context().get_option("ascii_mode"));
+    // keys are passed through in ascii mode
+    assert!(!engine.process_key(&key("a")));
+
+    // no toggle when another key is pressed in between
+    assert!(!engine.process_key(&key("Shift_L")));
+    assert!(!engine.process_key(&key("Shift+A")));
+    assert!(!engine.process_key(&key("Shift+Release+Shift_L")));
+    assert!(engine.context().get_option("ascii_mode"));
+
+    assert!(!engine.process_key(&key("Shift_L")));
+    assert!(!engine.process_key(&key("Shift+Release+Shift_L")));
+    assert!(!engine.context().get_option("ascii_mode"));
+}
+
+#[test]
+fn toggle_ascii_mode_by_caps_lock() {
+    use crate::rime::engine::test_engine;
+
+    let (mut engine, committed) = test_engine(
+        "ascii_composer:
+  switch_key:
+    Caps_Lock: clear
+engine:
+  processors: [ascii_composer]
+",
+    );
+    assert!(engine.process_key(&key("Caps_Lock")));
+    assert!(!engine.process_key(&key("Lock+Release+Caps_Lock")));
+    assert!(engine.context().get_option("ascii_mode"));
+    // letters are committed as if Caps Lock were off
+    assert!(engine.process_key(&key("Lock+a")));
+    assert_eq!(vec!["A"], *committed.lock().unwrap());
+
+    assert!(engine.process_key(&key("Lock+Caps_Lock")));
+    assert!(!engine.context().get_option("ascii_mode"));
+}
@@ -0,0 +1,135 @@
+use std::sync::{Arc, RwLock};
+
+use crate::rime::candidate::{Candidate, CandidateList};
+use crate::rime::component::Ticket;
+use crate::rime::context::Context;
+use crate::rime::filter::Filter;
+use crate::rime::translation::Translation;
+
+// CJK Unified Ideographs Extension B-I and the compatibility supplement,
+// which most fonts don't cover.
+pub(crate) fn is_extended_cjk(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x20000..=0x2A6DF
+            | 0x2A700..=0x2EE5F
+            | 0x2F800..=0x2FA1F
+            | 0x30000..=0x323AF
+    )
+}
+
+// Whether the text is made of characters in the common charset.
+pub(crate) fn filter_text(text: &str) -> bool {
+    !text.chars().any(is_extended_cjk)
+}
+
+// Skips candidates with characters out of the common charset.
+pub(crate) struct CharsetFilterTranslation {
+    translation: Arc<RwLock<dyn Translation>>,
+    exhausted: bool,
+}
+
+impl CharsetFilterTranslation {
+    pub(crate) fn new(translation: Arc<RwLock<dyn Translation>>) -> Self {
+        let mut filtered = Self {
+            translation,
+            exhausted: false,
+        };
+        filtered.locate_next_candidate();
+        filtered
+    }
+
+    fn locate_next_candidate(&mut self) -> bool {
+        let Ok(mut translation) = self.translation.write() else {
+            self.exhausted = true;
+            return false;
+        };
+        while let Some(candidate) = translation.peek() {
+            if filter_text(candidate.text()) {
+                return true;
+            }
+            translation.next();
+        }
+        self.exhausted = true;
+        false
+    }
+}
+
+impl Translation for CharsetFilterTranslation {
+    fn next(&mut self) -> Option<Arc<dyn Candidate>> {
+        if self.exhausted {
+            return None;
+        }
+        let candidate = self.translation.write().ok()?.next();
+        self.locate_next_candidate();
+        candidate
+    }
+
+    fn peek(&self) -> Option<Arc<dyn Candidate>> {
+        if self.exhausted {
+            return None;
+        }
+        self.translation.read().ok()?.peek()
+    }
+
+    fn exhausted(&self) -> bool {
+        self.exhausted
+    }
+}
+
+// Hides rare characters from the menu unless the "extended_charset" option
+// is on.
+pub(crate) struct CharsetFilter;
+
+impl CharsetFilter {
+    pub(crate) fn new(_ticket: &Ticket) -> Self {
+        Self
+    }
+}
+
+impl Filter for CharsetFilter {
+    fn apply(
+        &self,
+        translation: Arc<RwLock<dyn Translation>>,
+        _candidates: Arc<RwLock<CandidateList>>,
+        context: &Context,
+    ) -> Arc<RwLock<dyn Translation>> {
+        if context.get_option("extended_charset") {
+            return translation;
+        }
+        Arc::new(RwLock::new(CharsetFilterTranslation::new(translation)))
+    }
+}
+
+#[test]
+fn hide_extended_charset() {
+    use crate::rime::candidate::SimpleCandidate;
+    use crate::rime::menu::Menu;
+    use crate::rime::translation::FifoTranslation;
+
+    let texts = |context: &Context| {
+        let mut translation = FifoTranslation::new();
+        for text in ["\u{20000}", "好", "\u{2A700}好", "\u{2B740}", "嗎"] {
+            translation.append(Some(Arc::new(SimpleCandidate::new(
+                "simple".to_string(),
+                0,
+                1,
+                text.to_string(),
+                None,
+                None,
+            ))));
+        }
+        let mut menu = Menu::new();
+        menu.add_translation(Arc::new(RwLock::new(translation)));
+        menu.add_filter(&CharsetFilter, context);
+        menu.prepare(5);
+        (0..5)
+            .filter_map(|i| menu.get_candidate_at(i))
+            .map(|candidate| candidate.text().to_string())
+            .collect::<Vec<_>>()
+    };
+    let mut context = Context::new();
+    assert_eq!(vec!["好", "嗎"], texts(&context));
+    context.set_option("extended_charset", true);
+    assert_eq!(5, texts(&context).len());
+}
@@ -0,0 +1,232 @@
+use std::collections::BTreeSet;
+
+use log::{error, info};
+
+use crate::rime::algo::algebra::Projection;
+use crate::rime::component::Ticket;
+use crate::rime::engine::Engine;
+use crate::rime::key_event::{KeyEvent, KeySequence};
+use crate::rime::key_table::XK_RETURN;
+use crate::rime::processor::{ProcessResult, Processor};
+
+const ZERO_WIDTH_SPACE: char = '\u{200b}';
+
+// Collects keys pressed at the same time and, once the chord is released,
+// feeds the code it spells into the engine.
+pub(crate) struct ChordComposer {
+    alphabet: String,
+    algebra: Projection,
+    output_format: Projection,
+    prompt_format: Projection,
+    use_control: bool,
+    use_shift: bool,
+    use_alt: bool,
+    use_super: bool,
+    finish_chord_on_first_key_release: bool,
+    // keys being held down
+    pressed: BTreeSet<u32>,
+    // all keys pressed since the chord started
+    chord: BTreeSet<u32>,
+    // keys typed in the current composition, in their original order
+    raw_sequence: String,
+}
+
+impl ChordComposer {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        let config = ticket.schema.config();
+        let mut algebra = Projection::new();
+        algebra.load(config.get_list("chord_composer/algebra"));
+        let mut output_format = Projection::new();
+        output_format.load(config.get_list("chord_composer/output_format"));
+        let mut prompt_format = Projection::new();
+        prompt_format.load(config.get_list("chord_composer/prompt_format"));
+        Self {
+            alphabet: config.get_string("chord_composer/alphabet"),
+            algebra,
+            output_format,
+            prompt_format,
+            use_control: config.get_bool("chord_composer/use_control"),
+            use_shift: config.get_bool("chord_composer/use_shift"),
+            use_alt: config.get_bool("chord_composer/use_alt"),
+            use_super: config.get_bool("chord_composer/use_super"),
+            finish_chord_on_first_key_release: config
+                .get_bool("chord_composer/finish_chord_on_first_key_release"),
+            pressed: BTreeSet::new(),
+            chord: BTreeSet::new(),
+            raw_sequence: String::new(),
+        }
+    }
+
+    fn process_function_key(&mut self, key_event: &KeyEvent, engine: &mut Engine) -> ProcessResult {
+        if key_event.release() {
+            return ProcessResult::Noop;
+        }
+        if key_event.keycode() == XK_RETURN {
+            if !self.raw_sequence.is_empty() {
+                // commit raw input
+                let raw_sequence = std::mem::take(&mut self.raw_sequence);
+                engine.context_mut().set_input(raw_sequence);
+                engine.flush_context_events();
+            }
+            self.clear_chord(engine);
+        }
+        ProcessResult::Noop
+    }
+
+    fn process_chording_key(&mut self, key_event: &KeyEvent, engine: &mut Engine) -> ProcessResult {
+        if (key_event.ctrl() && !self.use_control)
+            || (key_event.shift() && !self.use_shift)
+            || (key_event.alt() && !self.use_alt)
+            || (key_event.super_() && !self.use_super)
+        {
+            self.clear_chord(engine);
+            return ProcessResult::Noop;
+        }
+        let ch = key_event.keycode();
+        // non chording key
+        if !self.alphabet.chars().any(|key| key as u32 == ch) {
+            self.clear_chord(engine);
+            return ProcessResult::Noop;
+        }
+        // chording key
+        if key_event.release() {
+            if self.pressed.remove(&ch)
+                && (self.finish_chord_on_first_key_release || self.pressed.is_empty())
+            {
+                self.finish_chord(engine);
+            }
+        } else {
+            self.pressed.insert(ch);
+            if self.chord.insert(ch) {
+                self.update_chord(engine);
+            }
+        }
+        ProcessResult::Accepted
+    }
+
+    // Spells the chord with keys in the order of the alphabet.
+    fn serialize_chord(&self) -> String {
+        let mut code: String = self
+            .alphabet
+            .chars()
+            .filter(|&key| self.chord.contains(&(key as u32)))
+            .collect();
+        self.algebra.apply(Some(&mut code));
+        code
+    }
+
+    fn update_chord(&mut self, engine: &mut Engine) {
+        let mut code = self.serialize_chord();
+        self.prompt_format.apply(Some(&mut code));
+        if engine.context().composition().segments.is_empty() {
+            // add an invisible place holder segment
+            // 1. to cheat context.is_composing() == true
+            // 2. to attach chord prompt to while chording
+            engine.context_mut().push_input(ZERO_WIDTH_SPACE);
+            engine.flush_context_events();
+            let Some(segment) = engine.context_mut().composition_mut().segments.last_mut() else {
+                error!("failed to update chord.");
+                return;
+            };
+            segment.tags.insert("phony".to_string());
+        }
+        if let Some(segment) = engine.context_mut().composition_mut().segments.last_mut() {
+            segment.tags.insert("chord_prompt".to_string());
+            segment.prompt = code;
+        }
+    }
+
+    // The chord composer is busy while the code is being processed, so the
+    // keys go to the processors that follow it.
+    fn finish_chord(&mut self, engine: &mut Engine) {
+        let mut code = self.serialize_chord();
+        self.output_format.apply(Some(&mut code));
+        self.clear_chord(engine);
+        info!("chord: {}", code);
+        let mut sequence = KeySequence::default();
+        if !sequence.parse(&code) {
+            return;
+        }
+        for key in sequence.iter() {
+            if !engine.process_key(key) {
+                // direct commit
+                if let Some(ch) = char::from_u32(key.keycode()) {
+                    engine.commit_text(&ch.to_string());
+                }
+                // exclude the character (eg. space) from the raw sequence
+                self.raw_sequence.clear();
+            }
+        }
+    }
+
+    fn clear_chord(&mut self, engine: &mut Engine) {
+        self.pressed.clear();
+        self.chord.clear();
+        let context = engine.context_mut();
+        let segments = &mut context.composition_mut().segments;
+        let Some(last_segment) = segments.last_mut() else {
+            return;
+        };
+        if segments.len() == 1 && last_segment.has_tag("phony") {
+            context.clear();
+        } else if last_segment.has_tag("chord_prompt") {
+            last_segment.prompt.clear();
+            last_segment.tags.remove("chord_prompt");
+        }
+    }
+}
+
+impl Processor for ChordComposer {
+    fn process_key_event(&mut self, key_event: &KeyEvent, engine: &mut Engine) -> ProcessResult {
+        let ch = key_event.keycode();
+        if !key_event.release() && (0x20..=0x7e).contains(&ch) {
+            // a new composition starts afresh
+            if !engine.context().is_composing() && self.chord.is_empty() {
+                self.raw_sequence.clear();
+            }
+            // save raw input
+            if !engine.context().is_composing() || !self.raw_sequence.is_empty() {
+                self.raw_sequence.push(ch as u8 as char);
+            }
+        }
+        let result = self.process_chording_key(key_event, engine);
+        if result != ProcessResult::Noop {
+            return result;
+        }
+        self.process_function_key(key_event, engine)
+    }
+}
+
+#[test]
+fn type_code_of_chord_on_release() {
+    use crate::rime::engine::test_engine;
+
+    let (mut engine, committed) = test_engine(
+        "chord_composer:
+  alphabet: sdf
+  algebra:
+    - xform/^sf$/z/
+engine:
+  processors: [chord_composer]
+  segmentors: [fallback_segmentor]
+",
+    );
+    let key = |repr: &str| repr.parse::<KeyEvent>().unwrap();
+    assert!(engine.process_key(&key("s")));
+    let prompt = |engine: &Engine| {
+        let segments = &engine.context().composition().segments;
+        segments.last().map(|segment| segment.prompt.clone())
+    };
+    assert_eq!(Some("s".to_string()), prompt(&engine));
+    assert!(engine.process_key(&key("f")));
+    assert_eq!(Some("z".to_string()), prompt(&engine));
+    // the chord is finished when all of its keys are released
+    assert!(engine.process_key(&key("Release+s")));
+    assert!(committed.lock().unwrap().is_empty());
+    assert!(engine.process_key(&key("Release+f")));
+    assert_eq!(vec!["z"], *committed.lock().unwrap());
+    assert!(!engine.context().is_composing());
+
+    // keys out of the alphabet are left alone
+    assert!(!engine.process_key(&key("x")));
+}
@@ -0,0 +1,84 @@
+use std::sync::{Arc, RwLock};
+
+use crate::rime::candidate::{Candidate, SimpleCandidate};
+use crate::rime::component::Ticket;
+use crate::rime::context::Context;
+use crate::rime::segmentation::Segment;
+use crate::rime::translation::{Translation, UniqueTranslation};
+use crate::rime::translator::Translator;
+
+// Offers the input itself when nothing better translates the segment.
+pub(crate) struct EchoTranslator {
+    delimiters: String,
+}
+
+impl EchoTranslator {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        let mut delimiters = ticket.schema.config().get_string("speller/delimiter");
+        if delimiters.is_empty() {
+            delimiters = " ".to_string();
+        }
+        Self { delimiters }
+    }
+
+    // Shows every delimiter in the input as the primary one.
+    fn format_preedit(&self, input: &str) -> String {
+        let primary = self.delimiters.chars().next().unwrap_or(' ');
+        input
+            .chars()
+            .map(|ch| {
+                if self.delimiters.contains(ch) {
+                    primary
+                } else {
+                    ch
+                }
+            })
+            .collect()
+    }
+}
+
+impl Translator for EchoTranslator {
+    fn query(
+        &mut self,
+        input: &str,
+        segment: &Segment,
+        _context: &Context,
+    ) -> Option<Arc<RwLock<dyn Translation>>> {
+        if input.is_empty() {
+            return None;
+        }
+        let mut candidate = SimpleCandidate::new(
+            "raw".to_string(),
+            segment.start,
+            segment.end,
+            input.to_string(),
+            None,
+            Some(self.format_preedit(input)),
+        );
+        // below any real translation
+        candidate.set_quality(-100.0);
+        Some(Arc::new(RwLock::new(UniqueTranslation::new(Some(
+            Arc::new(candidate),
+        )))))
+    }
+}
+
+#[test]
+fn echo_input_with_primary_delimiter_in_preedit() {
+    use crate::rime::engine::test_engine;
+    use crate::rime::key_event::KeyEvent;
+
+    let (mut engine, _) = test_engine(
+        "speller:\n  alphabet: abcdefghijklmnopqrstuvwxyz\n  delimiter: \" '\"\n\
+         engine:\n  processors: [speller]\n  segmentors: [abc_segmentor]\n  \
+         translators: [echo_translator]\n",
+    );
+    for ch in "ni'hao".chars() {
+        assert!(engine.process_key(&KeyEvent::new(ch as u32, 0)));
+    }
+    let cand = engine.context().get_selected_candidate().unwrap();
+    assert_eq!("raw", cand.type_());
+    assert_eq!("ni'hao", cand.text());
+    assert_eq!("ni hao", cand.preedit());
+    assert_eq!((0, 6), (cand.start(), cand.end()));
+}
@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+
+use log::{error, info};
+
+use crate::rime::component::Ticket;
+use crate::rime::config::config_types::{ConfigMap, ConfigValue};
+use crate::rime::engine::Engine;
+use crate::rime::key_event::KeyEvent;
+use crate::rime::key_table::{Mask, XK_BACK_SPACE, XK_DELETE, XK_ESCAPE, XK_RETURN, XK_SPACE};
+use crate::rime::processor::{ProcessResult, Processor};
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Confirm,
+    ToggleSelection,
+    CommitComment,
+    CommitScriptText,
+    CommitRawInput,
+    CommitComposition,
+    Revert,
+    Back,
+    DeleteCandidate,
+    Delete,
+    Cancel,
+    Noop,
+}
+
+impl Action {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "confirm" => Some(Action::Confirm),
+            "toggle_selection" => Some(Action::ToggleSelection),
+            "commit_comment" => Some(Action::CommitComment),
+            "commit_script_text" => Some(Action::CommitScriptText),
+            "commit_raw_input" => Some(Action::CommitRawInput),
+            "commit_composition" => Some(Action::CommitComposition),
+            "revert" => Some(Action::Revert),
+            "back" => Some(Action::Back),
+            "delete_candidate" => Some(Action::DeleteCandidate),
+            "delete" => Some(Action::Delete),
+            "cancel" => Some(Action::Cancel),
+            "noop" => Some(Action::Noop),
+            _ => None,
+        }
+    }
+}
+
+// What to do with printable characters that no other processor has taken.
+#[derive(Debug, Clone, Copy)]
+enum CharHandler {
+    DirectCommit,
+    AddToInput,
+}
+
+// The express editor commits the composition as soon as it is fully
+// converted, while the fluid editor keeps composing until Return is hit.
+pub(crate) struct Editor {
+    auto_commit: bool,
+    bindings: BTreeMap<KeyEvent, Action>,
+    char_handler: Option<CharHandler>,
+}
+
+impl Editor {
+    pub(crate) fn express(ticket: &Ticket) -> Self {
+        let mut editor = Self {
+            auto_commit: true,
+            bindings: BTreeMap::new(),
+            char_handler: Some(CharHandler::DirectCommit),
+        };
+        editor.bind(XK_SPACE, 0, Action::Confirm);
+        editor.bind(XK_BACK_SPACE, 0, Action::Revert);
+        editor.bind(XK_RETURN, 0, Action::CommitRawInput);
+        editor.bind(XK_RETURN, Mask::Control as i32, Action::CommitScriptText);
+        editor.bind(XK_RETURN, Mask::Shift as i32, Action::CommitComposition);
+        editor.bind(
+            XK_RETURN,
+            Mask::Control as i32 | Mask::Shift as i32,
+            Action::CommitComment,
+        );
+        editor.bind(XK_DELETE, 0, Action::Delete);
+        editor.bind(XK_DELETE, Mask::Control as i32, Action::DeleteCandidate);
+        editor.bind(XK_ESCAPE, 0, Action::Cancel);
+        editor.load_config(ticket);
+        editor
+    }
+
+    pub(crate) fn fluid(ticket: &Ticket) -> Self {
+        let mut editor = Self {
+            auto_commit: false,
+            bindings: BTreeMap::new(),
+            char_handler: Some(CharHandler::AddToInput),
+        };
+        editor.bind(XK_SPACE, 0, Action::Confirm);
+        editor.bind(XK_BACK_SPACE, 0, Action::Back);
+        editor.bind(XK_RETURN, 0, Action::CommitComposition);
+        editor.bind(XK_RETURN, Mask::Control as i32, Action::CommitRawInput);
+        editor.bind(XK_RETURN, Mask::Shift as i32, Action::CommitScriptText);
+        editor.bind(
+            XK_RETURN,
+            Mask::Control as i32 | Mask::Shift as i32,
+            Action::CommitComment,
+        );
+        editor.bind(XK_DELETE, 0, Action::Delete);
+        editor.bind(XK_DELETE, Mask::Control as i32, Action::DeleteCandidate);
+        editor.bind(XK_ESCAPE, 0, Action::Cancel);
+        editor.load_config(ticket);
+        editor
+    }
+
+    fn bind(&mut self, keycode: u32, modifier: i32, action: Action) {
+        self.bindings
+            .insert(KeyEvent::new(keycode, modifier), action);
+    }
+
+    // Bindings under editor/bindings map key representations to action names,
+    // e.g. `Return: commit_script_text`.
+    fn load_config(&mut self, ticket: &Ticket) {
+        let config = ticket.schema.config();
+        if let Some(item) = config.get_item("editor/bindings") {
+            let Some(bindings) = item.as_any().downcast_ref::<ConfigMap>() else {
+                error!("editor/bindings should be a map");
+                return;
+            };
+            for (key, value) in &bindings.map {
+                let Some(value) = value.as_any().downcast_ref::<ConfigValue>() else {
+                    continue;
+                };
+                let mut key_event = KeyEvent::default();
+                if !key_event.parse(key) {
+                    error!("invalid edit key: {}", key);
+                    continue;
+                }
+                match Action::from_name(value.str()) {
+                    Some(action) => {
+                        self.bindings.insert(key_event, action);
+                    }
+                    None => error!("invalid action: {}", value.str()),
+                }
+            }
+        }
+        match config.get_string("editor/char_handler").as_str() {
+            "" => {}
+            "direct_commit" => self.char_handler = Some(CharHandler::DirectCommit),
+            "add_to_input" => self.char_handler = Some(CharHandler::AddToInput),
+            "noop" => self.char_handler = None,
+            name => error!("invalid char_handler: {}", name),
+        }
+    }
+
+    fn perform(&self, action: Action, engine: &mut Engine) -> bool {
+        let context = engine.context_mut();
+        match action {
+            Action::Confirm => {
+                let _ = context.confirm_current_selection() || context.commit();
+            }
+            Action::ToggleSelection => {
+                let _ = context.reopen_previous_segment() || context.confirm_current_selection();
+            }
+            Action::CommitComment => {
+                if let Some(cand) = context.get_selected_candidate() {
+                    if !cand.comment().is_empty() {
+                        engine.commit_text(cand.comment());
+                        engine.context_mut().clear();
+                    }
+                }
+            }
+            Action::CommitScriptText => {
+                let text = context.get_script_text();
+                engine.commit_text(&text);
+                engine.context_mut().clear();
+            }
+            Action::CommitRawInput => {
+                context.clear_non_confirmed_composition();
+                context.commit();
+            }
+            Action::CommitComposition => {
+                if !context.confirm_current_selection() {
+                    return context.commit();
+                }
+                // let the selection close the segment before looking for
+                // the rest of the composition
+                engine.flush_context_events();
+                let context = engine.context_mut();
+                if !context.has_menu() {
+                    return context.commit();
+                }
+            }
+            Action::Revert => {
+                // different behavior in regard to previous operation type
+                let _ = context.reopen_previous_selection()
+                    || (context.pop_input(1) && context.reopen_previous_segment());
+            }
+            Action::Back => {
+                let _ = context.reopen_previous_segment()
+                    || context.reopen_previous_selection()
+                    || context.pop_input(1);
+            }
+            Action::DeleteCandidate => {
+                context.delete_current_selection();
+            }
+            Action::Delete => {
+                context.delete_input(1);
+            }
+            Action::Cancel => {
+                if !context.clear_previous_segment() {
+                    context.clear();
+                }
+            }
+            Action::Noop => return false,
+        }
+        true
+    }
+
+    fn handle_char(&self, handler: CharHandler, ch: char, engine: &mut Engine) -> ProcessResult {
+        let context = engine.context_mut();
+        match handler {
+            CharHandler::DirectCommit => {
+                context.commit();
+                ProcessResult::Rejected
+            }
+            CharHandler::AddToInput => {
+                context.push_input(ch);
+                engine.flush_context_events();
+                engine.context_mut().confirm_previous_selection();
+                ProcessResult::Accepted
+            }
+        }
+    }
+}
+
+impl Processor for Editor {
+    fn process_key_event(&mut self, key_event: &KeyEvent, engine: &mut Engine) -> ProcessResult {
+        if key_event.release() {
+            return ProcessResult::Rejected;
+        }
+        // Transient options are reset along with the schema, so keep the
+        // commit strategy of this editor in effect.
+        if engine.context().get_option("_auto_commit") != self.auto_commit {
+            engine
+                .context_mut()
+                .set_option("_auto_commit", self.auto_commit);
+        }
+        if engine.context().is_composing() {
+            let key = KeyEvent::new(
+                key_event.keycode(),
+                key_event.modifier() & !(Mask::Lock as i32),
+            );
+            if let Some(&action) = self.bindings.get(&key) {
+                info!("editor action: {:?}", action);
+                if self.perform(action, engine) {
+                    return ProcessResult::Accepted;
+                }
+            }
+        }
+        let ch = key_event.keycode();
+        if let Some(handler) = self.char_handler {
+            if !key_event.ctrl()
+                && !key_event.alt()
+                && !key_event.super_()
+                && ch > 0x20
+                && ch < 0x7f
+            {
+                info!("input char: '{}', {}, '{}'", ch as u8 as char, ch, key_event);
+                return self.handle_char(handler, ch as u8 as char, engine);
+            }
+        }
+        // not handled
+        ProcessResult::Noop
+    }
+}
+
+#[test]
+fn commit_composition_on_first_return() {
+    use crate::rime::engine::test_engine;
+
+    let (mut engine, committed) = test_engine(
+        "engine:
+  processors: [speller, fluid_editor]
+  segmentors: [abc_segmentor]
+  translators: [echo_translator]
+",
+    );
+    for ch in "ab".chars() {
+        assert!(engine.process_key(&KeyEvent::new(ch as u32, 0)));
+    }
+    assert!(engine.process_key(&KeyEvent::new(XK_RETURN, 0)));
+    assert_eq!(vec!["ab"], *committed.lock().unwrap());
+    assert!(!engine.context().is_composing());
+}
@@ -0,0 +1,48 @@
+use log::info;
+
+use crate::rime::component::Ticket;
+use crate::rime::segmentation::{Segment, SegmentStatus, Segmentation};
+use crate::rime::segmentor::Segmentor;
+
+// Covers input that no other segmentor has claimed with `raw` segments.
+pub(crate) struct FallbackSegmentor;
+
+impl FallbackSegmentor {
+    pub(crate) fn new(_ticket: &Ticket) -> Self {
+        Self
+    }
+}
+
+impl Segmentor for FallbackSegmentor {
+    fn proceed(&mut self, segmentation: &mut Segmentation) -> bool {
+        let len = segmentation.get_current_segment_length();
+        info!("current segment length: {}", len);
+        if len > 0 {
+            return false;
+        }
+        let k = segmentation.get_current_start_position();
+        info!("start pos: {}", k);
+        let Some(ch) = segmentation.input()[k..].chars().next() else {
+            return false;
+        };
+        let end = k + ch.len_utf8();
+        let count = segmentation.segments.len();
+        if count >= 2 {
+            let previous = &mut segmentation.segments[count - 2];
+            if previous.end == k && previous.status == SegmentStatus::Void && previous.has_tag("raw")
+            {
+                // extend the previous raw segment by one character
+                let mut segment = Segment::new(previous.start, end);
+                segment.tags = std::mem::take(&mut previous.tags);
+                *previous = segment;
+                segmentation.segments.pop();
+                return false;
+            }
+        }
+        let mut segment = Segment::new(k, end);
+        segment.tags.insert("raw".to_string());
+        segmentation.add_segment(segment);
+        // exclusive
+        false
+    }
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use log::{error, info};
+
+use crate::rime::dict::gram_db::GramDb;
+use crate::rime::schema::Schema;
+
+// A language model scoring a word by the text before it, e.g. n-grams.
+// Scores are natural log probabilities.
+pub(crate) trait Grammar: Send + Sync {
+    // is_rear tells that the word ends the sentence.
+    fn query(&self, context: &str, word: &str, is_rear: bool) -> f64;
+}
+
+// log(1e-8), charged for each word of the sentence in want of a grammar.
+const PENALTY: f64 = -18.420680743952367;
+
+// Weight of a word appended to a sentence, of which the context is the end.
+pub(crate) fn evaluate(
+    context: &str,
+    word: &str,
+    weight: f64,
+    is_rear: bool,
+    grammar: Option<&dyn Grammar>,
+) -> f64 {
+    weight + grammar.map_or(PENALTY, |grammar| grammar.query(context, word, is_rear))
+}
+
+impl Grammar for GramDb {
+    fn query(&self, context: &str, word: &str, is_rear: bool) -> f64 {
+        GramDb::query(self, context, word, is_rear)
+    }
+}
+
+// Loads the n-gram model "<data_dir>/<grammar/language>.gram" if the schema
+// names one.
+pub(crate) fn load_grammar(schema: &Schema) -> Option<Arc<dyn Grammar>> {
+    let language = schema.config().get_string("grammar/language");
+    if language.is_empty() {
+        return None;
+    }
+    let file_path = schema.data_dir()?.join(format!("{}.gram", language));
+    let Some(gram_db) = GramDb::load(file_path) else {
+        error!("failed to load grammar '{}'.", language);
+        return None;
+    };
+    info!(
+        "grammar '{}' of order {} loaded.",
+        language,
+        gram_db.max_order()
+    );
+    Some(Arc::new(gram_db))
+}
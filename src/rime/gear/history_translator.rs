@@ -0,0 +1,134 @@
+use std::sync::{Arc, RwLock};
+
+use crate::rime::candidate::{Candidate, SimpleCandidate};
+use crate::rime::component::Ticket;
+use crate::rime::context::Context;
+use crate::rime::segmentation::Segment;
+use crate::rime::translation::{FifoTranslation, Translation};
+use crate::rime::translator::Translator;
+
+const DEFAULT_INITIAL_QUALITY: f64 = 1000.0;
+
+// Offers the text committed lately on typing a trigger input, e.g. "z".
+pub(crate) struct HistoryTranslator {
+    tag: String,
+    input: String,
+    size: usize,
+    initial_quality: f64,
+}
+
+impl HistoryTranslator {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        if ticket.name_space == "translator" {
+            let ticket = Ticket::new(ticket.schema, ticket.context, "history", &ticket.klass);
+            return Self::new(&ticket);
+        }
+        let config = ticket.schema.config();
+        let name_space = &ticket.name_space;
+        let mut tag = config.get_string(&format!("{}/tag", name_space));
+        if tag.is_empty() {
+            tag = "abc".to_string();
+        }
+        let size_key = format!("{}/size", name_space);
+        let size = if config.contains(&size_key) {
+            config.get_int(&size_key).max(0) as usize
+        } else {
+            1
+        };
+        let initial_quality_key = format!("{}/initial_quality", name_space);
+        let initial_quality = if config.contains(&initial_quality_key) {
+            config.get_double(&initial_quality_key)
+        } else {
+            DEFAULT_INITIAL_QUALITY
+        };
+        Self {
+            tag,
+            input: config.get_string(&format!("{}/input", name_space)),
+            size,
+            initial_quality,
+        }
+    }
+}
+
+impl Translator for HistoryTranslator {
+    fn query(
+        &mut self,
+        input: &str,
+        segment: &Segment,
+        context: &Context,
+    ) -> Option<Arc<RwLock<dyn Translation>>> {
+        if !segment.has_tag(&self.tag) || self.input.is_empty() || input != self.input {
+            return None;
+        }
+        let mut translation = FifoTranslation::new();
+        // the latest first, leaving out keys passed through
+        for record in context
+            .commit_history()
+            .iter()
+            .rev()
+            .filter(|record| record.type_ != "thru")
+            .take(self.size)
+        {
+            let mut candidate = SimpleCandidate::new(
+                "history".to_string(),
+                segment.start,
+                segment.end,
+                record.text.clone(),
+                None,
+                None,
+            );
+            candidate.set_quality(self.initial_quality);
+            translation.append(Some(Arc::new(candidate)));
+        }
+        if translation.size() == 0 {
+            return None;
+        }
+        Some(Arc::new(RwLock::new(translation)))
+    }
+}
+
+#[test]
+fn recent_commits_on_trigger_input() {
+    use crate::rime::commit_history::CommitRecord;
+    use crate::rime::engine::test_engine;
+
+    let (mut engine, _) = test_engine(
+        "history:\n  input: z\n  size: 2\n  initial_quality: 5\n\
+         engine:\n  segmentors: [abc_segmentor]\n  translators: [history_translator]\n",
+    );
+    for (type_, text) in [
+        ("table", "你"),
+        ("table", "好"),
+        ("thru", ","),
+        ("raw", "嗎"),
+    ] {
+        engine
+            .context_mut()
+            .commit_history_mut()
+            .push(CommitRecord::new(type_, text));
+    }
+    let mut candidates = |input: &str| {
+        engine.context_mut().set_input(input.to_string());
+        engine.flush_context_events();
+        let Some(menu) = engine
+            .context()
+            .composition()
+            .segments
+            .last()
+            .and_then(|segment| segment.menu.clone())
+        else {
+            return vec![];
+        };
+        let mut menu = menu.write().unwrap();
+        menu.prepare(3);
+        (0..3)
+            .filter_map(|i| menu.get_candidate_at(i))
+            .map(|candidate| (candidate.text().to_string(), candidate.quality()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        vec![("嗎".to_string(), 5.0), ("好".to_string(), 5.0)],
+        candidates("z")
+    );
+    assert!(candidates("zz").is_empty());
+}
@@ -0,0 +1,227 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use log::{info, warn};
+
+use crate::rime::component::Ticket;
+use crate::rime::config::config_types::{ConfigList, ConfigMap};
+use crate::rime::context::Context;
+use crate::rime::engine::Engine;
+use crate::rime::key_event::{KeyEvent, KeySequence};
+use crate::rime::processor::{ProcessResult, Processor};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Condition {
+    // user has changed page
+    Paging,
+    // at least one candidate
+    HasMenu,
+    // input string is not empty
+    Composing,
+    Always,
+}
+
+impl Condition {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "paging" => Some(Condition::Paging),
+            "has_menu" => Some(Condition::HasMenu),
+            "composing" => Some(Condition::Composing),
+            "always" => Some(Condition::Always),
+            _ => None,
+        }
+    }
+
+    fn current(context: &Context) -> BTreeSet<Condition> {
+        let mut conditions = BTreeSet::new();
+        if context.is_composing() {
+            conditions.insert(Condition::Composing);
+        }
+        if context.has_menu() && !context.get_option("ascii_mode") {
+            conditions.insert(Condition::HasMenu);
+        }
+        if context
+            .composition()
+            .segments
+            .last()
+            .is_some_and(|segment| segment.has_tag("paging"))
+        {
+            conditions.insert(Condition::Paging);
+        }
+        conditions.insert(Condition::Always);
+        conditions
+    }
+}
+
+enum Action {
+    Send(Vec<KeyEvent>),
+    Toggle(String),
+    SetOption(String, bool),
+    Select(String),
+}
+
+struct KeyBinding {
+    whence: Condition,
+    action: Action,
+}
+
+pub(crate) struct KeyBinder {
+    bindings: BTreeMap<KeyEvent, Vec<KeyBinding>>,
+}
+
+impl KeyBinder {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        let mut key_binder = Self {
+            bindings: BTreeMap::new(),
+        };
+        key_binder.load_config(ticket);
+        key_binder
+    }
+
+    fn load_config(&mut self, ticket: &Ticket) {
+        let config = ticket.schema.config();
+        let Some(item) = config.get_item("key_binder/bindings") else {
+            return;
+        };
+        let Some(bindings) = item.as_any().downcast_ref::<ConfigList>() else {
+            warn!("key_binder/bindings should be a list.");
+            return;
+        };
+        for i in 0..bindings.size() {
+            let Some(item) = bindings.get_at(i) else {
+                continue;
+            };
+            let Some(map) = item.as_any().downcast_ref::<ConfigMap>() else {
+                continue;
+            };
+            let Some(whence) = map
+                .get_str("when")
+                .and_then(Condition::from_name)
+            else {
+                continue;
+            };
+            let Some(accept) = map.get_str("accept") else {
+                continue;
+            };
+            let mut target = KeyEvent::default();
+            if !target.parse(accept) {
+                warn!("invalid key: {}", accept);
+                continue;
+            }
+            let action = if let Some(send) = map.get_str("send") {
+                let mut key_event = KeyEvent::default();
+                if !key_event.parse(send) {
+                    warn!("invalid key: {}", send);
+                    continue;
+                }
+                Action::Send(vec![key_event])
+            } else if let Some(send_sequence) = map.get_str("send_sequence") {
+                let mut sequence = KeySequence::default();
+                if !sequence.parse(send_sequence) {
+                    warn!("invalid key sequence: {}", send_sequence);
+                    continue;
+                }
+                Action::Send(sequence.to_vec())
+            } else if let Some(option) = map.get_str("toggle") {
+                Action::Toggle(option.to_string())
+            } else if let Some(option) = map.get_str("set_option") {
+                // "!option" turns the option off
+                match option.strip_prefix('!') {
+                    Some(option) => Action::SetOption(option.to_string(), false),
+                    None => Action::SetOption(option.to_string(), true),
+                }
+            } else if let Some(schema_id) = map.get_str("select") {
+                Action::Select(schema_id.to_string())
+            } else {
+                warn!("invalid key binding #{}.", i);
+                continue;
+            };
+            self.bindings
+                .entry(target)
+                .or_default()
+                .push(KeyBinding { whence, action });
+        }
+    }
+
+    // Bound keys are fed back into the pipeline, where the key binder itself
+    // is skipped while it is still busy with the key that triggered them.
+    fn perform_key_binding(binding: &KeyBinding, engine: &mut Engine) {
+        match &binding.action {
+            Action::Send(keys) => {
+                for key in keys {
+                    engine.process_key(key);
+                }
+            }
+            Action::Toggle(option) => {
+                let context = engine.context_mut();
+                let value = !context.get_option(option);
+                context.set_option(option, value);
+            }
+            Action::SetOption(option, value) => {
+                engine.context_mut().set_option(option, *value);
+            }
+            Action::Select(schema_id) => {
+                engine.select_schema(schema_id);
+            }
+        }
+    }
+}
+
+impl Processor for KeyBinder {
+    fn process_key_event(&mut self, key_event: &KeyEvent, engine: &mut Engine) -> ProcessResult {
+        let Some(bindings) = self.bindings.get(key_event) else {
+            return ProcessResult::Noop;
+        };
+        // exec the first binding that matches conditions
+        let conditions = Condition::current(engine.context());
+        for binding in bindings {
+            if !conditions.contains(&binding.whence) {
+                continue;
+            }
+            info!("key binding: {}", key_event);
+            Self::perform_key_binding(binding, engine);
+            return ProcessResult::Accepted;
+        }
+        ProcessResult::Noop
+    }
+}
+
+#[test]
+fn bindings_apply_under_their_conditions() {
+    use crate::rime::engine::test_engine;
+
+    let (mut engine, _) = test_engine(
+        "key_binder:
+  bindings:
+    - when: composing
+      accept: minus
+      send: b
+    - when: always
+      accept: Control+t
+      toggle: full_shape
+    - when: always
+      accept: Control+s
+      set_option: \"!simplification\"
+engine:
+  processors: [key_binder, speller]
+",
+    );
+    let key = |repr: &str| {
+        let mut key_event = KeyEvent::default();
+        assert!(key_event.parse(repr));
+        key_event
+    };
+    assert!(engine.process_key(&key("Control+t")));
+    assert!(engine.context().get_option("full_shape"));
+    assert!(engine.process_key(&key("Control+t")));
+    assert!(!engine.context().get_option("full_shape"));
+    engine.context_mut().set_option("simplification", true);
+    assert!(engine.process_key(&key("Control+s")));
+    assert!(!engine.context().get_option("simplification"));
+
+    // bound only while composing
+    assert!(!engine.process_key(&key("minus")));
+    assert!(engine.process_key(&key("a")));
+    // the key sent is handled by the processors after the key binder
+    assert!(engine.process_key(&key("minus")));
+    assert_eq!("ab", engine.context().input());
+}
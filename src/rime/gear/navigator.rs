@@ -0,0 +1,224 @@
+use log::info;
+
+use crate::rime::candidate::BaseCandidate;
+use crate::rime::component::Ticket;
+use crate::rime::context::Context;
+use crate::rime::engine::Engine;
+use crate::rime::key_event::KeyEvent;
+use crate::rime::key_table::{
+    XK_END, XK_HOME, XK_KP_END, XK_KP_HOME, XK_KP_LEFT, XK_KP_RIGHT, XK_LEFT, XK_RIGHT,
+};
+use crate::rime::processor::{ProcessResult, Processor};
+use crate::rime::segmentation::SegmentStatus;
+use crate::rime::spans::Spans;
+
+pub(crate) struct Navigator {
+    input: String,
+    spans: Spans,
+}
+
+impl Navigator {
+    pub(crate) fn new(_ticket: &Ticket) -> Self {
+        Self {
+            input: String::new(),
+            spans: Spans::default(),
+        }
+    }
+
+    fn begin_move(&mut self, context: &mut Context) {
+        context.confirm_previous_selection();
+        // update spans
+        if self.input != context.input() || context.caret_pos() > self.spans.end() {
+            self.input = context.input().to_string();
+            self.spans.clear();
+            for segment in &context.composition().segments {
+                if let Some(cand) = segment.get_selected_candidate() {
+                    self.spans
+                        .add_spans(&BaseCandidate::get_genuine_candidate(&cand).spans());
+                }
+                self.spans.add_span(segment.start, segment.end);
+            }
+        }
+    }
+
+    fn left_by_char(&mut self, context: &mut Context) {
+        self.begin_move(context);
+        let _ = Self::move_left(context) || Self::go_to_end(context);
+    }
+
+    fn right_by_char(&mut self, context: &mut Context) {
+        self.begin_move(context);
+        let _ = Self::move_right(context) || Self::go_home(context);
+    }
+
+    fn left_by_syllable(&mut self, context: &mut Context) {
+        self.begin_move(context);
+        let confirmed_pos = context.composition().get_confirmed_position();
+        let _ = self.jump_left(context, confirmed_pos) || Self::go_to_end(context);
+    }
+
+    fn right_by_syllable(&mut self, context: &mut Context) {
+        self.begin_move(context);
+        let confirmed_pos = context.composition().get_confirmed_position();
+        let _ = self.jump_right(context, confirmed_pos) || Self::go_home(context);
+    }
+
+    fn home(&mut self, context: &mut Context) {
+        self.begin_move(context);
+        Self::go_home(context);
+    }
+
+    fn end(&mut self, context: &mut Context) {
+        self.begin_move(context);
+        Self::go_to_end(context);
+    }
+
+    fn jump_left(&self, context: &mut Context, start_pos: usize) -> bool {
+        info!("jump left.");
+        let caret_pos = context.caret_pos();
+        let mut stop = self.spans.previous_stop(caret_pos);
+        if stop < start_pos {
+            // jump to the end
+            stop = context.input().len();
+        }
+        if stop != caret_pos {
+            context.set_caret_pos(stop);
+            return true;
+        }
+        false
+    }
+
+    fn jump_right(&self, context: &mut Context, start_pos: usize) -> bool {
+        info!("jump right.");
+        let mut caret_pos = context.caret_pos();
+        if caret_pos == context.input().len() {
+            // jump from the start
+            caret_pos = start_pos;
+        }
+        let stop = self.spans.next_stop(caret_pos);
+        if stop != caret_pos {
+            context.set_caret_pos(stop);
+            return true;
+        }
+        false
+    }
+
+    fn move_left(context: &mut Context) -> bool {
+        info!("navigate left.");
+        let caret_pos = context.caret_pos();
+        if caret_pos == 0 {
+            return false;
+        }
+        context.set_caret_pos(caret_pos - 1);
+        true
+    }
+
+    fn move_right(context: &mut Context) -> bool {
+        info!("navigate right.");
+        let caret_pos = context.caret_pos();
+        if caret_pos >= context.input().len() {
+            return false;
+        }
+        context.set_caret_pos(caret_pos + 1);
+        true
+    }
+
+    fn go_home(context: &mut Context) -> bool {
+        info!("navigate home.");
+        let caret_pos = context.caret_pos();
+        // stop at the start of the unconfirmed part of the composition first
+        let mut confirmed_pos = caret_pos;
+        for segment in context.composition().segments.iter().rev() {
+            if segment.status >= SegmentStatus::Selected {
+                break;
+            }
+            confirmed_pos = segment.start;
+        }
+        if confirmed_pos < caret_pos {
+            context.set_caret_pos(confirmed_pos);
+            return true;
+        }
+        if caret_pos != 0 {
+            context.set_caret_pos(0);
+            return true;
+        }
+        false
+    }
+
+    fn go_to_end(context: &mut Context) -> bool {
+        info!("navigate end.");
+        let end_pos = context.input().len();
+        if context.caret_pos() != end_pos {
+            context.set_caret_pos(end_pos);
+            return true;
+        }
+        false
+    }
+}
+
+impl Processor for Navigator {
+    fn process_key_event(&mut self, key_event: &KeyEvent, engine: &mut Engine) -> ProcessResult {
+        if key_event.release() || key_event.alt() || key_event.super_() {
+            return ProcessResult::Noop;
+        }
+        let context = engine.context_mut();
+        if !context.is_composing() {
+            return ProcessResult::Noop;
+        }
+        let by_syllable = key_event.ctrl() || key_event.shift();
+        match key_event.keycode() {
+            XK_LEFT | XK_KP_LEFT if by_syllable => self.left_by_syllable(context),
+            XK_LEFT | XK_KP_LEFT => self.left_by_char(context),
+            XK_RIGHT | XK_KP_RIGHT if by_syllable => self.right_by_syllable(context),
+            XK_RIGHT | XK_KP_RIGHT => self.right_by_char(context),
+            XK_HOME | XK_KP_HOME => self.home(context),
+            XK_END | XK_KP_END => self.end(context),
+            _ => return ProcessResult::Noop,
+        }
+        ProcessResult::Accepted
+    }
+}
+
+#[cfg(test)]
+fn navigator_on(context: &mut Context, input: &str, vertices: &[usize]) -> Navigator {
+    context.set_input(input.to_string());
+    let mut spans = Spans::default();
+    for &vertex in vertices {
+        spans.add_vertex(vertex);
+    }
+    Navigator {
+        input: input.to_string(),
+        spans,
+    }
+}
+
+#[test]
+fn move_caret_by_char_wraps_around() {
+    let mut context = Context::new();
+    let mut navigator = navigator_on(&mut context, "nihao", &[0, 2, 5]);
+    navigator.right_by_char(&mut context);
+    assert_eq!(0, context.caret_pos());
+    navigator.right_by_char(&mut context);
+    assert_eq!(1, context.caret_pos());
+    navigator.left_by_char(&mut context);
+    navigator.left_by_char(&mut context);
+    assert_eq!(5, context.caret_pos());
+}
+
+#[test]
+fn move_caret_by_syllable() {
+    let mut context = Context::new();
+    let mut navigator = navigator_on(&mut context, "nihao", &[0, 2, 5]);
+    navigator.left_by_syllable(&mut context);
+    assert_eq!(2, context.caret_pos());
+    navigator.left_by_syllable(&mut context);
+    assert_eq!(0, context.caret_pos());
+    // past the first syllable to the end
+    navigator.left_by_syllable(&mut context);
+    assert_eq!(5, context.caret_pos());
+    // from the end to the first stop
+    navigator.right_by_syllable(&mut context);
+    assert_eq!(2, context.caret_pos());
+    navigator.right_by_syllable(&mut context);
+    assert_eq!(5, context.caret_pos());
+}
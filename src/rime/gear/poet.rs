@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::rime::candidate::Candidate;
+use crate::rime::dict::dictionary::DictEntryIterator;
+use crate::rime::dict::vocabulary::DictEntry;
+use crate::rime::gear::grammar::{evaluate, Grammar};
+use crate::rime::gear::translator_commons::Sentence;
+use crate::rime::language::Language;
+
+// Lines kept at each position of the input.
+const BEAM_WIDTH: usize = 7;
+// The best sentence and its alternatives.
+const MAX_SENTENCES: usize = 3;
+// Entries taken of each group of words spelled alike, for the grammar to
+// choose from.
+const MAX_HOMOPHONES: usize = 3;
+
+// Words found in the input, by start position and then end position, each
+// group in order of weight.
+pub(crate) type WordGraph = BTreeMap<usize, BTreeMap<usize, Vec<Arc<DictEntry>>>>;
+
+// The leading entries of a group of words spelled alike.
+pub(crate) fn homophones(mut iter: DictEntryIterator) -> Vec<Arc<DictEntry>> {
+    let mut entries = Vec::new();
+    while entries.len() < MAX_HOMOPHONES {
+        let Some(entry) = iter.peek() else {
+            break;
+        };
+        entries.push(entry);
+        iter.next();
+    }
+    entries
+}
+
+// Composes sentences of the words in the graph that make up the whole input,
+// scored by word weights plus the grammar if any.
+pub(crate) struct Poet {
+    language: Option<Arc<Language>>,
+    grammar: Option<Arc<dyn Grammar>>,
+}
+
+impl Poet {
+    pub(crate) fn new(language: Option<Arc<Language>>, grammar: Option<Arc<dyn Grammar>>) -> Self {
+        Self { language, grammar }
+    }
+
+    // A beam search, from left to right, where the best lines ending at each
+    // position are extended by the words starting there. Returns sentences of
+    // more than one word, best first, as a single word is found by lookup.
+    pub(crate) fn make_sentences(
+        &self,
+        graph: &WordGraph,
+        total_length: usize,
+        preceding_text: &str,
+    ) -> Vec<Sentence> {
+        let mut lines = BTreeMap::new();
+        lines.insert(0, vec![Sentence::new(self.language.clone())]);
+        for (&start_pos, words) in graph {
+            let Some(previous_lines) = lines.get(&start_pos).cloned() else {
+                continue;
+            };
+            for line in &previous_lines {
+                let context = if line.empty() {
+                    preceding_text
+                } else {
+                    line.text()
+                };
+                for (&end_pos, entries) in words {
+                    if end_pos > total_length {
+                        continue;
+                    }
+                    let is_rear = end_pos == total_length;
+                    for entry in entries {
+                        let weight = evaluate(
+                            context,
+                            &entry.text,
+                            entry.weight,
+                            is_rear,
+                            self.grammar.as_deref(),
+                        );
+                        let mut new_line = line.clone();
+                        new_line.extend(entry.clone(), end_pos, weight);
+                        keep_line(lines.entry(end_pos).or_default(), new_line);
+                    }
+                }
+            }
+        }
+        let mut sentences = lines.remove(&total_length).unwrap_or_default();
+        sentences.retain(|sentence| sentence.size() > 1);
+        sentences.truncate(MAX_SENTENCES);
+        sentences
+    }
+}
+
+// Inserts the line in order of weight, unless a better one of the same text
+// is there or it falls out of the beam.
+fn keep_line(lines: &mut Vec<Sentence>, line: Sentence) {
+    if let Some(index) = lines.iter().position(|kept| kept.text() == line.text()) {
+        if lines[index].weight() >= line.weight() {
+            return;
+        }
+        lines.remove(index);
+    }
+    let index = lines.partition_point(|kept| kept.weight() >= line.weight());
+    if index < BEAM_WIDTH {
+        lines.insert(index, line);
+        lines.truncate(BEAM_WIDTH);
+    }
+}
+
+#[cfg(test)]
+fn sample_graph() -> WordGraph {
+    let entry = |text: &str, probability: f64| {
+        Arc::new(DictEntry {
+            text: text.to_string(),
+            weight: probability.ln(),
+            ..Default::default()
+        })
+    };
+    let mut graph = WordGraph::new();
+    let ni = graph.entry(0).or_default();
+    ni.insert(2, vec![entry("你", 0.5), entry("泥", 0.3)]);
+    ni.insert(5, vec![entry("拟好", 0.1)]);
+    graph
+        .entry(2)
+        .or_default()
+        .insert(5, vec![entry("好", 0.4)]);
+    graph
+}
+
+#[test]
+fn make_sentences_by_word_weights() {
+    let poet = Poet::new(None, None);
+    let sentences = poet.make_sentences(&sample_graph(), 5, "");
+    // the single word is left to lookup
+    assert_eq!(
+        vec!["你好", "泥好"],
+        sentences.iter().map(|s| s.text()).collect::<Vec<_>>()
+    );
+    assert!(sentences[0].weight() > sentences[1].weight());
+}
+
+#[test]
+fn make_sentences_with_grammar() {
+    struct Bigram;
+    impl Grammar for Bigram {
+        fn query(&self, context: &str, word: &str, _is_rear: bool) -> f64 {
+            if context.ends_with('泥') && word == "好" {
+                0.0
+            } else {
+                -20.0
+            }
+        }
+    }
+    let poet = Poet::new(None, Some(Arc::new(Bigram)));
+    let sentences = poet.make_sentences(&sample_graph(), 5, "");
+    assert_eq!(
+        vec!["泥好", "你好"],
+        sentences.iter().map(|s| s.text()).collect::<Vec<_>>()
+    );
+}
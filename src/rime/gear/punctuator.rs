@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use log::{error, info, warn};
+
+use crate::rime::candidate::{Candidate, SimpleCandidate};
+use crate::rime::component::Ticket;
+use crate::rime::config::config_types::{ConfigItem, ConfigList, ConfigMap, ConfigValue};
+use crate::rime::context::Context;
+use crate::rime::engine::Engine;
+use crate::rime::key_event::KeyEvent;
+use crate::rime::key_table::XK_SPACE;
+use crate::rime::processor::{ProcessResult, Processor};
+use crate::rime::segmentation::{Segment, SegmentStatus, Segmentation};
+use crate::rime::segmentor::Segmentor;
+use crate::rime::translation::{FifoTranslation, Translation, UniqueTranslation};
+use crate::rime::translator::Translator;
+
+// Punctuation mappings of the schema, one for each shape.
+// A definition is either a single symbol, a list of alternatives, a map with
+// a `commit` symbol to commit right away, or a map with a `pair` of symbols.
+pub(crate) struct PunctConfig {
+    half_shape: Option<Arc<dyn ConfigItem>>,
+    full_shape: Option<Arc<dyn ConfigItem>>,
+}
+
+impl PunctConfig {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        let config = ticket.schema.config();
+        Self {
+            half_shape: config.get_item("punctuator/half_shape"),
+            full_shape: config.get_item("punctuator/full_shape"),
+        }
+    }
+
+    pub(crate) fn get_punct_definition(
+        &self,
+        key: &str,
+        full_shape: bool,
+    ) -> Option<Arc<dyn ConfigItem>> {
+        let mapping = if full_shape {
+            &self.full_shape
+        } else {
+            &self.half_shape
+        };
+        mapping
+            .as_ref()?
+            .as_any()
+            .downcast_ref::<ConfigMap>()?
+            .get(key)
+    }
+
+    fn is_punct_key(&self, key: &str) -> bool {
+        self.get_punct_definition(key, false).is_some()
+            || self.get_punct_definition(key, true).is_some()
+    }
+}
+
+pub(crate) struct Punctuator {
+    config: PunctConfig,
+    use_space: bool,
+    // which one of a pair of punctuation marks comes next, by key
+    oddness: HashMap<String, usize>,
+}
+
+impl Punctuator {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        Self {
+            config: PunctConfig::new(ticket),
+            use_space: ticket.schema.config().get_bool("punctuator/use_space"),
+            oddness: HashMap::new(),
+        }
+    }
+
+    fn alternate_punct(
+        &self,
+        context: &mut Context,
+        key: &str,
+        definition: &Arc<dyn ConfigItem>,
+    ) -> bool {
+        if definition.as_any().downcast_ref::<ConfigList>().is_none() {
+            return false;
+        }
+        let input = context.input().to_string();
+        let Some(segment) = context.composition_mut().segments.last_mut() else {
+            return false;
+        };
+        if segment.status == SegmentStatus::Void
+            || !segment.has_tag("punct")
+            || key != &input[segment.start..segment.end]
+        {
+            return false;
+        }
+        let Some(menu) = segment.menu.clone() else {
+            error!("missing candidate for punctuation '{}'.", key);
+            return false;
+        };
+        let mut menu = menu.write().unwrap();
+        if menu.prepare(segment.selected_index + 2) == 0 {
+            error!("missing candidate for punctuation '{}'.", key);
+            return false;
+        }
+        info!("alternating punctuation '{}'.", key);
+        segment.selected_index = (segment.selected_index + 1) % menu.candidate_count();
+        segment.tags.insert("paging".to_string());
+        true
+    }
+
+    fn confirm_unique_punct(context: &mut Context, definition: &Arc<dyn ConfigItem>) -> bool {
+        if definition.as_any().downcast_ref::<ConfigValue>().is_none() {
+            return false;
+        }
+        context.confirm_current_selection();
+        true
+    }
+
+    fn auto_commit_punct(context: &mut Context, definition: &Arc<dyn ConfigItem>) -> bool {
+        match definition.as_any().downcast_ref::<ConfigMap>() {
+            Some(map) if map.has_key("commit") => {
+                context.commit();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn pair_punct(&mut self, context: &mut Context, definition: &Arc<dyn ConfigItem>) -> bool {
+        match definition.as_any().downcast_ref::<ConfigMap>() {
+            Some(map) if map.has_key("pair") => {}
+            _ => return false,
+        }
+        let input = context.input().to_string();
+        let Some(segment) = context.composition_mut().segments.last_mut() else {
+            return false;
+        };
+        if segment.status == SegmentStatus::Void || !segment.has_tag("punct") {
+            return false;
+        }
+        let key = &input[segment.start..segment.end];
+        if segment
+            .menu
+            .as_ref()
+            .map_or(true, |menu| menu.write().unwrap().prepare(2) < 2)
+        {
+            error!("missing candidate for paired punctuation '{}'.", key);
+            return false;
+        }
+        info!("paired punctuation '{}'.", key);
+        let oddness = self.oddness.entry(key.to_string()).or_default();
+        segment.selected_index = *oddness;
+        *oddness = 1 - *oddness;
+        context.confirm_current_selection();
+        true
+    }
+}
+
+impl Processor for Punctuator {
+    fn process_key_event(&mut self, key_event: &KeyEvent, engine: &mut Engine) -> ProcessResult {
+        if key_event.release() || key_event.ctrl() || key_event.alt() || key_event.super_() {
+            return ProcessResult::Noop;
+        }
+        let ch = key_event.keycode();
+        if !(0x20..0x7f).contains(&ch) {
+            return ProcessResult::Noop;
+        }
+        let context = engine.context_mut();
+        if context.get_option("ascii_punct") {
+            return ProcessResult::Noop;
+        }
+        if !self.use_space && ch == XK_SPACE && context.is_composing() {
+            return ProcessResult::Noop;
+        }
+        let ch = ch as u8 as char;
+        if ch == '.' || ch == ':' {
+            // 3.14, 12:30
+            if let Some(record) = context.commit_history().back() {
+                if record.type_ == "thru"
+                    && record.text.len() == 1
+                    && record.text.as_bytes()[0].is_ascii_digit()
+                {
+                    return ProcessResult::Rejected;
+                }
+            }
+        }
+        let punct_key = ch.to_string();
+        let full_shape = context.get_option("full_shape");
+        let Some(definition) = self.config.get_punct_definition(&punct_key, full_shape) else {
+            return ProcessResult::Noop;
+        };
+        info!("punct key: '{}'", punct_key);
+        if !self.alternate_punct(context, &punct_key, &definition) {
+            context.push_input(ch);
+            engine.flush_context_events();
+            let context = engine.context_mut();
+            let _ = Self::confirm_unique_punct(context, &definition)
+                || Self::auto_commit_punct(context, &definition)
+                || self.pair_punct(context, &definition);
+        }
+        ProcessResult::Accepted
+    }
+}
+
+pub(crate) struct PunctSegmentor {
+    config: PunctConfig,
+}
+
+impl PunctSegmentor {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        Self {
+            config: PunctConfig::new(ticket),
+        }
+    }
+}
+
+impl Segmentor for PunctSegmentor {
+    fn proceed(&mut self, segmentation: &mut Segmentation) -> bool {
+        let k = segmentation.get_current_start_position();
+        let Some(&ch) = segmentation.input().as_bytes().get(k) else {
+            // no chance for others too
+            return false;
+        };
+        if !(0x20..0x7f).contains(&ch) {
+            return true;
+        }
+        if !self.config.is_punct_key(&(ch as char).to_string()) {
+            return true;
+        }
+        let mut segment = Segment::new(k, k + 1);
+        info!(
+            "add a punctuation segment [{}, {})",
+            segment.start, segment.end
+        );
+        segment.tags.insert("punct".to_string());
+        segmentation.add_segment(segment);
+        // exclusive
+        false
+    }
+}
+
+pub(crate) struct PunctTranslator {
+    config: PunctConfig,
+}
+
+impl PunctTranslator {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        Self {
+            config: PunctConfig::new(ticket),
+        }
+    }
+
+    fn translate_unique_punct(
+        segment: &Segment,
+        definition: &ConfigValue,
+    ) -> Arc<RwLock<dyn Translation>> {
+        Arc::new(RwLock::new(UniqueTranslation::new(Some(
+            create_punct_candidate(definition.str(), segment),
+        ))))
+    }
+
+    fn translate_alternating_punct(
+        key: &str,
+        segment: &Segment,
+        definition: &ConfigList,
+    ) -> Option<Arc<RwLock<dyn Translation>>> {
+        let mut translation = FifoTranslation::new();
+        for i in 0..definition.size() {
+            match definition.get_value_at(i) {
+                Some(value) => translation.append(Some(create_punct_candidate(value.str(), segment))),
+                None => warn!("invalid alternating punct at index {} for '{}'.", i, key),
+            }
+        }
+        if translation.size() == 0 {
+            warn!("empty candidate list for alternating punct '{}'.", key);
+            return None;
+        }
+        Some(Arc::new(RwLock::new(translation)))
+    }
+
+    fn translate_auto_commit_punct(
+        key: &str,
+        segment: &Segment,
+        definition: &ConfigMap,
+    ) -> Option<Arc<RwLock<dyn Translation>>> {
+        if !definition.has_key("commit") {
+            return None;
+        }
+        let Some(value) = definition.get_value("commit") else {
+            warn!("unrecognized punct definition for '{}'.", key);
+            return None;
+        };
+        Some(Self::translate_unique_punct(segment, value))
+    }
+
+    fn translate_paired_punct(
+        key: &str,
+        segment: &Segment,
+        definition: &ConfigMap,
+    ) -> Option<Arc<RwLock<dyn Translation>>> {
+        if !definition.has_key("pair") {
+            return None;
+        }
+        let pair = definition.get_list("pair").filter(|list| list.size() == 2);
+        let Some(pair) = pair else {
+            warn!("unrecognized pair definition for '{}'.", key);
+            return None;
+        };
+        let mut translation = FifoTranslation::new();
+        for i in 0..pair.size() {
+            match pair.get_value_at(i) {
+                Some(value) => translation.append(Some(create_punct_candidate(value.str(), segment))),
+                None => warn!("invalid paired punct at index {} for '{}'.", i, key),
+            }
+        }
+        if translation.size() != 2 {
+            warn!("invalid num of candidate for paired punct '{}'.", key);
+            return None;
+        }
+        Some(Arc::new(RwLock::new(translation)))
+    }
+}
+
+impl Translator for PunctTranslator {
+    fn query(
+        &mut self,
+        input: &str,
+        segment: &Segment,
+        context: &Context,
+    ) -> Option<Arc<RwLock<dyn Translation>>> {
+        if !segment.has_tag("punct") {
+            return None;
+        }
+        let definition = self
+            .config
+            .get_punct_definition(input, context.get_option("full_shape"))?;
+        info!("populating punctuation candidates for '{}'.", input);
+        let definition = definition.as_any();
+        if let Some(value) = definition.downcast_ref::<ConfigValue>() {
+            return Some(Self::translate_unique_punct(segment, value));
+        }
+        if let Some(list) = definition.downcast_ref::<ConfigList>() {
+            return Self::translate_alternating_punct(input, segment, list);
+        }
+        let map = definition.downcast_ref::<ConfigMap>()?;
+        Self::translate_auto_commit_punct(input, segment, map)
+            .or_else(|| Self::translate_paired_punct(input, segment, map))
+    }
+}
+
+fn create_punct_candidate(punct: &str, segment: &Segment) -> Arc<dyn Candidate> {
+    const HALF_SHAPE: &str = "〔半角〕";
+    const FULL_SHAPE: &str = "〔全角〕";
+    let mut chars = punct.chars();
+    let (is_half_shape, is_full_shape) = match (chars.next(), chars.next()) {
+        // a single unicode character
+        (Some(ch), None) => {
+            let is_ascii = ('\u{20}'..'\u{7f}').contains(&ch);
+            let is_ideographic_space = ch == '\u{3000}';
+            let is_full_shape_ascii = ('\u{ff01}'..='\u{ff5e}').contains(&ch);
+            let is_half_shape_kana = ('\u{ff65}'..='\u{ffdc}').contains(&ch);
+            (
+                is_ascii || is_half_shape_kana,
+                is_ideographic_space || is_full_shape_ascii,
+            )
+        }
+        _ => (false, false),
+    };
+    let comment = if is_half_shape {
+        HALF_SHAPE
+    } else if is_full_shape {
+        FULL_SHAPE
+    } else {
+        ""
+    };
+    let one_key = segment.end - segment.start == 1;
+    Arc::new(SimpleCandidate::new(
+        "punct".to_string(),
+        segment.start,
+        segment.end,
+        punct.to_string(),
+        Some(comment.to_string()),
+        one_key.then(|| punct.to_string()),
+    ))
+}
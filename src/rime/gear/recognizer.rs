@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+
+use log::{error, info};
+use regex::Regex;
+
+use crate::rime::component::Ticket;
+use crate::rime::config::config_types::{ConfigMap, ConfigValue};
+use crate::rime::engine::Engine;
+use crate::rime::key_event::KeyEvent;
+use crate::rime::processor::{ProcessResult, Processor};
+use crate::rime::segmentation::{Segment, Segmentation};
+use crate::rime::segmentor::Segmentor;
+
+pub(crate) struct RecognizerMatch {
+    pub(crate) tag: String,
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+}
+
+// Regular expressions under recognizer/patterns, by tag.
+#[derive(Default)]
+pub(crate) struct RecognizerPatterns(BTreeMap<String, Regex>);
+
+impl RecognizerPatterns {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        let mut patterns = Self::default();
+        let Some(item) = ticket.schema.config().get_item("recognizer/patterns") else {
+            return patterns;
+        };
+        let Some(pattern_map) = item.as_any().downcast_ref::<ConfigMap>() else {
+            error!("recognizer/patterns should be a map.");
+            return patterns;
+        };
+        for (tag, value) in &pattern_map.map {
+            let Some(value) = value.as_any().downcast_ref::<ConfigValue>() else {
+                continue;
+            };
+            match Regex::new(value.str()) {
+                Ok(pattern) => {
+                    patterns.0.insert(tag.clone(), pattern);
+                }
+                Err(e) => error!("error parsing pattern /{}/: {}", value.str(), e),
+            }
+        }
+        patterns
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // Finds a pattern matching the unconfirmed input up to its end, starting
+    // at the current segment or at the start of an existing segment.
+    pub(crate) fn get_match(
+        &self,
+        input: &str,
+        segmentation: &Segmentation,
+    ) -> Option<RecognizerMatch> {
+        let j = segmentation.get_current_end_position();
+        let k = segmentation.get_confirmed_position();
+        let active_input = input.get(k..)?;
+        info!("active input: {}[{}, {})", active_input, j, input.len());
+        for (tag, pattern) in &self.0 {
+            let Some(m) = pattern.find(active_input) else {
+                continue;
+            };
+            let start = k + m.start();
+            let end = k + m.end();
+            if end != input.len() {
+                continue;
+            }
+            let found = start == j
+                || segmentation
+                    .segments
+                    .iter()
+                    .take_while(|segment| start >= segment.start)
+                    .any(|segment| start == segment.start);
+            if found {
+                return Some(RecognizerMatch {
+                    tag: tag.clone(),
+                    start,
+                    end,
+                });
+            }
+        }
+        None
+    }
+}
+
+// Takes in characters that would make the input match one of the patterns,
+// even if other processors don't know about them.
+pub(crate) struct Recognizer {
+    patterns: RecognizerPatterns,
+    use_space: bool,
+}
+
+impl Recognizer {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        Self {
+            patterns: RecognizerPatterns::new(ticket),
+            use_space: ticket.schema.config().get_bool("recognizer/use_space"),
+        }
+    }
+}
+
+impl Processor for Recognizer {
+    fn process_key_event(&mut self, key_event: &KeyEvent, engine: &mut Engine) -> ProcessResult {
+        if self.patterns.is_empty()
+            || key_event.ctrl()
+            || key_event.alt()
+            || key_event.super_()
+            || key_event.release()
+        {
+            return ProcessResult::Noop;
+        }
+        let ch = key_event.keycode();
+        if (self.use_space && ch == 0x20) || (ch > 0x20 && ch < 0x80) {
+            // pattern matching against the input string plus the incoming character
+            let ch = ch as u8 as char;
+            let context = engine.context_mut();
+            let mut input = context.input().to_string();
+            input.push(ch);
+            if self
+                .patterns
+                .get_match(&input, context.composition())
+                .is_some()
+            {
+                context.push_input(ch);
+                return ProcessResult::Accepted;
+            }
+        }
+        ProcessResult::Noop
+    }
+}
+
+// Tags the segment matching one of the patterns, replacing any segments
+// that have been made in its span.
+pub(crate) struct Matcher {
+    patterns: RecognizerPatterns,
+}
+
+impl Matcher {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        Self {
+            patterns: RecognizerPatterns::new(ticket),
+        }
+    }
+}
+
+impl Segmentor for Matcher {
+    fn proceed(&mut self, segmentation: &mut Segmentation) -> bool {
+        if self.patterns.is_empty() {
+            return true;
+        }
+        let input = segmentation.input().to_string();
+        if let Some(m) = self.patterns.get_match(&input, segmentation) {
+            info!("match: {} [{}, {})", m.tag, m.start, m.end);
+            while segmentation.get_current_start_position() > m.start {
+                segmentation.segments.pop();
+            }
+            let mut segment = Segment::new(m.start, m.end);
+            segment.tags.insert(m.tag);
+            segmentation.add_segment(segment);
+        }
+        // continue this round
+        true
+    }
+}
+
+#[test]
+fn tag_input_matching_a_pattern() {
+    use crate::rime::engine::test_engine;
+
+    let (mut engine, _) = test_engine(
+        "recognizer:
+  patterns:
+    code: \"^[a-z]+[0-9]+$\"
+engine:
+  processors: [recognizer, speller]
+  segmentors: [abc_segmentor, matcher]
+",
+    );
+    // digits are taken only where they make the input match
+    assert!(!engine.process_key(&KeyEvent::new('1' as u32, 0)));
+    assert!(engine.process_key(&KeyEvent::new('a' as u32, 0)));
+    assert!(engine.process_key(&KeyEvent::new('b' as u32, 0)));
+    let segments = &engine.context().composition().segments;
+    assert!(segments[0].has_tag("abc"));
+
+    assert!(engine.process_key(&KeyEvent::new('1' as u32, 0)));
+    assert_eq!("ab1", engine.context().input());
+    // the match replaces the shorter segment
+    let segments = &engine.context().composition().segments;
+    assert_eq!(1, segments.len());
+    assert_eq!((0, 3), (segments[0].start, segments[0].end));
+    assert!(segments[0].has_tag("code"));
+    assert!(!segments[0].has_tag("abc"));
+}
@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+use crate::rime::algo::algebra::Projection;
+use crate::rime::candidate::{Candidate, CandidateList, ShadowCandidate};
+use crate::rime::component::Ticket;
+use crate::rime::context::Context;
+use crate::rime::dict::reverse_lookup_dictionary::ReverseLookupDictionary;
+use crate::rime::filter::Filter;
+use crate::rime::segmentation::Segment;
+use crate::rime::translation::Translation;
+
+// Comments candidates with their codes in a dictionary, e.g. to show how a
+// word is typed in the main scheme.
+pub(crate) struct ReverseLookupFilter {
+    commenter: Option<Arc<ReverseLookupCommenter>>,
+    tags: HashSet<String>,
+}
+
+struct ReverseLookupCommenter {
+    rev_dict: ReverseLookupDictionary,
+    overwrite_comment: bool,
+    append_comment: bool,
+    comment_formatter: Projection,
+}
+
+impl ReverseLookupFilter {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        if ticket.name_space == "filter" {
+            let ticket = Ticket::new(
+                ticket.schema,
+                ticket.context,
+                "reverse_lookup",
+                &ticket.klass,
+            );
+            return Self::new(&ticket);
+        }
+        let config = ticket.schema.config();
+        let name_space = &ticket.name_space;
+        let mut tags = HashSet::new();
+        if let Some(list) = config.get_list(&format!("{}/tags", name_space)) {
+            for i in 0..list.size() {
+                if let Some(tag) = list.get_str_at(i) {
+                    tags.insert(tag.to_string());
+                }
+            }
+        }
+        if tags.is_empty() {
+            tags.insert("abc".to_string());
+        }
+        let mut dict_name = config.get_string(&format!("{}/dictionary", name_space));
+        if dict_name.is_empty() {
+            dict_name = config.get_string("translator/dictionary");
+        }
+        let rev_dict = ticket
+            .schema
+            .data_dir()
+            .filter(|_| !dict_name.is_empty())
+            .and_then(|data_dir| ReverseLookupDictionary::create(&data_dir, &dict_name));
+        let commenter = rev_dict.map(|rev_dict| {
+            let mut comment_formatter = Projection::new();
+            comment_formatter.load(config.get_list(&format!("{}/comment_format", name_space)));
+            Arc::new(ReverseLookupCommenter {
+                rev_dict,
+                overwrite_comment: config.get_bool(&format!("{}/overwrite_comment", name_space)),
+                append_comment: config.get_bool(&format!("{}/append_comment", name_space)),
+                comment_formatter,
+            })
+        });
+        Self { commenter, tags }
+    }
+}
+
+impl Filter for ReverseLookupFilter {
+    fn apply(
+        &self,
+        translation: Arc<RwLock<dyn Translation>>,
+        _candidates: Arc<RwLock<CandidateList>>,
+        _context: &Context,
+    ) -> Arc<RwLock<dyn Translation>> {
+        let Some(commenter) = &self.commenter else {
+            return translation;
+        };
+        Arc::new(RwLock::new(ReverseLookupFilterTranslation::new(
+            translation,
+            commenter.clone(),
+        )))
+    }
+
+    fn applies_to_segment(&self, segment: &Segment) -> bool {
+        segment.has_any_tag_in(&self.tags)
+    }
+}
+
+impl ReverseLookupCommenter {
+    fn comment(&self, candidate: &Arc<dyn Candidate>) -> Option<String> {
+        let comment = candidate.comment();
+        if !comment.is_empty() && !self.overwrite_comment && !self.append_comment {
+            return None;
+        }
+        let mut codes = self.rev_dict.reverse_lookup(candidate.text())?.to_string();
+        self.comment_formatter.apply(Some(&mut codes));
+        if codes.is_empty() {
+            return None;
+        }
+        if self.append_comment && !comment.is_empty() {
+            Some(format!("{} {}", comment, codes))
+        } else {
+            Some(codes)
+        }
+    }
+}
+
+struct ReverseLookupFilterTranslation {
+    translation: Arc<RwLock<dyn Translation>>,
+    commenter: Arc<ReverseLookupCommenter>,
+    candidate: Option<Arc<dyn Candidate>>,
+}
+
+impl ReverseLookupFilterTranslation {
+    fn new(
+        translation: Arc<RwLock<dyn Translation>>,
+        commenter: Arc<ReverseLookupCommenter>,
+    ) -> Self {
+        let mut filtered = Self {
+            translation,
+            commenter,
+            candidate: None,
+        };
+        filtered.prepare_candidate();
+        filtered
+    }
+
+    fn prepare_candidate(&mut self) {
+        let Some(candidate) = self.translation.read().ok().and_then(|t| t.peek()) else {
+            self.candidate = None;
+            return;
+        };
+        self.candidate = match self.commenter.comment(&candidate) {
+            Some(comment) => Some(Arc::new(ShadowCandidate::new(
+                candidate.clone(),
+                candidate.type_().to_string(),
+                None,
+                Some(comment),
+                None,
+            )) as Arc<dyn Candidate>),
+            None => Some(candidate),
+        };
+    }
+}
+
+impl Translation for ReverseLookupFilterTranslation {
+    fn next(&mut self) -> Option<Arc<dyn Candidate>> {
+        let candidate = self.candidate.take()?;
+        if let Ok(mut translation) = self.translation.write() {
+            translation.next();
+        }
+        self.prepare_candidate();
+        Some(candidate)
+    }
+
+    fn peek(&self) -> Option<Arc<dyn Candidate>> {
+        self.candidate.clone()
+    }
+
+    fn exhausted(&self) -> bool {
+        self.candidate.is_none()
+    }
+}
+
+#[test]
+fn comment_with_codes_from_reverse_db() {
+    use std::fs;
+
+    use crate::rime::candidate::SimpleCandidate;
+    use crate::rime::common::PathExt;
+    use crate::rime::dict::reverse_lookup_dictionary::ReverseDb;
+    use crate::rime::dict::vocabulary::ReverseLookupTable;
+    use crate::rime::schema::Schema;
+    use crate::rime::translation::FifoTranslation;
+
+    let data_dir = PathExt::new("reverse_lookup_filter_test");
+    let _ = fs::remove_dir_all(&data_dir);
+    fs::create_dir_all(data_dir.join("build")).unwrap();
+    let reverse_path = data_dir.join("build").join("sample.reverse.bin");
+    let mut table = ReverseLookupTable::new();
+    table
+        .entry("好".to_string())
+        .or_default()
+        .insert("hao".to_string());
+    let codes = table.entry("号".to_string()).or_default();
+    codes.insert("hao4".to_string());
+    codes.insert("hao".to_string());
+    let mut reverse_db = ReverseDb::new(reverse_path.clone());
+    assert!(reverse_db.build(&table, 1) && reverse_db.save());
+
+    let mut reverse_db = ReverseDb::new(reverse_path);
+    assert!(reverse_db.exists() && reverse_db.load());
+    assert_eq!(1, reverse_db.dict_file_checksum());
+    assert_eq!(Some("hao hao4"), reverse_db.lookup("号"));
+    assert_eq!(None, reverse_db.lookup("猫"));
+
+    fs::write(
+        data_dir.join("sample.schema.yaml"),
+        "reverse_lookup:\n  dictionary: sample\n  append_comment: true\n",
+    )
+    .unwrap();
+    let schema = Schema::load(&data_dir, "sample").unwrap();
+    let context = Context::new();
+    let ticket = Ticket::new(&schema, &context, "filter", "reverse_lookup_filter");
+    let filter = ReverseLookupFilter::new(&ticket);
+    let mut translation = FifoTranslation::new();
+    for (text, comment) in [("好", ""), ("号", "x"), ("猫", "y")] {
+        translation.append(Some(Arc::new(SimpleCandidate::new(
+            "table".to_string(),
+            0,
+            3,
+            text.to_string(),
+            Some(comment.to_string()),
+            None,
+        ))));
+    }
+    let translation = filter.apply(
+        Arc::new(RwLock::new(translation)),
+        Arc::new(RwLock::new(CandidateList::new())),
+        &context,
+    );
+    let mut comments = Vec::new();
+    while let Some(candidate) = translation.write().unwrap().next() {
+        comments.push(candidate.comment().to_string());
+    }
+    // codes follow the comment, if any; words without codes are left as is
+    assert_eq!(vec!["hao", "x hao hao4", "y"], comments);
+}
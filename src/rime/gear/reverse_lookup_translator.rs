@@ -0,0 +1,238 @@
+use std::sync::{Arc, RwLock};
+
+use log::{error, info};
+
+use crate::rime::algo::syllabifier::{Syllabifier, SyllableGraph};
+use crate::rime::candidate::Candidate;
+use crate::rime::component::Ticket;
+use crate::rime::context::Context;
+use crate::rime::dict::corrector::NearSearchCorrector;
+use crate::rime::dict::dictionary::{DictEntryCollector, Dictionary};
+use crate::rime::dict::reverse_lookup_dictionary::ReverseLookupDictionary;
+use crate::rime::gear::translator_commons::{Phrase, TranslatorOptions};
+use crate::rime::language::Language;
+use crate::rime::segmentation::Segment;
+use crate::rime::translation::{DistinctTranslation, Translation};
+use crate::rime::translator::Translator;
+
+// Lets the user type in another scheme, e.g. pinyin after a backquote, and
+// shows the codes of each candidate in the main scheme.
+pub(crate) struct ReverseLookupTranslator {
+    tag: String,
+    prefix: String,
+    suffix: String,
+    options: Arc<TranslatorOptions>,
+    dict: Option<Arc<Dictionary>>,
+    rev_dict: Option<Arc<ReverseLookupDictionary>>,
+    language: Option<Arc<Language>>,
+}
+
+impl ReverseLookupTranslator {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        if ticket.name_space == "translator" {
+            let ticket = Ticket::new(
+                ticket.schema,
+                ticket.context,
+                "reverse_lookup",
+                &ticket.klass,
+            );
+            return Self::new(&ticket);
+        }
+        let config = ticket.schema.config();
+        let name_space = &ticket.name_space;
+        let mut tag = config.get_string(&format!("{}/tag", name_space));
+        if tag.is_empty() {
+            tag = "reverse_lookup".to_string();
+        }
+        let data_dir = ticket.schema.data_dir();
+        let dict_name = config.get_string(&format!("{}/dictionary", name_space));
+        let mut prism_name = config.get_string(&format!("{}/prism", name_space));
+        if prism_name.is_empty() {
+            prism_name = dict_name.clone();
+        }
+        let dict = if dict_name.is_empty() {
+            error!("{}/dictionary not specified.", name_space);
+            None
+        } else {
+            data_dir
+                .as_ref()
+                .and_then(|data_dir| Dictionary::create(data_dir, &dict_name, &prism_name))
+                .map(Arc::new)
+        };
+        // the dictionary of the main translator
+        let target_name = config.get_string("translator/dictionary");
+        let rev_dict = data_dir
+            .as_ref()
+            .filter(|_| !target_name.is_empty())
+            .and_then(|data_dir| ReverseLookupDictionary::create(data_dir, &target_name))
+            .map(Arc::new);
+        let language = dict.as_ref().map(|dict| {
+            Arc::new(Language::new(&Language::get_language_component(
+                dict.name(),
+            )))
+        });
+        Self {
+            tag,
+            prefix: config.get_string(&format!("{}/prefix", name_space)),
+            suffix: config.get_string(&format!("{}/suffix", name_space)),
+            options: Arc::new(TranslatorOptions::new(ticket)),
+            dict,
+            rev_dict,
+            language,
+        }
+    }
+}
+
+impl Translator for ReverseLookupTranslator {
+    fn query(
+        &mut self,
+        input: &str,
+        segment: &Segment,
+        _context: &Context,
+    ) -> Option<Arc<RwLock<dyn Translation>>> {
+        if !segment.has_tag(&self.tag) {
+            return None;
+        }
+        let dict = self.dict.as_ref()?;
+        if !dict.loaded() {
+            error!("dictionary '{}' not loaded.", dict.name());
+            return None;
+        }
+        info!("input = '{}', [{}, {})", input, segment.start, segment.end);
+
+        let mut code = input;
+        let mut start = segment.start;
+        if !self.prefix.is_empty() && code.starts_with(&self.prefix) {
+            code = &code[self.prefix.len()..];
+            start += self.prefix.len();
+        }
+        let mut has_suffix = false;
+        if !self.suffix.is_empty() && code.ends_with(&self.suffix) {
+            code = &code[..code.len() - self.suffix.len()];
+            has_suffix = true;
+        }
+        if code.is_empty() {
+            return None;
+        }
+
+        let syllabifier: Syllabifier<NearSearchCorrector> = Syllabifier::new(
+            self.options.delimiters().to_string(),
+            self.options.enable_completion(),
+            self.options.strict_spelling(),
+        );
+        let mut graph = SyllableGraph::default();
+        syllabifier.build_syllable_graph(code, dict.prism(), &mut graph);
+        let phrase = dict.lookup(&graph, 0, self.options.enable_completion(), 0.0)?;
+
+        let translation = ReverseLookupTranslation {
+            options: self.options.clone(),
+            language: self.language.clone(),
+            rev_dict: self.rev_dict.clone(),
+            code_length: code.len(),
+            start,
+            end: if has_suffix {
+                segment.end
+            } else {
+                start + code.len()
+            },
+            phrase,
+            phrase_end: None,
+            candidate: None,
+        }
+        .prepared();
+        if translation.exhausted() {
+            return None;
+        }
+        Some(Arc::new(RwLock::new(DistinctTranslation::new(Some(
+            Arc::new(RwLock::new(translation)),
+        )))))
+    }
+}
+
+// Phrases by decreasing input length, commented with their codes in the
+// main scheme.
+struct ReverseLookupTranslation {
+    options: Arc<TranslatorOptions>,
+    language: Option<Arc<Language>>,
+    rev_dict: Option<Arc<ReverseLookupDictionary>>,
+    code_length: usize,
+    // where the code starts and the segment ends in the input
+    start: usize,
+    end: usize,
+    phrase: DictEntryCollector,
+    phrase_end: Option<usize>,
+    candidate: Option<Arc<dyn Candidate>>,
+}
+
+impl ReverseLookupTranslation {
+    fn prepared(mut self) -> Self {
+        self.prepare_candidate();
+        self
+    }
+
+    fn prepare_candidate(&mut self) {
+        while let Some(entry) = self.phrase.last_entry() {
+            if !entry.get().exhausted() {
+                break;
+            }
+            entry.remove();
+        }
+        let Some((end_pos, entry)) = self
+            .phrase
+            .iter()
+            .next_back()
+            .and_then(|(&end_pos, iter)| Some((end_pos, iter.peek()?)))
+        else {
+            self.phrase_end = None;
+            self.candidate = None;
+            return;
+        };
+        // a phrase spelled by the whole code takes the suffix as well
+        let end = if end_pos >= self.code_length {
+            self.end
+        } else {
+            self.start + end_pos
+        };
+        let mut comment = self
+            .rev_dict
+            .as_ref()
+            .and_then(|rev_dict| rev_dict.reverse_lookup(&entry.text))
+            .unwrap_or_default()
+            .to_string();
+        self.options.format_comment(&mut comment);
+        let quality = entry.weight.exp() + self.options.initial_quality();
+        let mut phrase = Phrase::new(
+            self.language.clone(),
+            "reverse_lookup",
+            self.start,
+            end,
+            entry,
+        );
+        phrase.set_quality(quality);
+        phrase.set_comment(&comment);
+        self.phrase_end = Some(end_pos);
+        self.candidate = Some(Arc::new(phrase));
+    }
+}
+
+impl Translation for ReverseLookupTranslation {
+    fn next(&mut self) -> Option<Arc<dyn Candidate>> {
+        let candidate = self.candidate.take()?;
+        if let Some(iter) = self
+            .phrase_end
+            .and_then(|end_pos| self.phrase.get_mut(&end_pos))
+        {
+            iter.next();
+        }
+        self.prepare_candidate();
+        Some(candidate)
+    }
+
+    fn peek(&self) -> Option<Arc<dyn Candidate>> {
+        self.candidate.clone()
+    }
+
+    fn exhausted(&self) -> bool {
+        self.candidate.is_none()
+    }
+}
@@ -0,0 +1,464 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+
+use log::{error, info};
+use signals2::Connection;
+
+use crate::rime::algo::syllabifier::{Syllabifier, SyllableGraph};
+use crate::rime::algo::SyllableId;
+use crate::rime::candidate::Candidate;
+use crate::rime::component::Ticket;
+use crate::rime::context::Context;
+use crate::rime::dict::corrector::NearSearchCorrector;
+use crate::rime::dict::dictionary::{DictEntryCollector, Dictionary};
+use crate::rime::dict::user_dictionary::UserDictionary;
+use crate::rime::dict::vocabulary::DictEntry;
+use crate::rime::gear::grammar::load_grammar;
+use crate::rime::gear::poet::{homophones, Poet, WordGraph};
+use crate::rime::gear::translator_commons::{
+    create_user_dictionary, learn_from_commits, Phrase, Sentence, TranslatorOptions,
+};
+use crate::rime::language::Language;
+use crate::rime::segmentation::Segment;
+use crate::rime::spans::Spans;
+use crate::rime::translation::{
+    DistinctTranslation, FifoTranslation, Translation, UnionTranslation,
+};
+use crate::rime::translator::Translator;
+
+// Translates spellings of syllables, e.g. pinyin, into phrases found along
+// the paths of the syllable graph, plus sentences composed of them.
+pub(crate) struct ScriptTranslator {
+    options: Arc<TranslatorOptions>,
+    dict: Option<Arc<Dictionary>>,
+    user_dict: Option<Arc<RwLock<UserDictionary>>>,
+    language: Option<Arc<Language>>,
+    poet: Poet,
+    // slot learning from the commits of the context
+    connection: Option<Connection>,
+}
+
+impl ScriptTranslator {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        let config = ticket.schema.config();
+        let name_space = &ticket.name_space;
+        let dict_name = config.get_string(&format!("{}/dictionary", name_space));
+        let mut prism_name = config.get_string(&format!("{}/prism", name_space));
+        if prism_name.is_empty() {
+            prism_name = dict_name.clone();
+        }
+        let dict = if dict_name.is_empty() {
+            error!("{}/dictionary not specified.", name_space);
+            None
+        } else {
+            ticket
+                .schema
+                .data_dir()
+                .and_then(|data_dir| Dictionary::create(&data_dir, &dict_name, &prism_name))
+                .map(Arc::new)
+        };
+        let language = dict.as_ref().map(|dict| {
+            Arc::new(Language::new(&Language::get_language_component(
+                dict.name(),
+            )))
+        });
+        let options = Arc::new(TranslatorOptions::new(ticket));
+        let user_dict = create_user_dictionary(ticket, &dict_name);
+        let connection = user_dict.as_ref().map(|user_dict| {
+            learn_from_commits(
+                ticket,
+                user_dict.clone(),
+                &["phrase", "user_phrase", "sentence"],
+                options.delimiters(),
+            )
+        });
+        let poet = Poet::new(language.clone(), load_grammar(ticket.schema));
+        Self {
+            options,
+            dict,
+            user_dict,
+            language,
+            poet,
+            connection,
+        }
+    }
+
+    // Composes sentences of words found at each vertex of the graph.
+    fn make_sentences(
+        &self,
+        dict: &Dictionary,
+        graph: &SyllableGraph,
+        words: &DictEntryCollector,
+        preceding_text: &str,
+    ) -> Vec<Sentence> {
+        let mut word_graph = WordGraph::new();
+        for &start_pos in graph.vertices().keys() {
+            let collector = if start_pos == 0 {
+                Some(words.clone())
+            } else {
+                dict.lookup(graph, start_pos, false, 0.0)
+            };
+            let Some(collector) = collector else {
+                continue;
+            };
+            word_graph.insert(
+                start_pos,
+                collector
+                    .into_iter()
+                    .map(|(end_pos, iter)| (end_pos, homophones(iter)))
+                    .collect(),
+            );
+        }
+        self.poet
+            .make_sentences(&word_graph, graph.interpreted_length(), preceding_text)
+    }
+
+    // Words the user has committed with the whole input, ranked by how often
+    // and how recently.
+    fn make_user_phrases(&self, input: &str, segment: &Segment) -> Option<FifoTranslation> {
+        let code = input.trim_end_matches(|c| self.options.delimiters().contains(c));
+        let entries = self.user_dict.as_ref()?.write().ok()?.lookup(code, false);
+        if entries.is_empty() {
+            return None;
+        }
+        let mut preedit = input.to_string();
+        self.options.format_preedit(&mut preedit);
+        let mut translation = FifoTranslation::new();
+        for entry in entries {
+            let quality = entry.weight.exp() + self.options.initial_quality();
+            let mut phrase = Phrase::new(
+                self.language.clone(),
+                "user_phrase",
+                segment.start,
+                segment.start + input.len(),
+                entry,
+            );
+            phrase.set_quality(quality);
+            phrase.set_preedit(&preedit);
+            translation.append(Some(Arc::new(phrase)));
+        }
+        Some(translation)
+    }
+}
+
+impl Drop for ScriptTranslator {
+    fn drop(&mut self) {
+        if let Some(connection) = &self.connection {
+            connection.disconnect();
+        }
+    }
+}
+
+impl Translator for ScriptTranslator {
+    fn query(
+        &mut self,
+        input: &str,
+        segment: &Segment,
+        context: &Context,
+    ) -> Option<Arc<RwLock<dyn Translation>>> {
+        if !segment.has_any_tag_in(self.options.tags()) {
+            return None;
+        }
+        let dict = self.dict.as_ref()?;
+        if !dict.loaded() {
+            error!("dictionary '{}' not loaded.", dict.name());
+            return None;
+        }
+        info!("input = '{}', [{}, {})", input, segment.start, segment.end);
+
+        let mut corrector = NearSearchCorrector;
+        let mut syllabifier = Syllabifier::new(
+            self.options.delimiters().to_string(),
+            self.options.enable_completion(),
+            self.options.strict_spelling(),
+        );
+        if self.options.enable_correction() {
+            syllabifier.enable_correction(&mut corrector);
+        }
+        let mut graph = SyllableGraph::default();
+        let consumed = syllabifier.build_syllable_graph(input, dict.prism(), &mut graph);
+
+        let phrase = dict
+            .lookup(&graph, 0, self.options.enable_completion(), 0.0)
+            .unwrap_or_default();
+        let translated_len = phrase.keys().next_back().copied().unwrap_or(0);
+        let sentences = if translated_len < consumed && graph.edges().len() > 1 {
+            let preceding_text = context.composition().get_text_before(segment.start);
+            self.make_sentences(dict, &graph, &phrase, &preceding_text)
+        } else {
+            Vec::new()
+        };
+
+        let translation = ScriptTranslation::new(
+            self.options.clone(),
+            self.language.clone(),
+            input,
+            segment.start,
+            graph,
+            phrase,
+            sentences.into(),
+        );
+        let mut union = UnionTranslation::new();
+        if let Some(user_phrases) = self.make_user_phrases(input, segment) {
+            union.add_translation(Some(Arc::new(RwLock::new(user_phrases))));
+        }
+        union.add_translation(Some(Arc::new(RwLock::new(translation))));
+        if union.exhausted() {
+            return None;
+        }
+        Some(Arc::new(RwLock::new(DistinctTranslation::new(Some(
+            Arc::new(RwLock::new(union)),
+        )))))
+    }
+}
+
+// Phrases by decreasing input length, each group in order of weight, then
+// the sentences.
+struct ScriptTranslation {
+    options: Arc<TranslatorOptions>,
+    language: Option<Arc<Language>>,
+    input: String,
+    start: usize,
+    graph: SyllableGraph,
+    phrase: DictEntryCollector,
+    sentences: VecDeque<Sentence>,
+    // end position of the phrase being the current candidate
+    phrase_end: Option<usize>,
+    candidate: Option<Arc<dyn Candidate>>,
+}
+
+impl ScriptTranslation {
+    fn new(
+        options: Arc<TranslatorOptions>,
+        language: Option<Arc<Language>>,
+        input: &str,
+        start: usize,
+        graph: SyllableGraph,
+        phrase: DictEntryCollector,
+        sentences: VecDeque<Sentence>,
+    ) -> Self {
+        let mut translation = Self {
+            options,
+            language,
+            input: input.to_string(),
+            start,
+            graph,
+            phrase,
+            sentences,
+            phrase_end: None,
+            candidate: None,
+        };
+        translation.prepare_candidate();
+        translation
+    }
+
+    fn prepare_candidate(&mut self) {
+        while let Some(entry) = self.phrase.last_entry() {
+            if !entry.get().exhausted() {
+                break;
+            }
+            entry.remove();
+        }
+        let next_phrase = self
+            .phrase
+            .iter()
+            .next_back()
+            .and_then(|(&end_pos, iter)| Some((end_pos, iter.peek()?)));
+        if let Some((end_pos, entry)) = next_phrase {
+            let phrase = self.make_phrase(entry, end_pos);
+            self.phrase_end = Some(end_pos);
+            self.candidate = Some(Arc::new(phrase));
+            return;
+        }
+        self.phrase_end = None;
+        let sentence = self.sentences.pop_front();
+        self.candidate = sentence
+            .map(|sentence| Arc::new(self.make_sentence_candidate(sentence)) as Arc<dyn Candidate>);
+    }
+
+    fn make_phrase(&self, entry: Arc<DictEntry>, end_pos: usize) -> Phrase {
+        let quality = entry.weight.exp() + self.options.initial_quality();
+        let vertices = self.syllable_vertices(&entry.code, 0, end_pos);
+        let mut phrase = Phrase::new(
+            self.language.clone(),
+            "phrase",
+            self.start,
+            self.start + end_pos,
+            entry,
+        );
+        phrase.set_quality(quality);
+        phrase.set_preedit(&self.format_preedit(&vertices));
+        phrase.set_spans(self.make_spans(&vertices));
+        phrase
+    }
+
+    // Spans of a candidate passing the given vertices, followed by the
+    // syllables of the input left after it, as found in the syllable graph.
+    fn make_spans(&self, vertices: &[usize]) -> Spans {
+        let mut spans = Spans::default();
+        for &vertex in vertices {
+            spans.add_vertex(self.start + vertex);
+        }
+        let end_pos = vertices.last().copied().unwrap_or(0);
+        for &vertex in self.graph.vertices().keys() {
+            if vertex > end_pos {
+                spans.add_vertex(self.start + vertex);
+            }
+        }
+        spans
+    }
+
+    fn make_sentence_candidate(&self, mut sentence: Sentence) -> Sentence {
+        let mut vertices = vec![0];
+        let mut word_start = 0;
+        for (entry, &length) in sentence.components().iter().zip(sentence.word_lengths()) {
+            let word_end = word_start + length;
+            vertices.extend(
+                self.syllable_vertices(&entry.code, word_start, word_end)
+                    .into_iter()
+                    .skip(1),
+            );
+            word_start = word_end;
+        }
+        sentence.offset(self.start);
+        sentence.set_quality(sentence.weight().exp() + self.options.initial_quality());
+        sentence.set_preedit(&self.format_preedit(&vertices));
+        sentence.set_spans(self.make_spans(&vertices));
+        sentence
+    }
+
+    // Finds the syllable boundaries of a code spelled in [start_pos, end_pos)
+    // of the input.
+    fn syllable_vertices(
+        &self,
+        code: &[SyllableId],
+        start_pos: usize,
+        end_pos: usize,
+    ) -> Vec<usize> {
+        let mut vertices = vec![start_pos];
+        if !find_path(code, &self.graph, start_pos, end_pos, &mut vertices) {
+            vertices = vec![start_pos, end_pos];
+        }
+        vertices
+    }
+
+    fn format_preedit(&self, vertices: &[usize]) -> String {
+        let delimiters = self.options.delimiters();
+        let delimiter = delimiters.chars().next().unwrap_or(' ').to_string();
+        let mut preedit = vertices
+            .windows(2)
+            .filter_map(|span| self.input.get(span[0]..span[1]))
+            .map(|syllable| syllable.trim_end_matches(|c| delimiters.contains(c)))
+            .collect::<Vec<_>>()
+            .join(&delimiter);
+        self.options.format_preedit(&mut preedit);
+        preedit
+    }
+}
+
+// Walks the syllable graph along the code, recording the vertices passed.
+fn find_path(
+    code: &[SyllableId],
+    graph: &SyllableGraph,
+    current_pos: usize,
+    end_pos: usize,
+    vertices: &mut Vec<usize>,
+) -> bool {
+    let Some((&syllable_id, rest)) = code.split_first() else {
+        return current_pos == end_pos;
+    };
+    if current_pos == end_pos {
+        // predicted syllables beyond the input
+        return end_pos >= graph.interpreted_length();
+    }
+    let Some(spellings) = graph
+        .indices()
+        .get(&current_pos)
+        .and_then(|index| index.get(&syllable_id))
+    else {
+        return false;
+    };
+    for props in spellings {
+        if props.end_pos > end_pos {
+            continue;
+        }
+        vertices.push(props.end_pos);
+        if find_path(rest, graph, props.end_pos, end_pos, vertices) {
+            return true;
+        }
+        vertices.pop();
+    }
+    false
+}
+
+impl Translation for ScriptTranslation {
+    fn next(&mut self) -> Option<Arc<dyn Candidate>> {
+        let candidate = self.candidate.take()?;
+        if let Some(iter) = self
+            .phrase_end
+            .and_then(|end_pos| self.phrase.get_mut(&end_pos))
+        {
+            iter.next();
+        }
+        self.prepare_candidate();
+        Some(candidate)
+    }
+
+    fn peek(&self) -> Option<Arc<dyn Candidate>> {
+        self.candidate.clone()
+    }
+
+    fn exhausted(&self) -> bool {
+        self.candidate.is_none()
+    }
+}
+
+#[test]
+fn translate_with_compiled_dictionary() {
+    use std::fs;
+
+    use crate::rime::common::PathExt;
+    use crate::rime::dict::dict_compiler::DictCompiler;
+    use crate::rime::schema::Schema;
+
+    let data_dir = PathExt::new("script_translator_test");
+    fs::create_dir_all(&data_dir).unwrap();
+    fs::write(
+        data_dir.join("sample.dict.yaml"),
+        "---\nname: sample\nversion: \"1.0\"\n...\n\
+         你\tni\t100\n泥\tni\t50\n好\thao\t80\n你好\tni hao\t20\n",
+    )
+    .unwrap();
+    fs::write(
+        data_dir.join("sample.schema.yaml"),
+        "translator:\n  dictionary: sample\n  enable_user_dict: false\n",
+    )
+    .unwrap();
+    let compiler = DictCompiler::new(&data_dir, "sample", "sample");
+    assert!(compiler.compile(Some(&data_dir.join("sample.schema.yaml"))));
+
+    let schema = Schema::load(&data_dir, "sample").unwrap();
+    let context = Context::new();
+    let ticket = Ticket::new(&schema, &context, "translator", "script_translator");
+    let mut translator = ScriptTranslator::new(&ticket);
+    let mut segment = Segment::new(0, 5);
+    segment.tags.insert("abc".to_string());
+    let translation = translator.query("nihao", &segment, &context).unwrap();
+    let mut translation = translation.write().unwrap();
+    let mut candidates = Vec::new();
+    while let Some(candidate) = translation.next() {
+        candidates.push(candidate);
+    }
+    let texts: Vec<_> = candidates
+        .iter()
+        .map(|candidate| candidate.text())
+        .collect();
+    assert_eq!(vec!["你好", "你", "泥"], texts);
+    // caret stops at each syllable, also past the end of a shorter phrase
+    for candidate in &candidates {
+        let spans = candidate.spans();
+        assert_eq!(2, spans.count());
+        assert!(spans.has_vertex(2));
+        assert_eq!(5, spans.end());
+    }
+}
@@ -0,0 +1,323 @@
+use crate::rime::component::Ticket;
+use crate::rime::context::Context;
+use crate::rime::engine::Engine;
+use crate::rime::key_event::KeyEvent;
+use crate::rime::key_table::{
+    XK_0, XK_9, XK_DOWN, XK_END, XK_HOME, XK_KP_0, XK_KP_9, XK_KP_DOWN, XK_KP_END, XK_KP_HOME,
+    XK_KP_LEFT, XK_KP_NEXT, XK_KP_PRIOR, XK_KP_RIGHT, XK_KP_UP, XK_LEFT, XK_NEXT, XK_PRIOR,
+    XK_RIGHT, XK_UP,
+};
+use crate::rime::processor::{ProcessResult, Processor};
+
+enum Action {
+    PreviousCandidate,
+    NextCandidate,
+    PreviousPage,
+    NextPage,
+}
+
+pub(crate) struct Selector;
+
+impl Selector {
+    pub(crate) fn new(_ticket: &Ticket) -> Self {
+        Self
+    }
+
+    // Arrow keys move the highlight along the candidate list as it is laid out:
+    // stacked or linear, in horizontal or vertical text.
+    fn arrow_key_action(keycode: u32, vertical: bool, linear: bool) -> Option<Action> {
+        let (previous_candidate, next_candidate, previous_page, next_page) =
+            match (vertical, linear) {
+                (false, false) => (XK_UP, XK_DOWN, None, None),
+                (false, true) => (XK_LEFT, XK_RIGHT, Some(XK_UP), Some(XK_DOWN)),
+                (true, false) => (XK_RIGHT, XK_LEFT, None, None),
+                (true, true) => (XK_UP, XK_DOWN, Some(XK_RIGHT), Some(XK_LEFT)),
+            };
+        match keycode {
+            k if k == previous_candidate => Some(Action::PreviousCandidate),
+            k if k == next_candidate => Some(Action::NextCandidate),
+            k if Some(k) == previous_page => Some(Action::PreviousPage),
+            k if Some(k) == next_page => Some(Action::NextPage),
+            _ => None,
+        }
+    }
+
+    fn highlight(context: &mut Context, index: usize) -> bool {
+        if let Some(segment) = context.composition_mut().segments.last_mut() {
+            segment.tags.insert("paging".to_string());
+        }
+        context.highlight(index)
+    }
+
+    fn page_up(context: &mut Context, page_size: usize) -> bool {
+        let Some(segment) = context.composition().segments.last() else {
+            return false;
+        };
+        let index = segment.selected_index.saturating_sub(page_size);
+        Self::highlight(context, index)
+    }
+
+    fn page_down(context: &mut Context, page_size: usize) -> bool {
+        let Some(segment) = context.composition().segments.last() else {
+            return false;
+        };
+        let Some(menu) = segment.menu.clone() else {
+            return false;
+        };
+        let index = segment.selected_index + page_size;
+        let page_start = index / page_size * page_size;
+        let candidate_count = menu.write().unwrap().prepare(page_start + page_size);
+        if candidate_count <= page_start {
+            return false;
+        }
+        Self::highlight(context, index.min(candidate_count - 1))
+    }
+
+    fn cursor_up(context: &mut Context) -> bool {
+        match context.composition().segments.last() {
+            Some(segment) if segment.selected_index > 0 => {
+                let index = segment.selected_index - 1;
+                Self::highlight(context, index)
+            }
+            _ => false,
+        }
+    }
+
+    fn cursor_down(context: &mut Context) -> bool {
+        let Some(segment) = context.composition().segments.last() else {
+            return false;
+        };
+        let index = segment.selected_index + 1;
+        Self::highlight(context, index)
+    }
+
+    fn home(context: &mut Context, page_size: usize) -> bool {
+        let Some(segment) = context.composition().segments.last() else {
+            return false;
+        };
+        let page_start = segment.selected_index / page_size * page_size;
+        if segment.selected_index > page_start {
+            return context.highlight(page_start);
+        }
+        false
+    }
+
+    fn end(context: &mut Context, page_size: usize) -> bool {
+        if context.caret_pos() < context.input().len() {
+            // navigator should handle this
+            return false;
+        }
+        let Some(segment) = context.composition().segments.last() else {
+            return false;
+        };
+        let Some(menu) = segment.menu.clone() else {
+            return false;
+        };
+        let page_no = segment.selected_index / page_size;
+        let Some(page) = menu.write().unwrap().create_page(page_size, page_no) else {
+            return false;
+        };
+        if page.candidates.is_empty() {
+            return false;
+        }
+        context.highlight(page_no * page_size + page.candidates.len() - 1)
+    }
+
+    fn select_candidate_at(context: &mut Context, index: usize, page_size: usize) -> bool {
+        if index >= page_size {
+            return false;
+        }
+        let Some(segment) = context.composition().segments.last() else {
+            return false;
+        };
+        let page_start = segment.selected_index / page_size * page_size;
+        context.select(page_start + index)
+    }
+}
+
+impl Processor for Selector {
+    fn process_key_event(&mut self, key_event: &KeyEvent, engine: &mut Engine) -> ProcessResult {
+        if key_event.release() || key_event.alt() || key_event.super_() {
+            return ProcessResult::Noop;
+        }
+        let page_size = engine.schema().page_size();
+        let select_keys = engine.schema().select_keys().to_string();
+        let context = engine.context_mut();
+        match context.composition().segments.last() {
+            Some(segment) if segment.menu.is_some() && !segment.has_tag("raw") => {}
+            _ => return ProcessResult::Noop,
+        }
+        let keycode = match key_event.keycode() {
+            XK_KP_HOME => XK_HOME,
+            XK_KP_LEFT => XK_LEFT,
+            XK_KP_UP => XK_UP,
+            XK_KP_RIGHT => XK_RIGHT,
+            XK_KP_DOWN => XK_DOWN,
+            XK_KP_PRIOR => XK_PRIOR,
+            XK_KP_NEXT => XK_NEXT,
+            XK_KP_END => XK_END,
+            keycode => keycode,
+        };
+        match keycode {
+            XK_PRIOR => {
+                Self::page_up(context, page_size);
+                return ProcessResult::Accepted;
+            }
+            XK_NEXT => {
+                Self::page_down(context, page_size);
+                return ProcessResult::Accepted;
+            }
+            XK_HOME => {
+                return if Self::home(context, page_size) {
+                    ProcessResult::Accepted
+                } else {
+                    ProcessResult::Noop
+                };
+            }
+            XK_END => {
+                return if Self::end(context, page_size) {
+                    ProcessResult::Accepted
+                } else {
+                    ProcessResult::Noop
+                };
+            }
+            _ => {}
+        }
+        let vertical = context.get_option("_vertical");
+        let linear = context.get_option("_linear");
+        if let Some(action) = Self::arrow_key_action(keycode, vertical, linear) {
+            // Left and Right keys are shared with the navigator, which takes
+            // them unless the caret is at the end of input.
+            let horizontal_arrow = keycode == XK_LEFT || keycode == XK_RIGHT;
+            if horizontal_arrow
+                && (key_event.ctrl()
+                    || key_event.shift()
+                    || context.caret_pos() != context.input().len())
+            {
+                return ProcessResult::Noop;
+            }
+            let moved = match action {
+                Action::PreviousCandidate => Self::cursor_up(context),
+                Action::NextCandidate => Self::cursor_down(context),
+                Action::PreviousPage => Self::page_up(context, page_size),
+                Action::NextPage => Self::page_down(context, page_size),
+            };
+            return if moved || !horizontal_arrow {
+                ProcessResult::Accepted
+            } else {
+                ProcessResult::Noop
+            };
+        }
+        if key_event.ctrl() {
+            return ProcessResult::Noop;
+        }
+        let index = if !select_keys.is_empty() {
+            (0x20..0x7f)
+                .contains(&keycode)
+                .then(|| select_keys.find(keycode as u8 as char))
+                .flatten()
+        } else if (XK_0..=XK_9).contains(&keycode) {
+            Some(((keycode - XK_0) as usize + 9) % 10)
+        } else if (XK_KP_0..=XK_KP_9).contains(&keycode) {
+            Some(((keycode - XK_KP_0) as usize + 9) % 10)
+        } else {
+            None
+        };
+        match index {
+            Some(index) => {
+                Self::select_candidate_at(context, index, page_size);
+                ProcessResult::Accepted
+            }
+            // not handled
+            None => ProcessResult::Noop,
+        }
+    }
+}
+
+#[cfg(test)]
+fn engine_with_menu(yaml: &str, candidate_count: usize) -> Engine {
+    use std::sync::{Arc, RwLock};
+
+    use crate::rime::candidate::SimpleCandidate;
+    use crate::rime::engine::test_engine;
+    use crate::rime::menu::Menu;
+    use crate::rime::segmentation::{Segment, SegmentStatus};
+    use crate::rime::translation::FifoTranslation;
+
+    let (mut engine, _) = test_engine(yaml);
+    engine.context_mut().set_input("a".to_string());
+    engine.flush_context_events();
+    let mut translation = FifoTranslation::new();
+    for i in 0..candidate_count {
+        translation.append(Some(Arc::new(SimpleCandidate::new(
+            "simple".to_string(),
+            0,
+            1,
+            i.to_string(),
+            None,
+            None,
+        ))));
+    }
+    let mut menu = Menu::new();
+    menu.add_translation(Arc::new(RwLock::new(translation)));
+    let mut segment = Segment::new(0, 1);
+    segment.status = SegmentStatus::Guess;
+    segment.menu = Some(Arc::new(RwLock::new(menu)));
+    engine.context_mut().composition_mut().segments = vec![segment];
+    engine
+}
+
+#[cfg(test)]
+fn selected_index(engine: &Engine) -> usize {
+    engine.context().composition().segments[0].selected_index
+}
+
+#[test]
+fn page_and_select_by_digit() {
+    use crate::rime::segmentation::SegmentStatus;
+
+    let mut engine = engine_with_menu(
+        "menu:\n  page_size: 3\nengine:\n  processors: [selector]\n",
+        7,
+    );
+    assert!(engine.process_key(&KeyEvent::new(XK_NEXT, 0)));
+    assert!(engine.process_key(&KeyEvent::new(XK_NEXT, 0)));
+    assert_eq!(6, selected_index(&engine));
+    // no page past the last one
+    assert!(engine.process_key(&KeyEvent::new(XK_NEXT, 0)));
+    assert_eq!(6, selected_index(&engine));
+    assert!(engine.process_key(&KeyEvent::new(XK_PRIOR, 0)));
+    assert_eq!(3, selected_index(&engine));
+
+    // Home and End stay within the page
+    assert!(engine.process_key(&KeyEvent::new(XK_END, 0)));
+    assert_eq!(5, selected_index(&engine));
+    assert!(engine.process_key(&KeyEvent::new(XK_UP, 0)));
+    assert_eq!(4, selected_index(&engine));
+    assert!(engine.process_key(&KeyEvent::new(XK_HOME, 0)));
+    assert_eq!(3, selected_index(&engine));
+
+    // digits count from 1 on the current page
+    assert!(engine.process_key(&KeyEvent::new('2' as u32, 0)));
+    let segment = &engine.context().composition().segments[0];
+    assert_eq!(4, segment.selected_index);
+    assert_eq!(SegmentStatus::Confirmed, segment.status);
+}
+
+#[test]
+fn select_keys_and_linear_layout() {
+    let mut engine = engine_with_menu(
+        "menu:\n  page_size: 3\n  select_keys: asd\nengine:\n  processors: [selector]\n",
+        7,
+    );
+    engine.context_mut().set_option("_linear", true);
+    // in a linear menu, Right moves to the next candidate, Down to the next page
+    assert!(engine.process_key(&KeyEvent::new(XK_RIGHT, 0)));
+    assert_eq!(1, selected_index(&engine));
+    assert!(engine.process_key(&KeyEvent::new(XK_DOWN, 0)));
+    assert_eq!(4, selected_index(&engine));
+    // digits are left alone once select keys are given
+    assert!(!engine.process_key(&KeyEvent::new('1' as u32, 0)));
+    assert!(engine.process_key(&KeyEvent::new('d' as u32, 0)));
+    assert_eq!(5, selected_index(&engine));
+}
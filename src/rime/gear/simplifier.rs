@@ -0,0 +1,323 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::sync::{Arc, RwLock};
+
+use log::{error, info, warn};
+
+use crate::rime::candidate::{Candidate, CandidateList, ShadowCandidate};
+use crate::rime::common::PathExt;
+use crate::rime::component::Ticket;
+use crate::rime::context::Context;
+use crate::rime::filter::Filter;
+use crate::rime::segmentation::Segment;
+use crate::rime::translation::Translation;
+
+const DEFAULT_OPENCC_DICTS: [&str; 2] = ["TSPhrases.txt", "TSCharacters.txt"];
+
+#[derive(Default)]
+struct TrieNode {
+    children: HashMap<char, usize>,
+    value: Option<String>,
+}
+
+// Conversion table of OpenCC text dictionaries, keyed by characters.
+struct ConversionTrie {
+    nodes: Vec<TrieNode>,
+}
+
+impl ConversionTrie {
+    fn new() -> Self {
+        Self {
+            nodes: vec![TrieNode::default()],
+        }
+    }
+
+    // Keeps the value inserted first.
+    fn insert(&mut self, key: &str, value: &str) {
+        let mut node = 0;
+        for ch in key.chars() {
+            node = match self.nodes[node].children.get(&ch) {
+                Some(&child) => child,
+                None => {
+                    self.nodes.push(TrieNode::default());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children.insert(ch, child);
+                    child
+                }
+            };
+        }
+        self.nodes[node]
+            .value
+            .get_or_insert_with(|| value.to_string());
+    }
+
+    // Each line has a key, a tab and the candidate values separated by
+    // spaces, of which the first is taken.
+    fn load(&mut self, file_path: &PathExt) -> bool {
+        let contents = match fs::read_to_string(file_path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                error!("error opening opencc dict '{}': {}", file_path, e);
+                return false;
+            }
+        };
+        for line in contents.lines() {
+            let Some((key, values)) = line.split_once('\t') else {
+                continue;
+            };
+            if let Some(value) = values.split_whitespace().next() {
+                self.insert(key, value);
+            }
+        }
+        true
+    }
+
+    // Returns the byte length and value of the longest key the text starts
+    // with.
+    fn longest_prefix(&self, text: &str) -> Option<(usize, &str)> {
+        let mut node = 0;
+        let mut found = None;
+        for (pos, ch) in text.char_indices() {
+            let Some(&child) = self.nodes[node].children.get(&ch) else {
+                break;
+            };
+            node = child;
+            if let Some(value) = &self.nodes[node].value {
+                found = Some((pos + ch.len_utf8(), value.as_str()));
+            }
+        }
+        found
+    }
+
+    // Converts by forward maximum matching, so that phrases take precedence
+    // over the characters in them.
+    fn convert(&self, text: &str) -> String {
+        let mut converted = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(ch) = rest.chars().next() {
+            match self.longest_prefix(rest) {
+                Some((length, value)) => {
+                    converted.push_str(value);
+                    rest = &rest[length..];
+                }
+                None => {
+                    converted.push(ch);
+                    rest = &rest[ch.len_utf8()..];
+                }
+            }
+        }
+        converted
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TipsLevel {
+    None,
+    Char,
+    All,
+}
+
+struct Converter {
+    trie: ConversionTrie,
+    tips_level: TipsLevel,
+}
+
+impl Converter {
+    fn shadow(&self, candidate: Arc<dyn Candidate>) -> Arc<dyn Candidate> {
+        let original = candidate.text();
+        let converted = self.trie.convert(original);
+        if converted == original {
+            return candidate;
+        }
+        let show_tips = match self.tips_level {
+            TipsLevel::None => false,
+            TipsLevel::Char => original.chars().count() == 1,
+            TipsLevel::All => true,
+        };
+        let comment = show_tips.then(|| format!("〔{}〕", original));
+        Arc::new(ShadowCandidate::new(
+            candidate.clone(),
+            candidate.type_().to_string(),
+            Some(converted),
+            comment,
+            None,
+        ))
+    }
+}
+
+// Converts the text of candidates with OpenCC dictionaries, traditional to
+// simplified Chinese by default, while the option is on.
+pub(crate) struct Simplifier {
+    option_name: String,
+    tags: HashSet<String>,
+    converter: Option<Arc<Converter>>,
+}
+
+impl Simplifier {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        if ticket.name_space == "filter" {
+            let ticket = Ticket::new(ticket.schema, ticket.context, "simplifier", &ticket.klass);
+            return Self::new(&ticket);
+        }
+        let config = ticket.schema.config();
+        let name_space = &ticket.name_space;
+        let mut option_name = config.get_string(&format!("{}/option_name", name_space));
+        if option_name.is_empty() {
+            option_name = "simplification".to_string();
+        }
+        let mut tags = HashSet::new();
+        if let Some(list) = config.get_list(&format!("{}/tags", name_space)) {
+            for i in 0..list.size() {
+                if let Some(tag) = list.get_str_at(i) {
+                    tags.insert(tag.to_string());
+                }
+            }
+        }
+        if tags.is_empty() {
+            tags.insert("abc".to_string());
+        }
+        let tips_level = match config.get_string(&format!("{}/tips", name_space)).as_str() {
+            "none" => TipsLevel::None,
+            "char" => TipsLevel::Char,
+            _ => TipsLevel::All,
+        };
+        // dictionaries listed first take precedence
+        let dict_names = match config.get_list(&format!("{}/opencc_dicts", name_space)) {
+            Some(list) => (0..list.size())
+                .filter_map(|i| list.get_str_at(i))
+                .map(str::to_string)
+                .collect(),
+            None => DEFAULT_OPENCC_DICTS.iter().map(|s| s.to_string()).collect(),
+        };
+        let converter = ticket.schema.data_dir().and_then(|data_dir| {
+            let opencc_dir = data_dir.join("opencc");
+            let mut trie = ConversionTrie::new();
+            let mut loaded = false;
+            for dict_name in &dict_names {
+                let file_path = opencc_dir.join(dict_name);
+                info!("loading opencc dict: {}", file_path);
+                loaded |= trie.load(&file_path);
+            }
+            if !loaded {
+                warn!("no opencc dict available for {}.", name_space);
+                return None;
+            }
+            Some(Arc::new(Converter { trie, tips_level }))
+        });
+        Self {
+            option_name,
+            tags,
+            converter,
+        }
+    }
+}
+
+impl Filter for Simplifier {
+    fn apply(
+        &self,
+        translation: Arc<RwLock<dyn Translation>>,
+        _candidates: Arc<RwLock<CandidateList>>,
+        context: &Context,
+    ) -> Arc<RwLock<dyn Translation>> {
+        let Some(converter) = &self.converter else {
+            return translation;
+        };
+        if !context.get_option(&self.option_name) {
+            return translation;
+        }
+        Arc::new(RwLock::new(SimplifiedTranslation::new(
+            translation,
+            converter.clone(),
+        )))
+    }
+
+    fn applies_to_segment(&self, segment: &Segment) -> bool {
+        segment.has_any_tag_in(&self.tags)
+    }
+}
+
+struct SimplifiedTranslation {
+    translation: Arc<RwLock<dyn Translation>>,
+    converter: Arc<Converter>,
+    candidate: Option<Arc<dyn Candidate>>,
+}
+
+impl SimplifiedTranslation {
+    fn new(translation: Arc<RwLock<dyn Translation>>, converter: Arc<Converter>) -> Self {
+        let mut simplified = Self {
+            translation,
+            converter,
+            candidate: None,
+        };
+        simplified.prepare_candidate();
+        simplified
+    }
+
+    fn prepare_candidate(&mut self) {
+        self.candidate = self
+            .translation
+            .read()
+            .ok()
+            .and_then(|translation| translation.peek())
+            .map(|candidate| self.converter.shadow(candidate));
+    }
+}
+
+impl Translation for SimplifiedTranslation {
+    fn next(&mut self) -> Option<Arc<dyn Candidate>> {
+        let candidate = self.candidate.take()?;
+        if let Ok(mut translation) = self.translation.write() {
+            translation.next();
+        }
+        self.prepare_candidate();
+        Some(candidate)
+    }
+
+    fn peek(&self) -> Option<Arc<dyn Candidate>> {
+        self.candidate.clone()
+    }
+
+    fn exhausted(&self) -> bool {
+        self.candidate.is_none()
+    }
+}
+
+#[test]
+fn convert_by_forward_maximum_matching() {
+    use crate::rime::candidate::SimpleCandidate;
+
+    let mut trie = ConversionTrie::new();
+    // phrases
+    trie.insert("頭髮", "头发");
+    trie.insert("乾隆", "乾隆");
+    // characters
+    trie.insert("頭", "头");
+    trie.insert("髮", "发");
+    trie.insert("乾", "干");
+    trie.insert("乾", "乾");
+    assert_eq!("头发干了", trie.convert("頭髮乾了"));
+    assert_eq!("乾隆头", trie.convert("乾隆頭"));
+    assert_eq!("乾隆干", trie.convert("乾隆乾"));
+
+    let converter = Converter {
+        trie,
+        tips_level: TipsLevel::Char,
+    };
+    let candidate = |text: &str| -> Arc<dyn Candidate> {
+        Arc::new(SimpleCandidate::new(
+            "table".to_string(),
+            0,
+            2,
+            text.to_string(),
+            None,
+            None,
+        ))
+    };
+    let shadow = converter.shadow(candidate("乾"));
+    assert_eq!("干", shadow.text());
+    assert_eq!("〔乾〕", shadow.comment());
+    assert_eq!("", converter.shadow(candidate("頭髮")).comment());
+    let unchanged = candidate("了");
+    let shadow = converter.shadow(unchanged.clone());
+    assert!(Arc::ptr_eq(&unchanged, &shadow));
+}
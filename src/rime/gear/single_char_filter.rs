@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+
+use crate::rime::candidate::{BaseCandidate, Candidate, CandidateList};
+use crate::rime::component::Ticket;
+use crate::rime::context::Context;
+use crate::rime::filter::Filter;
+use crate::rime::gear::translator_commons::Phrase;
+use crate::rime::translation::Translation;
+
+// Puts single characters ahead of longer phrases while the "single_char"
+// option is on.
+pub(crate) struct SingleCharFilter;
+
+impl SingleCharFilter {
+    pub(crate) fn new(_ticket: &Ticket) -> Self {
+        Self
+    }
+}
+
+impl Filter for SingleCharFilter {
+    fn apply(
+        &self,
+        translation: Arc<RwLock<dyn Translation>>,
+        _candidates: Arc<RwLock<CandidateList>>,
+        context: &Context,
+    ) -> Arc<RwLock<dyn Translation>> {
+        if !context.get_option("single_char") {
+            return translation;
+        }
+        Arc::new(RwLock::new(SingleCharFirstTranslation::new(translation)))
+    }
+}
+
+// Only the leading table entries are rearranged, leaving the order of
+// completions, sentences and the rest intact.
+fn is_table_entry(candidate: &Arc<dyn Candidate>) -> bool {
+    let genuine = BaseCandidate::get_genuine_candidate(candidate);
+    genuine.as_any().downcast_ref::<Phrase>().is_some()
+        && matches!(genuine.type_(), "table" | "user_table")
+}
+
+struct SingleCharFirstTranslation {
+    translation: Arc<RwLock<dyn Translation>>,
+    cache: VecDeque<Arc<dyn Candidate>>,
+}
+
+impl SingleCharFirstTranslation {
+    fn new(translation: Arc<RwLock<dyn Translation>>) -> Self {
+        let mut rearranged = Self {
+            translation,
+            cache: VecDeque::new(),
+        };
+        rearranged.rearrange();
+        rearranged
+    }
+
+    fn rearrange(&mut self) {
+        let Ok(mut translation) = self.translation.write() else {
+            return;
+        };
+        let mut phrases = Vec::new();
+        while let Some(candidate) = translation.peek() {
+            if !is_table_entry(&candidate) {
+                break;
+            }
+            if candidate.text().chars().count() == 1 {
+                self.cache.push_back(candidate);
+            } else {
+                phrases.push(candidate);
+            }
+            translation.next();
+        }
+        self.cache.extend(phrases);
+    }
+}
+
+impl Translation for SingleCharFirstTranslation {
+    fn next(&mut self) -> Option<Arc<dyn Candidate>> {
+        if let Some(candidate) = self.cache.pop_front() {
+            return Some(candidate);
+        }
+        self.translation.write().ok()?.next()
+    }
+
+    fn peek(&self) -> Option<Arc<dyn Candidate>> {
+        if let Some(candidate) = self.cache.front() {
+            return Some(candidate.clone());
+        }
+        self.translation.read().ok()?.peek()
+    }
+
+    fn exhausted(&self) -> bool {
+        self.cache.is_empty()
+            && self
+                .translation
+                .read()
+                .map_or(true, |translation| translation.exhausted())
+    }
+}
+
+#[test]
+fn single_characters_first() {
+    use crate::rime::candidate::SimpleCandidate;
+    use crate::rime::dict::vocabulary::DictEntry;
+    use crate::rime::menu::Menu;
+    use crate::rime::translation::FifoTranslation;
+
+    let phrase = |type_: &str, text: &str| {
+        let entry = Arc::new(DictEntry {
+            text: text.to_string(),
+            ..Default::default()
+        });
+        Some(Arc::new(Phrase::new(None, type_, 0, 2, entry)) as Arc<dyn Candidate>)
+    };
+    let translation = || {
+        let mut translation = FifoTranslation::new();
+        translation.append(phrase("table", "你好"));
+        translation.append(phrase("table", "你"));
+        translation.append(phrase("user_table", "泥"));
+        translation.append(phrase("table", "拟好"));
+        translation.append(Some(Arc::new(SimpleCandidate::new(
+            "sentence".to_string(),
+            0,
+            2,
+            "你号".to_string(),
+            None,
+            None,
+        ))));
+        // not moved ahead of the sentence
+        translation.append(phrase("table", "尼"));
+        Arc::new(RwLock::new(translation))
+    };
+    let texts = |context: &Context| {
+        let mut menu = Menu::new();
+        menu.add_translation(translation());
+        menu.add_filter(&SingleCharFilter, context);
+        menu.prepare(6);
+        (0..6)
+            .filter_map(|i| menu.get_candidate_at(i))
+            .map(|candidate| candidate.text().to_string())
+            .collect::<Vec<_>>()
+    };
+    let mut context = Context::new();
+    assert_eq!(
+        vec!["你好", "你", "泥", "拟好", "你号", "尼"],
+        texts(&context)
+    );
+    context.set_option("single_char", true);
+    assert_eq!(
+        vec!["你", "泥", "你好", "拟好", "你号", "尼"],
+        texts(&context)
+    );
+}
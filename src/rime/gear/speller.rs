@@ -0,0 +1,228 @@
+use std::sync::Arc;
+
+use log::info;
+
+use crate::rime::candidate::{BaseCandidate, Candidate, SimpleCandidate};
+use crate::rime::component::Ticket;
+use crate::rime::context::Context;
+use crate::rime::engine::Engine;
+use crate::rime::key_event::KeyEvent;
+use crate::rime::key_table::XK_SPACE;
+use crate::rime::processor::{ProcessResult, Processor};
+use crate::rime::segmentation::Segment;
+
+pub(crate) const RIME_ALPHABET: &str = "zyxwvutsrqponmlkjihgfedcba";
+
+pub(crate) struct Speller {
+    alphabet: String,
+    delimiters: String,
+    initials: String,
+    finals: String,
+    max_code_length: usize,
+    auto_select: bool,
+    use_space: bool,
+}
+
+impl Speller {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        let config = ticket.schema.config();
+        let mut alphabet = config.get_string("speller/alphabet");
+        if alphabet.is_empty() {
+            alphabet = RIME_ALPHABET.to_string();
+        }
+        let mut initials = config.get_string("speller/initials");
+        if initials.is_empty() {
+            initials = alphabet.clone();
+        }
+        Self {
+            alphabet,
+            delimiters: config.get_string("speller/delimiter"),
+            initials,
+            finals: config.get_string("speller/finals"),
+            max_code_length: config.get_int("speller/max_code_length").max(0) as usize,
+            auto_select: config.get_bool("speller/auto_select"),
+            use_space: config.get_bool("speller/use_space"),
+        }
+    }
+
+    fn expecting_an_initial(&self, context: &Context) -> bool {
+        let caret_pos = context.caret_pos();
+        if caret_pos == 0 || caret_pos == context.composition().get_current_start_position() {
+            return true;
+        }
+        let previous_char = context.input().as_bytes()[caret_pos - 1] as char;
+        self.finals.contains(previous_char) || !self.alphabet.contains(previous_char)
+    }
+
+    fn is_auto_selectable(&self, cand: &Arc<dyn Candidate>, input: &str) -> bool {
+        // reaches end of input
+        cand.end() == input.len()
+            && (is_table_entry(cand) || is_simple_candidate(cand))
+            // no delimiters
+            && !input[cand.start()..].contains(|c: char| self.delimiters.contains(c))
+    }
+
+    // Handles input beyond max_code_length when auto_select is false.
+    fn auto_select_at_max_code_length(&self, context: &mut Context) -> bool {
+        if self.max_code_length == 0 || !context.has_menu() {
+            return false;
+        }
+        let Some(cand) = context.get_selected_candidate() else {
+            return false;
+        };
+        if !is_table_entry(&cand)
+            || cand.end() - cand.start() < self.max_code_length
+            || !self.is_auto_selectable(&cand, context.input())
+        {
+            return false;
+        }
+        context.confirm_current_selection()
+    }
+
+    fn auto_select_unique_candidate(&self, context: &mut Context) -> bool {
+        if !self.auto_select || !context.has_menu() {
+            return false;
+        }
+        let Some(segment) = context.composition().segments.last() else {
+            return false;
+        };
+        let unique_candidate = segment
+            .menu
+            .as_ref()
+            .is_some_and(|menu| menu.write().unwrap().prepare(2) == 1);
+        if !unique_candidate {
+            return false;
+        }
+        let Some(cand) = segment.get_selected_candidate() else {
+            return false;
+        };
+        let matches_input_pattern = self.max_code_length == 0 // match any length if not set
+            || cand.end() - cand.start() >= self.max_code_length;
+        if matches_input_pattern && self.is_auto_selectable(&cand, context.input()) {
+            return context.confirm_current_selection();
+        }
+        false
+    }
+
+    fn auto_select_previous_match(
+        &self,
+        context: &mut Context,
+        previous_segment: Option<Segment>,
+    ) -> bool {
+        if !self.auto_select || self.max_code_length > 0 {
+            return false;
+        }
+        // if and only if current conversion fails
+        if context.has_menu() {
+            return false;
+        }
+        let Some(previous_segment) = previous_segment else {
+            return false;
+        };
+        let Some(cand) = previous_segment.get_selected_candidate() else {
+            return false;
+        };
+        let input = context.input().to_string();
+        let end = previous_segment.end;
+        if !self.is_auto_selectable(&cand, &input[..end]) {
+            return false;
+        }
+        // reuse previous match
+        let segments = &mut context.composition_mut().segments;
+        segments.pop();
+        segments.push(previous_segment);
+        context.confirm_current_selection();
+        if context.get_option("_auto_commit") {
+            context.set_input(input[..end].to_string());
+            context.commit();
+            context.set_input(input[end..].to_string());
+        }
+        true
+    }
+}
+
+impl Processor for Speller {
+    fn process_key_event(&mut self, key_event: &KeyEvent, engine: &mut Engine) -> ProcessResult {
+        if key_event.release() || key_event.ctrl() || key_event.alt() || key_event.super_() {
+            return ProcessResult::Noop;
+        }
+        let ch = key_event.keycode();
+        if ch == XK_SPACE && (!self.use_space || key_event.shift()) {
+            return ProcessResult::Noop;
+        }
+        // not a valid key for spelling
+        if !(0x20..0x7f).contains(&ch) {
+            return ProcessResult::Noop;
+        }
+        let ch = ch as u8 as char;
+        if !self.alphabet.contains(ch) && !self.delimiters.contains(ch) {
+            return ProcessResult::Noop;
+        }
+        let is_initial = self.initials.contains(ch);
+        if !is_initial && self.expecting_an_initial(engine.context()) {
+            return ProcessResult::Noop;
+        }
+        if is_initial && self.auto_select_at_max_code_length(engine.context_mut()) {
+            info!("auto-select at max code length.");
+            engine.flush_context_events();
+        }
+        // make a backup of the previous conversion before modifying input
+        let previous_segment = if self.auto_select && engine.context().has_menu() {
+            engine.context().composition().segments.last().cloned()
+        } else {
+            None
+        };
+        info!("add to input: '{}', {}", ch, key_event);
+        engine.context_mut().push_input(ch);
+        engine.flush_context_events();
+        // so that next BackSpace won't revert previous selection
+        engine.context_mut().confirm_previous_selection();
+        if self.auto_select_previous_match(engine.context_mut(), previous_segment) {
+            info!("auto-select previous match.");
+            return ProcessResult::Accepted;
+        }
+        if self.auto_select_unique_candidate(engine.context_mut()) {
+            info!("auto-select unique candidate.");
+        }
+        ProcessResult::Accepted
+    }
+}
+
+fn is_table_entry(cand: &Arc<dyn Candidate>) -> bool {
+    let type_ = BaseCandidate::get_genuine_candidate(cand).type_();
+    type_ == "table" || type_ == "user_table"
+}
+
+fn is_simple_candidate(cand: &Arc<dyn Candidate>) -> bool {
+    BaseCandidate::get_genuine_candidate(cand)
+        .as_any()
+        .downcast_ref::<SimpleCandidate>()
+        .is_some()
+}
+
+#[test]
+fn auto_select_at_max_code_length() {
+    use crate::rime::engine::test_engine;
+    use crate::rime::segmentation::SegmentStatus;
+
+    let (mut engine, _) = test_engine(
+        "speller:\n  alphabet: abc'\n  initials: ab\n  delimiter: \"'\"\n  \
+         max_code_length: 2\n  auto_select: true\n\
+         engine:\n  processors: [speller]\n  segmentors: [abc_segmentor]\n  \
+         translators: [echo_translator]\n",
+    );
+    // neither a non-initial nor a delimiter starts the input
+    assert!(!engine.process_key(&KeyEvent::new('c' as u32, 0)));
+    assert!(!engine.process_key(&KeyEvent::new('\'' as u32, 0)));
+    assert!(!engine.process_key(&KeyEvent::new('d' as u32, 0)));
+    assert!(engine.process_key(&KeyEvent::new('a' as u32, 0)));
+    let segment = &engine.context().composition().segments[0];
+    assert_eq!(SegmentStatus::Guess, segment.status);
+
+    // the unique candidate is selected once the code is long enough
+    assert!(engine.process_key(&KeyEvent::new('c' as u32, 0)));
+    assert_eq!("ac", engine.context().input());
+    let segment = &engine.context().composition().segments[0];
+    assert_eq!(SegmentStatus::Confirmed, segment.status);
+    assert_eq!(2, segment.end);
+}
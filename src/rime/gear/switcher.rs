@@ -0,0 +1,434 @@
+use std::sync::{Arc, RwLock};
+
+use log::{info, warn};
+
+use crate::rime::candidate::{Candidate, SimpleCandidate};
+use crate::rime::component::Ticket;
+use crate::rime::config::config_component::Config;
+use crate::rime::config::config_types::{ConfigList, ConfigMap};
+use crate::rime::context::Context;
+use crate::rime::engine::Engine;
+use crate::rime::key_event::KeyEvent;
+use crate::rime::key_table::{
+    XK_DOWN, XK_ESCAPE, XK_KP_DOWN, XK_KP_UP, XK_RETURN, XK_SPACE, XK_UP,
+};
+use crate::rime::menu::Menu;
+use crate::rime::processor::{ProcessResult, Processor};
+use crate::rime::schema::Schema;
+use crate::rime::segmentation::{Segment, SegmentStatus};
+use crate::rime::translation::FifoTranslation;
+
+const DEFAULT_HOTKEYS: [&str; 2] = ["Control+grave", "F4"];
+const DEFAULT_SELECT_KEYS: &str = "1234567890";
+const DEFAULT_CAPTION: &str = "〔方案選單〕";
+
+// A switch of the schema: either a single option turned on and off, or a
+// group of options of which exactly one is on.
+#[derive(Clone)]
+struct Switch {
+    options: Vec<String>,
+    states: Vec<String>,
+    reset: Option<usize>,
+}
+
+impl Switch {
+    // Switches without states are hidden from the menu, but still reset.
+    fn load_all(config: &Config) -> Vec<Switch> {
+        let mut switches = Vec::new();
+        let Some(list) = config.get_list("switches") else {
+            return switches;
+        };
+        for i in 0..list.size() {
+            let Some(item) = list.get_at(i) else {
+                continue;
+            };
+            let Some(map) = item.as_any().downcast_ref::<ConfigMap>() else {
+                continue;
+            };
+            let options = if let Some(name) = map.get_str("name") {
+                vec![name.to_string()]
+            } else if let Some(options) = map.get_list("options") {
+                list_strings(options)
+            } else {
+                warn!("invalid switch #{}.", i);
+                continue;
+            };
+            if options.is_empty() {
+                continue;
+            }
+            let states = map.get_list("states").map(list_strings).unwrap_or_default();
+            let reset = map.get_int("reset").map(|reset| reset as usize);
+            switches.push(Switch {
+                options,
+                states,
+                reset,
+            });
+        }
+        switches
+    }
+
+    fn is_toggle(&self) -> bool {
+        self.options.len() == 1
+    }
+
+    fn state_count(&self) -> usize {
+        if self.is_toggle() {
+            2
+        } else {
+            self.options.len()
+        }
+    }
+
+    fn current_state(&self, context: &Context) -> usize {
+        if self.is_toggle() {
+            context.get_option(&self.options[0]) as usize
+        } else {
+            self.options
+                .iter()
+                .position(|option| context.get_option(option))
+                .unwrap_or(0)
+        }
+    }
+
+    fn apply_state(&self, state: usize, context: &mut Context) {
+        if self.is_toggle() {
+            context.set_option(&self.options[0], state != 0);
+        } else {
+            for (i, option) in self.options.iter().enumerate() {
+                context.set_option(option, i == state);
+            }
+        }
+    }
+
+    fn option_key(schema_id: &str, option: &str) -> String {
+        format!("var/option/{}/{}", schema_id, option)
+    }
+
+    fn saved_state(&self, user_config: &Config, schema_id: &str) -> Option<usize> {
+        if self.is_toggle() {
+            let key = Self::option_key(schema_id, &self.options[0]);
+            user_config
+                .contains(&key)
+                .then(|| user_config.get_bool(&key) as usize)
+        } else {
+            self.options
+                .iter()
+                .position(|option| user_config.get_bool(&Self::option_key(schema_id, option)))
+        }
+    }
+
+    fn save_state(&self, user_config: &Config, schema_id: &str, context: &Context) {
+        for option in &self.options {
+            user_config.set_bool(
+                &Self::option_key(schema_id, option),
+                context.get_option(option),
+            );
+        }
+    }
+}
+
+fn list_strings(list: &ConfigList) -> Vec<String> {
+    (0..list.size())
+        .filter_map(|i| list.get_str_at(i))
+        .map(str::to_string)
+        .collect()
+}
+
+#[derive(Clone)]
+enum SwitcherItem {
+    Schema(String),
+    Switch(Switch),
+}
+
+// A menu of the schemas in "schema_list" and the switches of the current
+// schema, opened by a hotkey. It should come first among the processors,
+// since it takes over all keys while the menu is open. Option states are
+// remembered per schema in "<data_dir>/user.yaml", under
+// "var/option/<schema_id>", and restored when the schema is loaded.
+pub(crate) struct Switcher {
+    hotkeys: Vec<KeyEvent>,
+    schema_list: Vec<String>,
+    caption: String,
+    user_config: Option<Config>,
+    active: bool,
+    items: Vec<SwitcherItem>,
+    highlighted: usize,
+}
+
+impl Switcher {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        // settings shared by all schemas go in default.yaml
+        let mut default_config = Config::new();
+        let has_default_config = ticket
+            .schema
+            .data_dir()
+            .is_some_and(|data_dir| default_config.load_from_file(&data_dir.join("default.yaml")));
+        let config = if has_default_config {
+            &default_config
+        } else {
+            ticket.schema.config()
+        };
+        let mut hotkeys = Vec::new();
+        let hotkey_names = config
+            .get_list("switcher/hotkeys")
+            .map(|list| list_strings(&list))
+            .unwrap_or_else(|| DEFAULT_HOTKEYS.iter().map(|s| s.to_string()).collect());
+        for name in hotkey_names {
+            let mut key_event = KeyEvent::default();
+            if key_event.parse(&name) {
+                hotkeys.push(key_event);
+            } else {
+                warn!("invalid switcher hotkey: {}", name);
+            }
+        }
+        let mut schema_list = Vec::new();
+        if let Some(list) = config.get_list("schema_list") {
+            for i in 0..list.size() {
+                let Some(item) = list.get_at(i) else {
+                    continue;
+                };
+                if let Some(schema_id) = item
+                    .as_any()
+                    .downcast_ref::<ConfigMap>()
+                    .and_then(|map| map.get_str("schema"))
+                {
+                    schema_list.push(schema_id.to_string());
+                }
+            }
+        }
+        let mut caption = config.get_string("switcher/caption");
+        if caption.is_empty() {
+            caption = DEFAULT_CAPTION.to_string();
+        }
+        let user_config = ticket.schema.data_dir().map(|data_dir| {
+            let mut user_config = Config::new();
+            // a missing file is created on save
+            user_config.load_from_file(&data_dir.join("user.yaml"));
+            user_config
+        });
+        Self {
+            hotkeys,
+            schema_list,
+            caption,
+            user_config,
+            active: false,
+            items: Vec::new(),
+            highlighted: 0,
+        }
+    }
+
+    // The current schema comes first, followed by its switches and then the
+    // other schemas.
+    fn load_items(&mut self, schema: &Schema) {
+        self.items.clear();
+        self.items
+            .push(SwitcherItem::Schema(schema.schema_id().to_string()));
+        self.items.extend(
+            Switch::load_all(schema.config())
+                .into_iter()
+                .filter(|switch| switch.states.len() >= switch.state_count())
+                .map(SwitcherItem::Switch),
+        );
+        self.items.extend(
+            self.schema_list
+                .iter()
+                .filter(|&schema_id| schema_id != schema.schema_id())
+                .map(|schema_id| SwitcherItem::Schema(schema_id.clone())),
+        );
+    }
+
+    fn item_candidate(item: &SwitcherItem, engine: &Engine) -> Arc<dyn Candidate> {
+        let (type_, text) = match item {
+            SwitcherItem::Schema(schema_id) => {
+                let schema = engine.schema();
+                let name = if schema_id == schema.schema_id() {
+                    Some(schema.schema_name().to_string())
+                } else {
+                    schema
+                        .data_dir()
+                        .and_then(|data_dir| Schema::load(&data_dir, schema_id))
+                        .map(|schema| schema.schema_name().to_string())
+                };
+                ("schema", name.unwrap_or_else(|| schema_id.clone()))
+            }
+            SwitcherItem::Switch(switch) => {
+                let state = switch.current_state(engine.context());
+                let next_state = (state + 1) % switch.state_count();
+                (
+                    "switch",
+                    format!("{} → {}", switch.states[state], switch.states[next_state]),
+                )
+            }
+        };
+        Arc::new(SimpleCandidate::new(
+            type_.to_string(),
+            0,
+            0,
+            text,
+            None,
+            None,
+        ))
+    }
+
+    fn activate(&mut self, engine: &mut Engine) {
+        info!("switcher activated.");
+        // the menu replaces whatever is being composed
+        engine.context_mut().clear();
+        engine.flush_context_events();
+        self.load_items(engine.schema());
+        self.highlighted = 0;
+        self.active = true;
+
+        let mut translation = FifoTranslation::new();
+        for item in &self.items {
+            translation.append(Some(Self::item_candidate(item, engine)));
+        }
+        let mut menu = Menu::new();
+        menu.add_translation(Arc::new(RwLock::new(translation)));
+        let mut segment = Segment::new(0, 0);
+        segment.status = SegmentStatus::Guess;
+        segment.tags.insert("switcher".to_string());
+        segment.prompt = self.caption.clone();
+        segment.menu = Some(Arc::new(RwLock::new(menu)));
+        let composition = engine.context_mut().composition_mut();
+        composition.segments.clear();
+        composition.segments.push(segment);
+    }
+
+    fn deactivate(&mut self, engine: &mut Engine) {
+        info!("switcher deactivated.");
+        self.active = false;
+        self.items.clear();
+        engine.context_mut().clear();
+    }
+
+    fn highlight(&mut self, index: usize, engine: &mut Engine) {
+        if index >= self.items.len() {
+            return;
+        }
+        self.highlighted = index;
+        if let Some(segment) = engine.context_mut().composition_mut().segments.last_mut() {
+            segment.selected_index = index;
+        }
+    }
+
+    fn select(&mut self, index: usize, engine: &mut Engine) {
+        let Some(item) = self.items.get(index).cloned() else {
+            return;
+        };
+        self.deactivate(engine);
+        match item {
+            SwitcherItem::Schema(schema_id) => {
+                if schema_id != engine.schema().schema_id() {
+                    engine.select_schema(&schema_id);
+                }
+            }
+            SwitcherItem::Switch(switch) => {
+                let state = switch.current_state(engine.context());
+                let next_state = (state + 1) % switch.state_count();
+                switch.apply_state(next_state, engine.context_mut());
+                if let Some(user_config) = &self.user_config {
+                    let schema_id = engine.schema().schema_id();
+                    switch.save_state(user_config, schema_id, engine.context());
+                    user_config.save();
+                }
+            }
+        }
+    }
+
+    fn select_key_index(key_event: &KeyEvent, engine: &Engine) -> Option<usize> {
+        if key_event.modifier() != 0 {
+            return None;
+        }
+        let ch = char::from_u32(key_event.keycode())?;
+        let select_keys = match engine.schema().select_keys() {
+            "" => DEFAULT_SELECT_KEYS,
+            select_keys => select_keys,
+        };
+        select_keys.chars().position(|key| key == ch)
+    }
+}
+
+impl Processor for Switcher {
+    fn process_key_event(&mut self, key_event: &KeyEvent, engine: &mut Engine) -> ProcessResult {
+        if !self.active {
+            if key_event.release() || !self.hotkeys.contains(key_event) {
+                return ProcessResult::Noop;
+            }
+            self.activate(engine);
+            return ProcessResult::Accepted;
+        }
+        if key_event.release() {
+            return ProcessResult::Accepted;
+        }
+        if key_event.keycode() == XK_ESCAPE || self.hotkeys.contains(key_event) {
+            self.deactivate(engine);
+            return ProcessResult::Accepted;
+        }
+        match key_event.keycode() {
+            XK_UP | XK_KP_UP => {
+                if self.highlighted > 0 {
+                    self.highlight(self.highlighted - 1, engine);
+                }
+            }
+            XK_DOWN | XK_KP_DOWN => self.highlight(self.highlighted + 1, engine),
+            XK_RETURN | XK_SPACE => self.select(self.highlighted, engine),
+            _ => {
+                if let Some(index) = Self::select_key_index(key_event, engine) {
+                    let page_size = engine.schema().page_size();
+                    let page_start = self.highlighted / page_size * page_size;
+                    self.select(page_start + index, engine);
+                }
+            }
+        }
+        ProcessResult::Accepted
+    }
+
+    // Sets the switches of the schema to the states saved last time, or else
+    // to their reset states.
+    fn on_schema_loaded(&mut self, engine: &mut Engine) {
+        let schema_id = engine.schema().schema_id().to_string();
+        for switch in Switch::load_all(engine.schema().config()) {
+            let saved = self
+                .user_config
+                .as_ref()
+                .and_then(|user_config| switch.saved_state(user_config, &schema_id));
+            if let Some(state) = saved.or(switch.reset) {
+                switch.apply_state(state.min(switch.state_count() - 1), engine.context_mut());
+            }
+        }
+    }
+}
+
+#[test]
+fn save_and_restore_options() {
+    use std::fs;
+
+    use crate::rime::common::PathExt;
+
+    let data_dir = PathExt::new("switcher_test");
+    let _ = fs::remove_dir_all(&data_dir);
+    fs::create_dir_all(&data_dir).unwrap();
+    fs::write(
+        data_dir.join("sample.schema.yaml"),
+        "switches:\n  - name: ascii_mode\n    reset: 0\n    states: [中文, 西文]\n\
+         switcher:\n  caption: \"[Switch]\"\nengine:\n  processors: [switcher]\n",
+    )
+    .unwrap();
+
+    let mut hotkey = KeyEvent::default();
+    assert!(hotkey.parse("F4"));
+    let mut engine = Engine::new(Schema::load(&data_dir, "sample").unwrap());
+    assert!(!engine.context().get_option("ascii_mode"));
+    assert!(engine.process_key(&hotkey));
+    let segments = &engine.context().composition().segments;
+    assert_eq!("[Switch]", segments[0].prompt);
+    // the switch comes after the current schema
+    assert!(engine.process_key(&KeyEvent::new(XK_DOWN, 0)));
+    assert!(engine.process_key(&KeyEvent::new(XK_RETURN, 0)));
+    assert!(engine.context().get_option("ascii_mode"));
+
+    // the saved state wins over the reset state in the next session
+    let engine = Engine::new(Schema::load(&data_dir, "sample").unwrap());
+    assert!(engine.context().get_option("ascii_mode"));
+}
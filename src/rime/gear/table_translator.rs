@@ -0,0 +1,518 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, RwLock};
+
+use log::{error, info};
+use signals2::{Connect1, Connection};
+
+use crate::rime::algo::encoder::{PhraseCollector, TableEncoder};
+use crate::rime::algo::spelling::SpellingType;
+use crate::rime::candidate::{BaseCandidate, Candidate};
+use crate::rime::component::Ticket;
+use crate::rime::context::Context;
+use crate::rime::dict::dictionary::{DictEntryIterator, Dictionary};
+use crate::rime::dict::reverse_lookup_dictionary::ReverseLookupDictionary;
+use crate::rime::dict::user_dictionary::UserDictionary;
+use crate::rime::dict::vocabulary::DictEntry;
+use crate::rime::gear::charset_filter::CharsetFilterTranslation;
+use crate::rime::gear::grammar::load_grammar;
+use crate::rime::gear::poet::{homophones, Poet, WordGraph};
+use crate::rime::gear::translator_commons::{
+    create_user_dictionary, learn_from_commits, Phrase, Sentence, TranslatorOptions,
+};
+use crate::rime::language::Language;
+use crate::rime::segmentation::Segment;
+use crate::rime::translation::{
+    DistinctTranslation, FifoTranslation, Translation, UnionTranslation,
+};
+use crate::rime::translator::Translator;
+
+const EXPAND_SEARCH_LIMIT: usize = 512;
+
+// Phrases made by the encoder out of words committed in a row, stored in the
+// user dictionary as ordinary entries under their encoded codes. The codes of
+// the characters come from the reverse db of the dictionary.
+struct EncodedPhrases {
+    user_dict: Arc<RwLock<UserDictionary>>,
+    rev_dict: ReverseLookupDictionary,
+}
+
+impl EncodedPhrases {
+    fn new(user_dict: Arc<RwLock<UserDictionary>>, rev_dict: ReverseLookupDictionary) -> Self {
+        Self {
+            user_dict,
+            rev_dict,
+        }
+    }
+
+    fn save(&self) {
+        if let Ok(mut user_dict) = self.user_dict.write() {
+            user_dict.save();
+        }
+    }
+}
+
+impl PhraseCollector for EncodedPhrases {
+    fn create_entry(&self, phrase: &str, code_str: &str, _value: &str) {
+        if let Ok(mut user_dict) = self.user_dict.write() {
+            user_dict.add_entry(code_str, phrase);
+        }
+    }
+
+    fn translate_word(&self, word: &str) -> Option<Vec<String>> {
+        let codes = self.rev_dict.reverse_lookup(word)?;
+        Some(codes.split(' ').map(str::to_string).collect())
+    }
+}
+
+// Looks up codes of shape-based input methods directly in the table, e.g.
+// Wubi and Cangjie.
+pub(crate) struct TableTranslator {
+    options: Arc<TranslatorOptions>,
+    dict: Option<Arc<Dictionary>>,
+    user_dict: Option<Arc<RwLock<UserDictionary>>>,
+    language: Option<Arc<Language>>,
+    enable_charset_filter: bool,
+    enable_sentence: bool,
+    sentence_over_completion: bool,
+    poet: Poet,
+    // slots connected to the commit notifier of the context
+    connections: Vec<Connection>,
+}
+
+impl TableTranslator {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        let config = ticket.schema.config();
+        let name_space = &ticket.name_space;
+        let dict_name = config.get_string(&format!("{}/dictionary", name_space));
+        let dict = if dict_name.is_empty() {
+            error!("{}/dictionary not specified.", name_space);
+            None
+        } else {
+            ticket
+                .schema
+                .data_dir()
+                .and_then(|data_dir| Dictionary::create(&data_dir, &dict_name, &dict_name))
+                .map(Arc::new)
+        };
+        let language = dict.as_ref().map(|dict| {
+            Arc::new(Language::new(&Language::get_language_component(
+                dict.name(),
+            )))
+        });
+        let enable_sentence_key = format!("{}/enable_sentence", name_space);
+        let enable_sentence =
+            !config.contains(&enable_sentence_key) || config.get_bool(&enable_sentence_key);
+
+        let options = Arc::new(TranslatorOptions::new(ticket));
+        let user_dict = create_user_dictionary(ticket, &dict_name);
+        let mut connections = Vec::new();
+
+        if config.get_bool(&format!("{}/enable_encoder", name_space)) {
+            let rev_dict = ticket
+                .schema
+                .data_dir()
+                .filter(|_| !dict_name.is_empty())
+                .and_then(|data_dir| ReverseLookupDictionary::create(&data_dir, &dict_name));
+            match (&user_dict, rev_dict) {
+                (Some(user_dict), Some(rev_dict)) => {
+                    let phrases = Arc::new(EncodedPhrases::new(user_dict.clone(), rev_dict));
+                    let mut encoder = TableEncoder::new(Some(phrases.clone()));
+                    if encoder.load_settings(Some(config)) {
+                        let encoder = Arc::new(encoder);
+                        connections.push(ticket.context.commit_notifier().connect(
+                            move |context: Arc<Context>| {
+                                on_commit(&encoder, &phrases, &context);
+                            },
+                        ));
+                    } else {
+                        error!("failed to load encoder settings for '{}'.", name_space);
+                    }
+                }
+                (None, _) => error!("the encoder of '{}' needs a user dictionary.", name_space),
+                (_, None) => error!("the encoder of '{}' needs a reverse db.", name_space),
+            }
+        }
+
+        if let Some(user_dict) = &user_dict {
+            connections.push(learn_from_commits(
+                ticket,
+                user_dict.clone(),
+                &["table", "user_table", "sentence"],
+                options.delimiters(),
+            ));
+        }
+
+        let poet = Poet::new(language.clone(), load_grammar(ticket.schema));
+        Self {
+            options,
+            dict,
+            user_dict,
+            language,
+            enable_charset_filter: config
+                .get_bool(&format!("{}/enable_charset_filter", name_space)),
+            enable_sentence,
+            sentence_over_completion: config
+                .get_bool(&format!("{}/sentence_over_completion", name_space)),
+            poet,
+            connections,
+        }
+    }
+
+    // Composes sentences of words whose codes make up the whole input.
+    fn make_sentences(
+        &self,
+        dict: &Dictionary,
+        input: &str,
+        start: usize,
+        preceding_text: &str,
+    ) -> Option<Arc<RwLock<dyn Translation>>> {
+        let mut word_graph = WordGraph::new();
+        for start_pos in 0..input.len() {
+            let mut words = BTreeMap::new();
+            for end_pos in start_pos + 1..=input.len() {
+                let Some(code) = input.get(start_pos..end_pos) else {
+                    continue;
+                };
+                if let Some(iter) = dict.lookup_words(code, false, 0) {
+                    words.insert(end_pos, homophones(iter));
+                }
+            }
+            if !words.is_empty() {
+                word_graph.insert(start_pos, words);
+            }
+        }
+        let mut translation = FifoTranslation::new();
+        for sentence in self
+            .poet
+            .make_sentences(&word_graph, input.len(), preceding_text)
+        {
+            translation.append(Some(Arc::new(
+                self.make_sentence_candidate(sentence, input, start),
+            )));
+        }
+        (translation.size() > 0)
+            .then(|| Arc::new(RwLock::new(translation)) as Arc<RwLock<dyn Translation>>)
+    }
+
+    fn make_sentence_candidate(
+        &self,
+        mut sentence: Sentence,
+        input: &str,
+        start: usize,
+    ) -> Sentence {
+        let delimiter = self
+            .options
+            .delimiters()
+            .chars()
+            .next()
+            .unwrap_or(' ')
+            .to_string();
+        let mut word_start = 0;
+        let mut preedit = sentence
+            .word_lengths()
+            .iter()
+            .map(|&length| {
+                let word = &input[word_start..word_start + length];
+                word_start += length;
+                word
+            })
+            .collect::<Vec<_>>()
+            .join(&delimiter);
+        self.options.format_preedit(&mut preedit);
+        sentence.offset(start);
+        sentence.set_quality(sentence.weight().exp() + self.options.initial_quality());
+        sentence.set_preedit(&preedit);
+        sentence
+    }
+}
+
+impl Drop for TableTranslator {
+    fn drop(&mut self) {
+        for connection in &self.connections {
+            connection.disconnect();
+        }
+    }
+}
+
+impl Translator for TableTranslator {
+    fn query(
+        &mut self,
+        input: &str,
+        segment: &Segment,
+        context: &Context,
+    ) -> Option<Arc<RwLock<dyn Translation>>> {
+        if !segment.has_any_tag_in(self.options.tags()) {
+            return None;
+        }
+        let dict = self.dict.as_ref()?;
+        if !dict.loaded() {
+            error!("dictionary '{}' not loaded.", dict.name());
+            return None;
+        }
+        info!("input = '{}', [{}, {})", input, segment.start, segment.end);
+
+        let code = input.trim_end_matches(|c| self.options.delimiters().contains(c));
+        let predictive = self.options.enable_completion();
+        let entries = dict
+            .lookup_words(code, predictive, EXPAND_SEARCH_LIMIT)
+            .unwrap_or_default();
+        let user_phrases: VecDeque<Arc<DictEntry>> = self
+            .user_dict
+            .as_ref()
+            .and_then(|user_dict| Some(user_dict.write().ok()?.lookup(code, predictive)))
+            .unwrap_or_default()
+            .into();
+        let table_translation = TableTranslation::new(
+            self.options.clone(),
+            self.language.clone(),
+            input,
+            segment.start,
+            segment.end,
+            entries,
+            user_phrases,
+        );
+        let mut translation = (!table_translation.exhausted())
+            .then(|| Arc::new(RwLock::new(table_translation)) as Arc<RwLock<dyn Translation>>);
+        if self.enable_charset_filter && !context.get_option("extended_charset") {
+            translation = translation.map(|translation| {
+                Arc::new(RwLock::new(CharsetFilterTranslation::new(translation)))
+                    as Arc<RwLock<dyn Translation>>
+            });
+        }
+
+        let first_is_completion = translation
+            .as_ref()
+            .and_then(|translation| translation.read().ok()?.peek())
+            .is_some_and(|candidate| candidate.type_() == "completion");
+        let preceding_text = context.composition().get_text_before(segment.start);
+        if self.sentence_over_completion && first_is_completion {
+            if let Some(sentences) = self.make_sentences(dict, code, segment.start, &preceding_text)
+            {
+                let mut union = UnionTranslation::new();
+                union.add_translation(Some(sentences));
+                union.add_translation(translation);
+                translation = Some(Arc::new(RwLock::new(union)));
+            }
+        }
+        let exhausted = translation.as_ref().map_or(true, |translation| {
+            translation
+                .read()
+                .map_or(true, |translation| translation.exhausted())
+        });
+        if exhausted && self.enable_sentence {
+            translation = self.make_sentences(dict, code, segment.start, &preceding_text);
+        }
+        let translation = translation?;
+        Some(Arc::new(RwLock::new(DistinctTranslation::new(Some(
+            translation,
+        )))))
+    }
+}
+
+// User phrases and table entries, exact matches before completions.
+struct TableTranslation {
+    options: Arc<TranslatorOptions>,
+    language: Option<Arc<Language>>,
+    input: String,
+    start: usize,
+    end: usize,
+    entries: DictEntryIterator,
+    user_phrases: VecDeque<Arc<DictEntry>>,
+    candidate: Option<Arc<dyn Candidate>>,
+    // whether the candidate is made of the front user phrase
+    is_user_phrase: bool,
+}
+
+impl TableTranslation {
+    fn new(
+        options: Arc<TranslatorOptions>,
+        language: Option<Arc<Language>>,
+        input: &str,
+        start: usize,
+        end: usize,
+        entries: DictEntryIterator,
+        user_phrases: VecDeque<Arc<DictEntry>>,
+    ) -> Self {
+        let mut translation = Self {
+            options,
+            language,
+            input: input.to_string(),
+            start,
+            end,
+            entries,
+            user_phrases,
+            candidate: None,
+            is_user_phrase: false,
+        };
+        translation.prepare_candidate();
+        translation
+    }
+
+    // Whether the next one is a user phrase rather than a table entry.
+    fn prefers_user_phrase(&self) -> bool {
+        let Some(user_phrase) = self.user_phrases.front() else {
+            return false;
+        };
+        self.entries.peek().map_or(true, |entry| {
+            user_phrase.remaining_code_length <= entry.remaining_code_length
+        })
+    }
+
+    fn prepare_candidate(&mut self) {
+        self.is_user_phrase = self.prefers_user_phrase();
+        self.candidate = self.make_candidate(self.is_user_phrase);
+    }
+
+    fn make_candidate(&self, is_user_phrase: bool) -> Option<Arc<dyn Candidate>> {
+        let entry = if is_user_phrase {
+            self.user_phrases.front()?.clone()
+        } else {
+            self.entries.peek()?
+        };
+        let spelling_type = if entry.remaining_code_length > 0 {
+            SpellingType::Completion
+        } else {
+            SpellingType::Normal
+        };
+        let type_ = match (spelling_type, is_user_phrase) {
+            (SpellingType::Completion, _) => "completion",
+            (_, true) => "user_table",
+            _ => "table",
+        };
+        let mut quality = entry.weight.exp() + self.options.initial_quality();
+        if spelling_type == SpellingType::Completion {
+            quality -= 1.0;
+        }
+        let mut comment = entry.comment.clone();
+        self.options.format_comment(&mut comment);
+        let mut preedit = self.input.clone();
+        self.options.format_preedit(&mut preedit);
+        let mut phrase = Phrase::new(self.language.clone(), type_, self.start, self.end, entry);
+        phrase.set_quality(quality);
+        phrase.set_comment(&comment);
+        phrase.set_preedit(&preedit);
+        Some(Arc::new(phrase))
+    }
+}
+
+impl Translation for TableTranslation {
+    fn next(&mut self) -> Option<Arc<dyn Candidate>> {
+        let candidate = self.candidate.take()?;
+        if self.is_user_phrase {
+            self.user_phrases.pop_front();
+        } else {
+            self.entries.next();
+        }
+        self.prepare_candidate();
+        Some(candidate)
+    }
+
+    fn peek(&self) -> Option<Arc<dyn Candidate>> {
+        self.candidate.clone()
+    }
+
+    fn exhausted(&self) -> bool {
+        self.candidate.is_none()
+    }
+}
+
+// Encodes the words committed in a row into a new phrase, which is saved to
+// the user dictionary. A sentence counts as the words it is made of.
+fn on_commit(encoder: &TableEncoder, phrases: &EncodedPhrases, context: &Context) {
+    let mut phrase = String::new();
+    let mut word_count = 0;
+    let mut encoded = false;
+    for segment in &context.composition().segments {
+        let Some(candidate) = segment.get_selected_candidate() else {
+            continue;
+        };
+        let candidate = BaseCandidate::get_genuine_candidate(&candidate);
+        match candidate.type_() {
+            "table" | "user_table" => {
+                phrase.push_str(candidate.text());
+                word_count += 1;
+            }
+            "sentence" => {
+                phrase.push_str(candidate.text());
+                word_count += candidate
+                    .as_any()
+                    .downcast_ref::<Sentence>()
+                    .map_or(1, Sentence::size);
+            }
+            _ => {
+                if word_count > 1 {
+                    encoded |= encoder.encode_phrase(&phrase, "0");
+                }
+                phrase.clear();
+                word_count = 0;
+            }
+        }
+    }
+    if word_count > 1 {
+        encoded |= encoder.encode_phrase(&phrase, "0");
+    }
+    if encoded {
+        phrases.save();
+    }
+}
+
+#[test]
+fn encode_phrase_of_committed_words() {
+    use std::fs;
+
+    use crate::rime::common::PathExt;
+    use crate::rime::dict::dict_compiler::DictCompiler;
+    use crate::rime::menu::Menu;
+    use crate::rime::schema::Schema;
+    use crate::rime::segmentation::SegmentStatus;
+
+    let data_dir = PathExt::new("table_translator_test");
+    let _ = fs::remove_dir_all(&data_dir);
+    fs::create_dir_all(&data_dir).unwrap();
+    fs::write(
+        data_dir.join("sample.dict.yaml"),
+        "---\nname: sample\nversion: \"1.0\"\n...\n一\taa\t100\n二\tbb\t100\n",
+    )
+    .unwrap();
+    fs::write(
+        data_dir.join("sample.schema.yaml"),
+        "translator:\n  dictionary: sample\n  enable_encoder: true\n  enable_sentence: false\n\
+         encoder:\n  rules:\n    - length_equal: 2\n      formula: \"AaAbBaBb\"\n",
+    )
+    .unwrap();
+    let compiler = DictCompiler::new(&data_dir, "sample", "sample");
+    assert!(compiler.compile(Some(&data_dir.join("sample.schema.yaml"))));
+
+    let schema = Schema::load(&data_dir, "sample").unwrap();
+    let mut context = Context::new();
+    let ticket = Ticket::new(&schema, &context, "translator", "table_translator");
+    let mut translator = TableTranslator::new(&ticket);
+    let first = |translator: &mut TableTranslator, context: &Context, start: usize, end: usize| {
+        let mut segment = Segment::new(start, end);
+        segment.tags.insert("abc".to_string());
+        let translation = translator.query(&context.input()[start..end], &segment, context)?;
+        let candidate = translation.read().unwrap().peek()?;
+        let mut menu = Menu::new();
+        menu.add_translation(translation);
+        segment.menu = Some(Arc::new(RwLock::new(menu)));
+        segment.status = SegmentStatus::Selected;
+        Some((segment, candidate.text().to_string()))
+    };
+
+    // commit "一" and "二" typed in a row
+    context.set_input("aabb".to_string());
+    let (first_word, text) = first(&mut translator, &context, 0, 2).unwrap();
+    assert_eq!("一", text);
+    let (second_word, text) = first(&mut translator, &context, 2, 4).unwrap();
+    assert_eq!("二", text);
+    context.composition_mut().reset("aabb");
+    context.composition_mut().segments = vec![first_word, second_word];
+    assert!(context.commit());
+
+    // the phrase is found by the code encoded from those of its characters
+    context.set_input("aabb".to_string());
+    let (_, text) = first(&mut translator, &context, 0, 4).unwrap();
+    assert_eq!("一二", text);
+    let user_db = fs::read_to_string(data_dir.join("sample.userdb.txt")).unwrap();
+    assert!(user_db.contains("aabb \t一二\t"));
+}
@@ -0,0 +1,433 @@
+use std::any::Any;
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+use log::error;
+use signals2::{Connect1, Connection};
+
+use crate::rime::algo::algebra::Projection;
+use crate::rime::candidate::{BaseCandidate, Candidate};
+use crate::rime::component::Ticket;
+use crate::rime::context::Context;
+use crate::rime::dict::user_dictionary::UserDictionary;
+use crate::rime::dict::vocabulary::{Code, DictEntry};
+use crate::rime::language::{Language, LanguageProvider};
+use crate::rime::spans::Spans;
+
+// Settings shared by translators, read from the translator's name space.
+pub(crate) struct TranslatorOptions {
+    tags: HashSet<String>,
+    delimiters: String,
+    enable_completion: bool,
+    strict_spelling: bool,
+    initial_quality: f64,
+    enable_correction: bool,
+    preedit_formatter: Projection,
+    comment_formatter: Projection,
+}
+
+impl TranslatorOptions {
+    pub(crate) fn new(ticket: &Ticket) -> Self {
+        let config = ticket.schema.config();
+        let name_space = &ticket.name_space;
+        let mut delimiters = config.get_string("speller/delimiter");
+        let custom_delimiters = config.get_string(&format!("{}/delimiter", name_space));
+        if !custom_delimiters.is_empty() {
+            delimiters = custom_delimiters;
+        }
+        if delimiters.is_empty() {
+            delimiters = " ".to_string();
+        }
+        let mut tags = HashSet::new();
+        let tag = config.get_string(&format!("{}/tag", name_space));
+        if !tag.is_empty() {
+            tags.insert(tag);
+        }
+        if let Some(list) = config.get_list(&format!("{}/tags", name_space)) {
+            for i in 0..list.size() {
+                if let Some(tag) = list.get_str_at(i) {
+                    tags.insert(tag.to_string());
+                }
+            }
+        }
+        if tags.is_empty() {
+            tags.insert("abc".to_string());
+        }
+        let enable_completion_key = format!("{}/enable_completion", name_space);
+        let enable_completion =
+            !config.contains(&enable_completion_key) || config.get_bool(&enable_completion_key);
+        let mut preedit_formatter = Projection::new();
+        preedit_formatter.load(config.get_list(&format!("{}/preedit_format", name_space)));
+        let mut comment_formatter = Projection::new();
+        comment_formatter.load(config.get_list(&format!("{}/comment_format", name_space)));
+        Self {
+            tags,
+            delimiters,
+            enable_completion,
+            strict_spelling: config.get_bool(&format!("{}/strict_spelling", name_space)),
+            initial_quality: config.get_double(&format!("{}/initial_quality", name_space)),
+            enable_correction: config.get_bool(&format!("{}/enable_correction", name_space)),
+            preedit_formatter,
+            comment_formatter,
+        }
+    }
+
+    pub(crate) fn tags(&self) -> &HashSet<String> {
+        &self.tags
+    }
+
+    pub(crate) fn delimiters(&self) -> &str {
+        &self.delimiters
+    }
+
+    pub(crate) fn enable_completion(&self) -> bool {
+        self.enable_completion
+    }
+
+    pub(crate) fn strict_spelling(&self) -> bool {
+        self.strict_spelling
+    }
+
+    pub(crate) fn initial_quality(&self) -> f64 {
+        self.initial_quality
+    }
+
+    pub(crate) fn enable_correction(&self) -> bool {
+        self.enable_correction
+    }
+
+    pub(crate) fn format_preedit(&self, preedit: &mut String) {
+        self.preedit_formatter.apply(Some(preedit));
+    }
+
+    pub(crate) fn format_comment(&self, comment: &mut String) {
+        self.comment_formatter.apply(Some(comment));
+    }
+}
+
+// Opens the user dictionary named by "<ns>/user_dict", or else after the
+// dictionary of the translator, unless "<ns>/enable_user_dict" is false.
+pub(crate) fn create_user_dictionary(
+    ticket: &Ticket,
+    dict_name: &str,
+) -> Option<Arc<RwLock<UserDictionary>>> {
+    let config = ticket.schema.config();
+    let name_space = &ticket.name_space;
+    let enable_user_dict_key = format!("{}/enable_user_dict", name_space);
+    if config.contains(&enable_user_dict_key) && !config.get_bool(&enable_user_dict_key) {
+        return None;
+    }
+    let mut user_dict_name = config.get_string(&format!("{}/user_dict", name_space));
+    if user_dict_name.is_empty() {
+        user_dict_name = dict_name.to_string();
+    }
+    if user_dict_name.is_empty() {
+        return None;
+    }
+    let Some(data_dir) = ticket.schema.data_dir() else {
+        error!("no data dir for user dictionary '{}'.", user_dict_name);
+        return None;
+    };
+    UserDictionary::create(&data_dir, &user_dict_name)
+        .map(|user_dict| Arc::new(RwLock::new(user_dict)))
+}
+
+// Counts each committed candidate of the given types in the user dictionary,
+// under the input it was typed with, and saves the dictionary. The translator
+// disconnects the returned connection when it is dropped.
+pub(crate) fn learn_from_commits(
+    ticket: &Ticket,
+    user_dict: Arc<RwLock<UserDictionary>>,
+    types: &'static [&'static str],
+    delimiters: &str,
+) -> Connection {
+    let delimiters = delimiters.to_string();
+    ticket
+        .context
+        .commit_notifier()
+        .connect(move |context: Arc<Context>| {
+            let Ok(mut user_dict) = user_dict.write() else {
+                return;
+            };
+            let input = context.input();
+            let mut updated = false;
+            for segment in &context.composition().segments {
+                let Some(candidate) = segment.get_selected_candidate() else {
+                    continue;
+                };
+                let candidate = BaseCandidate::get_genuine_candidate(&candidate);
+                if !types.contains(&candidate.type_()) {
+                    continue;
+                }
+                let Some(code) = input.get(candidate.start()..candidate.end()) else {
+                    continue;
+                };
+                let code = code.trim_matches(|c| delimiters.contains(c));
+                if !code.is_empty() {
+                    updated |= user_dict.update_entry(code, candidate.text());
+                }
+            }
+            if updated {
+                user_dict.commit_tick();
+                user_dict.save();
+            }
+        })
+}
+
+// A candidate made of a dictionary entry.
+#[derive(Clone)]
+pub(crate) struct Phrase {
+    base: BaseCandidate,
+    entry: Arc<DictEntry>,
+    language: Option<Arc<Language>>,
+    spans: Spans,
+}
+
+impl Phrase {
+    pub(crate) fn new(
+        language: Option<Arc<Language>>,
+        type_: &str,
+        start: usize,
+        end: usize,
+        entry: Arc<DictEntry>,
+    ) -> Self {
+        Self {
+            base: BaseCandidate::new(type_.to_string(), start, end, None),
+            entry,
+            language,
+            spans: Spans::default(),
+        }
+    }
+
+    pub(crate) fn entry(&self) -> &Arc<DictEntry> {
+        &self.entry
+    }
+
+    pub(crate) fn code(&self) -> &Code {
+        &self.entry.code
+    }
+
+    pub(crate) fn weight(&self) -> f64 {
+        self.entry.weight
+    }
+
+    pub(crate) fn set_comment(&mut self, comment: &str) {
+        Arc::make_mut(&mut self.entry).comment = comment.to_string();
+    }
+
+    pub(crate) fn set_preedit(&mut self, preedit: &str) {
+        Arc::make_mut(&mut self.entry).preedit = preedit.to_string();
+    }
+
+    pub(crate) fn set_spans(&mut self, spans: Spans) {
+        self.spans = spans;
+    }
+}
+
+impl LanguageProvider for Phrase {
+    fn language(&self) -> Option<&Language> {
+        self.language.as_deref()
+    }
+}
+
+impl Candidate for Phrase {
+    fn type_(&self) -> &str {
+        self.base.type_()
+    }
+
+    fn start(&self) -> usize {
+        self.base.start()
+    }
+
+    fn end(&self) -> usize {
+        self.base.end()
+    }
+
+    fn quality(&self) -> f64 {
+        self.base.quality()
+    }
+
+    fn text(&self) -> &str {
+        &self.entry.text
+    }
+
+    fn comment(&self) -> &str {
+        &self.entry.comment
+    }
+
+    fn preedit(&self) -> &str {
+        &self.entry.preedit
+    }
+
+    fn spans(&self) -> Spans {
+        if self.spans.count() > 0 {
+            self.spans.clone()
+        } else {
+            let mut spans = Spans::default();
+            spans.add_span(self.start(), self.end());
+            spans
+        }
+    }
+
+    fn set_type(&mut self, type_: &str) {
+        self.base.set_type(type_);
+    }
+
+    fn set_start(&mut self, start: usize) {
+        self.base.set_start(start);
+    }
+
+    fn set_end(&mut self, end: usize) {
+        self.base.set_end(end);
+    }
+
+    fn set_quality(&mut self, quality: f64) {
+        self.base.set_quality(quality);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// A phrase composed of words, with the input length of each.
+#[derive(Clone)]
+pub(crate) struct Sentence {
+    phrase: Phrase,
+    components: Vec<Arc<DictEntry>>,
+    word_lengths: Vec<usize>,
+}
+
+impl Sentence {
+    pub(crate) fn new(language: Option<Arc<Language>>) -> Self {
+        Self {
+            phrase: Phrase::new(language, "sentence", 0, 0, Arc::new(DictEntry::default())),
+            components: Vec::new(),
+            word_lengths: Vec::new(),
+        }
+    }
+
+    // Appends a word ending at end_pos, relative to the start of the sentence,
+    // adding to the sentence the weight it was given there.
+    pub(crate) fn extend(&mut self, entry: Arc<DictEntry>, end_pos: usize, weight: f64) {
+        let sentence_entry = Arc::make_mut(&mut self.phrase.entry);
+        sentence_entry.text.push_str(&entry.text);
+        sentence_entry.code.extend(entry.code.iter().copied());
+        // word weights are log probabilities
+        sentence_entry.weight += weight;
+        self.word_lengths.push(end_pos - self.phrase.end());
+        self.phrase.set_end(end_pos);
+        self.components.push(entry);
+    }
+
+    // Moves the sentence to where its segment starts.
+    pub(crate) fn offset(&mut self, offset: usize) {
+        let start = self.phrase.start() + offset;
+        let end = self.phrase.end() + offset;
+        self.phrase.set_start(start);
+        self.phrase.set_end(end);
+    }
+
+    pub(crate) fn empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    pub(crate) fn size(&self) -> usize {
+        self.components.len()
+    }
+
+    pub(crate) fn components(&self) -> &[Arc<DictEntry>] {
+        &self.components
+    }
+
+    pub(crate) fn word_lengths(&self) -> &[usize] {
+        &self.word_lengths
+    }
+
+    pub(crate) fn weight(&self) -> f64 {
+        self.phrase.weight()
+    }
+
+    pub(crate) fn set_comment(&mut self, comment: &str) {
+        self.phrase.set_comment(comment);
+    }
+
+    pub(crate) fn set_preedit(&mut self, preedit: &str) {
+        self.phrase.set_preedit(preedit);
+    }
+
+    // Divides the sentence by syllables rather than by words.
+    pub(crate) fn set_spans(&mut self, spans: Spans) {
+        self.phrase.set_spans(spans);
+    }
+}
+
+impl LanguageProvider for Sentence {
+    fn language(&self) -> Option<&Language> {
+        self.phrase.language()
+    }
+}
+
+impl Candidate for Sentence {
+    fn type_(&self) -> &str {
+        self.phrase.type_()
+    }
+
+    fn start(&self) -> usize {
+        self.phrase.start()
+    }
+
+    fn end(&self) -> usize {
+        self.phrase.end()
+    }
+
+    fn quality(&self) -> f64 {
+        self.phrase.quality()
+    }
+
+    fn text(&self) -> &str {
+        self.phrase.text()
+    }
+
+    fn comment(&self) -> &str {
+        self.phrase.comment()
+    }
+
+    fn preedit(&self) -> &str {
+        self.phrase.preedit()
+    }
+
+    fn spans(&self) -> Spans {
+        if self.phrase.spans.count() > 0 {
+            return self.phrase.spans.clone();
+        }
+        let mut spans = Spans::default();
+        let mut end = self.start();
+        spans.add_vertex(end);
+        for &length in &self.word_lengths {
+            end += length;
+            spans.add_vertex(end);
+        }
+        spans
+    }
+
+    fn set_type(&mut self, type_: &str) {
+        self.phrase.set_type(type_);
+    }
+
+    fn set_start(&mut self, start: usize) {
+        self.phrase.set_start(start);
+    }
+
+    fn set_end(&mut self, end: usize) {
+        self.phrase.set_end(end);
+    }
+
+    fn set_quality(&mut self, quality: f64) {
+        self.phrase.set_quality(quality);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
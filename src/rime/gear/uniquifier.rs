@@ -0,0 +1,179 @@
+use std::sync::{Arc, RwLock};
+
+use itertools::Itertools;
+
+use crate::rime::candidate::{Candidate, CandidateList, UniquifiedCandidate};
+use crate::rime::component::Ticket;
+use crate::rime::context::Context;
+use crate::rime::filter::Filter;
+use crate::rime::translation::Translation;
+
+// Folds candidates of the same text, e.g. found by different translators,
+// into the first of them.
+pub(crate) struct Uniquifier;
+
+impl Uniquifier {
+    pub(crate) fn new(_ticket: &Ticket) -> Self {
+        Self
+    }
+}
+
+impl Filter for Uniquifier {
+    fn apply(
+        &self,
+        translation: Arc<RwLock<dyn Translation>>,
+        candidates: Arc<RwLock<CandidateList>>,
+        _context: &Context,
+    ) -> Arc<RwLock<dyn Translation>> {
+        Arc::new(RwLock::new(UniquifiedTranslation::new(
+            translation,
+            candidates,
+        )))
+    }
+}
+
+// Keeps the highest quality among the duplicates and all their comments.
+fn fold(previous: &Arc<dyn Candidate>, next: Arc<dyn Candidate>) -> Arc<dyn Candidate> {
+    let mut items = match previous.as_any().downcast_ref::<UniquifiedCandidate>() {
+        Some(uniquified) => uniquified.items().clone(),
+        None => vec![previous.clone()],
+    };
+    items.push(next);
+    let quality = items
+        .iter()
+        .map(|item| item.quality())
+        .fold(f64::NEG_INFINITY, f64::max);
+    let comment = items
+        .iter()
+        .map(|item| item.comment())
+        .filter(|comment| !comment.is_empty())
+        .unique()
+        .join(" ");
+    let mut items = items.into_iter();
+    let mut uniquified = UniquifiedCandidate::new(
+        items.next().unwrap(),
+        "uniquified".to_string(),
+        None,
+        Some(comment),
+    );
+    for item in items {
+        uniquified.append(item);
+    }
+    // as is, even below zero
+    uniquified.set_quality(quality);
+    Arc::new(uniquified)
+}
+
+struct UniquifiedTranslation {
+    translation: Arc<RwLock<dyn Translation>>,
+    // candidates the menu has taken so far
+    candidates: Arc<RwLock<CandidateList>>,
+}
+
+impl UniquifiedTranslation {
+    fn new(
+        translation: Arc<RwLock<dyn Translation>>,
+        candidates: Arc<RwLock<CandidateList>>,
+    ) -> Self {
+        let uniquified = Self {
+            translation,
+            candidates,
+        };
+        if let Ok(mut translation) = uniquified.translation.write() {
+            while let Some(next) = translation.peek() {
+                if !uniquified.fold_into_previous(next) {
+                    break;
+                }
+                translation.next();
+            }
+        }
+        uniquified
+    }
+
+    // Returns whether the candidate is a duplicate of one taken by the menu.
+    fn fold_into_previous(&self, next: Arc<dyn Candidate>) -> bool {
+        let Ok(mut candidates) = self.candidates.write() else {
+            return false;
+        };
+        let Some(previous) = candidates
+            .iter_mut()
+            .flatten()
+            .find(|previous| previous.text() == next.text())
+        else {
+            return false;
+        };
+        *previous = fold(previous, next);
+        true
+    }
+}
+
+impl Translation for UniquifiedTranslation {
+    fn next(&mut self) -> Option<Arc<dyn Candidate>> {
+        let mut translation = self.translation.write().ok()?;
+        let mut candidate = translation.next()?;
+        // the candidate joins the menu only after it is returned, so its
+        // duplicates are folded into it here
+        while let Some(next) = translation.peek() {
+            if next.text() == candidate.text() {
+                candidate = fold(&candidate, next);
+            } else if !self.fold_into_previous(next) {
+                break;
+            }
+            translation.next();
+        }
+        Some(candidate)
+    }
+
+    fn peek(&self) -> Option<Arc<dyn Candidate>> {
+        self.translation.read().ok()?.peek()
+    }
+
+    fn exhausted(&self) -> bool {
+        self.translation
+            .read()
+            .map_or(true, |translation| translation.exhausted())
+    }
+}
+
+#[test]
+fn fold_duplicates() {
+    use crate::rime::candidate::SimpleCandidate;
+    use crate::rime::menu::Menu;
+    use crate::rime::translation::FifoTranslation;
+
+    let candidate = |text: &str, quality: f64, comment: &str| {
+        let mut candidate = SimpleCandidate::new(
+            "simple".to_string(),
+            0,
+            1,
+            text.to_string(),
+            Some(comment.to_string()),
+            None,
+        );
+        candidate.set_quality(quality);
+        Some(Arc::new(candidate) as Arc<dyn Candidate>)
+    };
+    let mut translation = FifoTranslation::new();
+    translation.append(candidate("a", 1.0, "x"));
+    translation.append(candidate("a", 0.5, ""));
+    translation.append(candidate("b", 0.0, ""));
+    // duplicates of a candidate the menu has taken already
+    translation.append(candidate("a", 3.0, "y"));
+    translation.append(candidate("a", 2.0, "x"));
+    // duplicates below zero quality
+    translation.append(candidate("c", -2.0, ""));
+    translation.append(candidate("c", -1.0, ""));
+
+    let mut menu = Menu::new();
+    menu.add_translation(Arc::new(RwLock::new(translation)));
+    menu.add_filter(&Uniquifier, &Context::new());
+    assert_eq!(3, menu.prepare(7));
+    let a = menu.get_candidate_at(0).unwrap();
+    assert_eq!("a", a.text());
+    assert_eq!(3.0, a.quality());
+    assert_eq!("x y", a.comment());
+    assert_eq!("b", menu.get_candidate_at(1).unwrap().text());
+    let c = menu.get_candidate_at(2).unwrap();
+    assert_eq!("c", c.text());
+    assert_eq!(-1.0, c.quality());
+}
@@ -47,11 +47,11 @@ impl KeyEvent {
         (self.modifier & (Mask::Alt as i32)) != 0
     }
 
-    fn caps(&self) -> bool {
+    pub(crate) fn caps(&self) -> bool {
         (self.modifier & (Mask::Lock as i32)) != 0
     }
 
-    fn super_(&self) -> bool {
+    pub(crate) fn super_(&self) -> bool {
         (self.modifier & (Mask::Super as i32)) != 0
     }
 
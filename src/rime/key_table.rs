@@ -3990,6 +3990,35 @@ pub enum Mask {
 pub const XK_VOID_SYMBOL: u32 = 0xffffff; /* Void symbol */
 pub(crate) const XK_BACK_SPACE: u32 = 0xff08; /* Back space, back char */
 pub(crate) const XK_RETURN: u32 = 0xff0d; /* Return, enter */
+pub(crate) const XK_ESCAPE: u32 = 0xff1b;
+pub(crate) const XK_DELETE: u32 = 0xffff; /* Delete, rubout */
+pub(crate) const XK_SPACE: u32 = 0x0020; /* U+0020 SPACE */
+pub(crate) const XK_0: u32 = 0x0030; /* U+0030 DIGIT ZERO */
+pub(crate) const XK_9: u32 = 0x0039; /* U+0039 DIGIT NINE */
+pub(crate) const XK_HOME: u32 = 0xff50;
+pub(crate) const XK_LEFT: u32 = 0xff51; /* Move left, left arrow */
+pub(crate) const XK_UP: u32 = 0xff52; /* Move up, up arrow */
+pub(crate) const XK_RIGHT: u32 = 0xff53; /* Move right, right arrow */
+pub(crate) const XK_DOWN: u32 = 0xff54; /* Move down, down arrow */
+pub(crate) const XK_PRIOR: u32 = 0xff55; /* Prior, previous */
+pub(crate) const XK_NEXT: u32 = 0xff56; /* Next */
+pub(crate) const XK_END: u32 = 0xff57; /* EOL */
+pub(crate) const XK_KP_HOME: u32 = 0xff95;
+pub(crate) const XK_KP_LEFT: u32 = 0xff96;
+pub(crate) const XK_KP_UP: u32 = 0xff97;
+pub(crate) const XK_KP_RIGHT: u32 = 0xff98;
+pub(crate) const XK_KP_DOWN: u32 = 0xff99;
+pub(crate) const XK_KP_PRIOR: u32 = 0xff9a;
+pub(crate) const XK_KP_NEXT: u32 = 0xff9b;
+pub(crate) const XK_KP_END: u32 = 0xff9c;
+pub(crate) const XK_EISU_TOGGLE: u32 = 0xff30; /* Alphanumeric toggle */
+pub(crate) const XK_KP_0: u32 = 0xffb0;
+pub(crate) const XK_KP_9: u32 = 0xffb9;
+pub(crate) const XK_SHIFT_L: u32 = 0xffe1; /* Left shift */
+pub(crate) const XK_SHIFT_R: u32 = 0xffe2; /* Right shift */
+pub(crate) const XK_CONTROL_L: u32 = 0xffe3; /* Left control */
+pub(crate) const XK_CONTROL_R: u32 = 0xffe4; /* Right control */
+pub(crate) const XK_CAPS_LOCK: u32 = 0xffe5; /* Caps lock */
 
 pub fn get_modifier_by_name(name: Option<&str>) -> i32 {
     if let Some(name) = name {
@@ -1,15 +1,16 @@
 use crate::rime::candidate::{Candidate, CandidateList};
+use crate::rime::context::Context;
 use crate::rime::filter::Filter;
 use crate::rime::translation::{MergedTranslation, Translation};
 use log::{error, info};
 use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard};
 
 #[derive(Default)]
-struct Page {
-    page_size: usize,
-    page_no: usize,
-    is_last_page: bool,
-    candidates: CandidateList,
+pub(crate) struct Page {
+    pub(crate) page_size: usize,
+    pub(crate) page_no: usize,
+    pub(crate) is_last_page: bool,
+    pub(crate) candidates: CandidateList,
 }
 
 impl Page {
@@ -31,15 +32,18 @@ impl Page {
 pub(crate) struct Menu {
     merged: Arc<RwLock<MergedTranslation>>,
     result: Arc<RwLock<dyn Translation>>,
-    candidates: CandidateList,
+    // Shared with filters, which may revise the candidates already taken,
+    // e.g. to fold a duplicate into an earlier candidate.
+    candidates: Arc<RwLock<CandidateList>>,
 }
 
 impl Menu {
-    fn new() -> Self {
-        let candidates = CandidateList::new();
-        let merged_translation = MergedTranslation::new(candidates.clone());
-        let merged = Arc::new(RwLock::new(merged_translation.clone()));
-        let result = Arc::new(RwLock::new(merged_translation)) as Arc<RwLock<dyn Translation>>;
+    pub(crate) fn new() -> Self {
+        let candidates = Arc::new(RwLock::new(CandidateList::new()));
+        let merged = Arc::new(RwLock::new(MergedTranslation::new(CandidateList::new())));
+        // The result starts out as the merged translation itself, so that translations
+        // added later are visible through it.
+        let result = merged.clone() as Arc<RwLock<dyn Translation>>;
         Self {
             merged,
             result,
@@ -47,7 +51,7 @@ impl Menu {
         }
     }
 
-    fn add_translation(&mut self, translation: Arc<RwLock<dyn Translation>>) {
+    pub(crate) fn add_translation(&mut self, translation: Arc<RwLock<dyn Translation>>) {
         if let Ok(mut merged) = self.merged.write() {
             merged.add_translation(Some(translation));
             info!("Updated total translations: {}", merged.size());
@@ -56,21 +60,27 @@ impl Menu {
         }
     }
 
-    fn add_filter(&mut self, _filter: Box<dyn Filter>) {
-        //let result = Arc::clone(&self.result);
-        //filter.apply(result, &mut self.candidates);
-        todo!()
+    // Chains the filter onto the translations added so far.
+    pub(crate) fn add_filter(&mut self, filter: &dyn Filter, context: &Context) {
+        self.result = filter.apply(self.result.clone(), self.candidates.clone(), context);
+    }
+
+    fn candidates(&self) -> RwLockReadGuard<CandidateList> {
+        self.candidates.read().unwrap()
     }
 
     pub(crate) fn prepare(&mut self, candidate_count: usize) -> usize {
         info!("Preparing {} candidates", candidate_count);
 
         // Pre-allocate space
+        let prepared = self.candidates().len();
         self.candidates
-            .reserve(candidate_count.saturating_sub(self.candidates.len()));
+            .write()
+            .unwrap()
+            .reserve(candidate_count.saturating_sub(prepared));
 
         let mut continue_loop = true;
-        while self.candidates.len() < candidate_count && continue_loop {
+        while self.candidates().len() < candidate_count && continue_loop {
             match self.result.read() {
                 Ok(result_read_guard) => {
                     if result_read_guard.exhausted() {
@@ -81,8 +91,8 @@ impl Menu {
                     match self.result.write() {
                         Ok(mut result_write_guard) => {
                             let cand = result_write_guard.next();
-                            self.candidates.push(cand);
                             drop(result_write_guard); // Immediately release write lock
+                            self.candidates.write().unwrap().push(cand);
                         }
                         Err(e) => {
                             error!("Failed to acquire write lock: {}", e);
@@ -97,10 +107,10 @@ impl Menu {
             }
         }
 
-        self.candidates.len()
+        self.candidates().len()
     }
 
-    fn create_page(&mut self, page_size: usize, page_no: usize) -> Option<Page> {
+    pub(crate) fn create_page(&mut self, page_size: usize, page_no: usize) -> Option<Page> {
         let start_pos = page_size * page_no;
         let mut end_pos = start_pos + page_size;
 
@@ -109,7 +119,7 @@ impl Menu {
             Err(_) => return None,
         };
 
-        if end_pos > self.candidates.len() {
+        if end_pos > self.candidates().len() {
             if !exhausted {
                 end_pos = self.prepare(end_pos);
                 exhausted = match is_exhausted(&self.result.read()) {
@@ -117,7 +127,7 @@ impl Menu {
                     Err(_) => return None,
                 };
             } else {
-                end_pos = self.candidates.len();
+                end_pos = self.candidates().len();
             }
 
             if start_pos >= end_pos {
@@ -129,26 +139,27 @@ impl Menu {
         let mut page = Page::default();
         page.page_size = page_size;
         page.page_no = page_no;
-        page.is_last_page = exhausted && end_pos == self.candidates.len();
-        page.candidates = self.candidates[start_pos..end_pos].to_vec();
+        let candidates = self.candidates();
+        page.is_last_page = exhausted && end_pos == candidates.len();
+        page.candidates = candidates[start_pos..end_pos].to_vec();
         Some(page)
     }
 
     pub(crate) fn get_candidate_at(&mut self, index: usize) -> Option<Arc<dyn Candidate>> {
-        if index >= self.candidates.len() && index >= self.prepare(index + 1) {
+        if index >= self.candidates().len() && index >= self.prepare(index + 1) {
             return None;
         }
-        self.candidates[index].clone()
+        self.candidates()[index].clone()
     }
 
     // CAVEAT: returns the number of candidates currently obtained,
     // rather than the total number of available candidates.
-    fn candidate_count(&self) -> usize {
-        return self.candidates.len();
+    pub(crate) fn candidate_count(&self) -> usize {
+        return self.candidates().len();
     }
 
     pub(crate) fn is_empty(&self) -> bool {
-        self.candidates.is_empty() && {
+        self.candidates().is_empty() && {
             match self.result.read() {
                 Ok(result_read_guard) => result_read_guard.exhausted(),
                 Err(e) => {
@@ -0,0 +1,25 @@
+use std::sync::{Arc, RwLock};
+
+use crate::rime::component::Component;
+use crate::rime::engine::Engine;
+use crate::rime::key_event::KeyEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProcessResult {
+    // Stop the processing chain and let the key event pass through.
+    Rejected,
+    // The key event has been consumed.
+    Accepted,
+    // Leave the key event to the next processor.
+    Noop,
+}
+
+pub(crate) trait Processor {
+    fn process_key_event(&mut self, key_event: &KeyEvent, engine: &mut Engine) -> ProcessResult;
+
+    // Called once the engine has created the components of a schema, so that
+    // the processor may set up the context for it.
+    fn on_schema_loaded(&mut self, _engine: &mut Engine) {}
+}
+
+pub(crate) type ProcessorComponent = Component<Arc<RwLock<dyn Processor>>>;
@@ -21,7 +21,7 @@ impl Registry {
         self.map.lock().unwrap().get(name).cloned()
     }
 
-    fn register(&self, name: &str, component: Arc<dyn ComponentBase>) {
+    pub(crate) fn register(&self, name: &str, component: Arc<dyn ComponentBase>) {
         info!("Registering component: {}", name);
         let mut map = self.map.lock().unwrap();
         if map.contains_key(name) {
@@ -40,7 +40,7 @@ impl Registry {
         self.map.lock().unwrap().clear()
     }
 
-    fn instance() -> &'static Self {
+    pub(crate) fn instance() -> &'static Self {
         &INSTANCE
     }
 
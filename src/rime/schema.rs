@@ -0,0 +1,78 @@
+use log::error;
+
+use crate::rime::common::PathExt;
+use crate::rime::config::config_component::Config;
+
+const DEFAULT_PAGE_SIZE: usize = 5;
+
+pub(crate) struct Schema {
+    schema_id: String,
+    schema_name: String,
+    config: Config,
+    page_size: usize,
+    select_keys: String,
+}
+
+impl Schema {
+    pub(crate) fn new(schema_id: &str, config: Config) -> Self {
+        let schema_name = match config.get_string("schema/name") {
+            name if name.is_empty() => schema_id.to_string(),
+            name => name,
+        };
+        let page_size = match config.get_int("menu/page_size") {
+            size if size < 1 => DEFAULT_PAGE_SIZE,
+            size => size as usize,
+        };
+        // librime reads the keys from "menu/alternative_select_keys"; the
+        // shorter "menu/select_keys" is taken when that is not set.
+        let mut select_keys = config.get_string("menu/alternative_select_keys");
+        if select_keys.is_empty() {
+            select_keys = config.get_string("menu/select_keys");
+        }
+        Self {
+            schema_id: schema_id.to_string(),
+            schema_name,
+            config,
+            page_size,
+            select_keys,
+        }
+    }
+
+    // Loads "<schema_id>.schema.yaml" from the data directory.
+    pub(crate) fn load(data_dir: &PathExt, schema_id: &str) -> Option<Self> {
+        let mut config = Config::new();
+        if !config.load_from_file(&data_dir.join(format!("{}.schema.yaml", schema_id))) {
+            error!("failed to load schema '{}'.", schema_id);
+            return None;
+        }
+        Some(Self::new(schema_id, config))
+    }
+
+    pub(crate) fn schema_id(&self) -> &str {
+        &self.schema_id
+    }
+
+    pub(crate) fn schema_name(&self) -> &str {
+        &self.schema_name
+    }
+
+    // The directory where the schema file is found, also holding the
+    // compiled dictionaries.
+    pub(crate) fn data_dir(&self) -> Option<PathExt> {
+        self.config
+            .file_path()
+            .and_then(|file_path| file_path.parent().map(PathExt::new))
+    }
+
+    pub(crate) fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub(crate) fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    pub(crate) fn select_keys(&self) -> &str {
+        &self.select_keys
+    }
+}
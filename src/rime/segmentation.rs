@@ -29,7 +29,7 @@ pub(crate) struct Segment {
 }
 
 impl Segment {
-    fn new(start_pos: usize, end_pos: usize) -> Self {
+    pub(crate) fn new(start_pos: usize, end_pos: usize) -> Self {
         Self {
             start: start_pos,
             end: end_pos,
@@ -38,7 +38,7 @@ impl Segment {
         }
     }
 
-    fn clear(&mut self) {
+    pub(crate) fn clear(&mut self) {
         self.status = SegmentStatus::Void;
         self.tags.clear();
         self.menu = None;
@@ -46,7 +46,7 @@ impl Segment {
         self.prompt.clear();
     }
 
-    fn close(&mut self) {
+    pub(crate) fn close(&mut self) {
         if let Some(cand) = self.get_selected_candidate() {
             if cand.end() < self.end {
                 // having selected a partially matched candidate, split it into 2 segments
@@ -79,6 +79,10 @@ impl Segment {
         self.tags.contains(tag)
     }
 
+    pub(crate) fn has_any_tag_in(&self, tags: &HashSet<String>) -> bool {
+        !self.tags.is_disjoint(tags)
+    }
+
     pub(crate) fn get_candidate_at(&self, index: usize) -> Option<Arc<dyn Candidate>> {
         match self.menu.as_ref()?.write() {
             Ok(mut writable) => writable.get_candidate_at(index),
@@ -101,7 +105,7 @@ pub(crate) struct Segmentation {
 }
 
 impl Segmentation {
-    fn reset(&mut self, new_input: &str) {
+    pub(crate) fn reset(&mut self, new_input: &str) {
         info!("reset to {} segments.", self.segments.len());
 
         let input_bytes = self.input.as_bytes();
@@ -135,13 +139,13 @@ impl Segmentation {
         self.input = new_input.to_string();
     }
 
-    fn reset_segments(&mut self, num_segments: usize) {
+    pub(crate) fn reset_segments(&mut self, num_segments: usize) {
         if num_segments < self.segments.len() {
             self.segments.truncate(num_segments);
         }
     }
 
-    fn add_segment(&mut self, segment: Segment) -> bool {
+    pub(crate) fn add_segment(&mut self, segment: Segment) -> bool {
         let start = self.get_current_start_position();
         if segment.start != start {
             // rule one: in one round, we examine only those segs
@@ -192,23 +196,23 @@ impl Segmentation {
         false
     }
 
-    fn has_finished_segmentation(&self) -> bool {
+    pub(crate) fn has_finished_segmentation(&self) -> bool {
         self.segments.last().map_or(0, |seg| seg.end) >= self.input.len()
     }
 
-    fn get_current_start_position(&self) -> usize {
+    pub(crate) fn get_current_start_position(&self) -> usize {
         self.segments.last().map_or(0, |seg| seg.start)
     }
 
-    fn get_current_end_position(&self) -> usize {
+    pub(crate) fn get_current_end_position(&self) -> usize {
         self.segments.last().map_or(0, |seg| seg.end)
     }
 
-    fn get_current_segment_length(&self) -> usize {
+    pub(crate) fn get_current_segment_length(&self) -> usize {
         self.segments.last().map_or(0, |seg| seg.end - seg.start)
     }
 
-    fn get_confirmed_position(&self) -> usize {
+    pub(crate) fn get_confirmed_position(&self) -> usize {
         self.segments
             .iter()
             .filter(|seg| seg.status >= SegmentStatus::Selected)
@@ -217,7 +221,7 @@ impl Segmentation {
             .unwrap_or(0)
     }
 
-    fn input(&self) -> &str {
+    pub(crate) fn input(&self) -> &str {
         &self.input
     }
 }
@@ -0,0 +1,10 @@
+use crate::rime::component::Component;
+use crate::rime::segmentation::Segmentation;
+
+pub(crate) trait Segmentor {
+    // Returns false to stop the following segmentors from working on the
+    // current segment.
+    fn proceed(&mut self, segmentation: &mut Segmentation) -> bool;
+}
+
+pub(crate) type SegmentorComponent = Component<Box<dyn Segmentor>>;
@@ -0,0 +1,62 @@
+// Positions in the input that divide it into syllables, used to move the
+// caret and to delimit the preedit of a phrase.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Spans {
+    vertices: Vec<usize>,
+}
+
+impl Spans {
+    pub(crate) fn add_vertex(&mut self, vertex: usize) {
+        if let Err(pos) = self.vertices.binary_search(&vertex) {
+            self.vertices.insert(pos, vertex);
+        }
+    }
+
+    pub(crate) fn add_span(&mut self, start: usize, end: usize) {
+        self.add_vertex(start);
+        self.add_vertex(end);
+    }
+
+    pub(crate) fn add_spans(&mut self, spans: &Spans) {
+        for &vertex in &spans.vertices {
+            self.add_vertex(vertex);
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.vertices.clear();
+    }
+
+    pub(crate) fn previous_stop(&self, caret_pos: usize) -> usize {
+        self.vertices
+            .iter()
+            .rev()
+            .find(|&&vertex| vertex < caret_pos)
+            .copied()
+            .unwrap_or(caret_pos)
+    }
+
+    pub(crate) fn next_stop(&self, caret_pos: usize) -> usize {
+        self.vertices
+            .iter()
+            .find(|&&vertex| vertex > caret_pos)
+            .copied()
+            .unwrap_or(caret_pos)
+    }
+
+    pub(crate) fn count(&self) -> usize {
+        self.vertices.len().saturating_sub(1)
+    }
+
+    pub(crate) fn start(&self) -> usize {
+        self.vertices.first().copied().unwrap_or(0)
+    }
+
+    pub(crate) fn end(&self) -> usize {
+        self.vertices.last().copied().unwrap_or(0)
+    }
+
+    pub(crate) fn has_vertex(&self, vertex: usize) -> bool {
+        self.vertices.binary_search(&vertex).is_ok()
+    }
+}
@@ -37,13 +37,13 @@ pub(crate) trait Translation {
     fn exhausted(&self) -> bool;
 }
 
-struct UniqueTranslation {
+pub(crate) struct UniqueTranslation {
     candidate: Option<Arc<dyn Candidate>>,
     exhausted: bool,
 }
 
 impl UniqueTranslation {
-    fn new(candidate: Option<Arc<dyn Candidate>>) -> Self {
+    pub(crate) fn new(candidate: Option<Arc<dyn Candidate>>) -> Self {
         let exhausted = candidate.is_none();
         Self {
             candidate,
@@ -79,14 +79,14 @@ impl Translation for UniqueTranslation {
     }
 }
 
-struct FifoTranslation {
+pub(crate) struct FifoTranslation {
     candies: CandidateList,
     cursor: usize,
     exhausted: bool,
 }
 
 impl FifoTranslation {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             candies: CandidateList::new(),
             cursor: 0,
@@ -94,12 +94,12 @@ impl FifoTranslation {
         }
     }
 
-    fn append(&mut self, candy: Option<Arc<dyn Candidate>>) {
+    pub(crate) fn append(&mut self, candy: Option<Arc<dyn Candidate>>) {
         self.candies.push(candy);
         self.set_exhausted(false);
     }
 
-    fn size(&self) -> usize {
+    pub(crate) fn size(&self) -> usize {
         self.candies.len() - self.cursor
     }
 
@@ -114,10 +114,12 @@ impl Translation for FifoTranslation {
             return None;
         }
 
-        if self.cursor + 1 >= self.candies.len() {
+        let candy = self.candies[self.cursor].clone();
+        self.cursor += 1;
+        if self.cursor >= self.candies.len() {
             self.set_exhausted(true);
         }
-        self.candies[self.cursor].clone()
+        candy
     }
 
     fn peek(&self) -> Option<Arc<dyn Candidate>> {
@@ -133,20 +135,20 @@ impl Translation for FifoTranslation {
     }
 }
 
-struct UnionTranslation {
+pub(crate) struct UnionTranslation {
     translations: VecDeque<Arc<RwLock<dyn Translation>>>,
     exhausted: bool,
 }
 
 impl UnionTranslation {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             translations: VecDeque::new(),
             exhausted: true,
         }
     }
 
-    fn add_translation(&mut self, translation: Option<Arc<RwLock<dyn Translation>>>) {
+    pub(crate) fn add_translation(&mut self, translation: Option<Arc<RwLock<dyn Translation>>>) {
         if let Some(arc) = translation {
             let cloned = arc.clone();
             if let Ok(translation) = arc.read() {
@@ -171,15 +173,15 @@ impl Translation for UnionTranslation {
             return None;
         }
 
+        let mut candidate = None;
         if let Some(front) = self.translations.pop_front() {
             let cloned = front.clone();
             match front.write() {
                 Ok(mut translation) => {
-                    let candidate = translation.next();
+                    candidate = translation.next();
                     if !translation.exhausted() {
                         self.translations.push_front(cloned);
                     }
-                    return candidate;
                 }
                 Err(_) => {
                     error!("Failed to acquire write lock");
@@ -192,7 +194,7 @@ impl Translation for UnionTranslation {
             self.set_exhausted(true);
         }
 
-        None
+        candidate
     }
 
     fn peek(&self) -> Option<Arc<dyn Candidate>> {
@@ -291,7 +293,7 @@ impl MergedTranslation {
         let mut counts_below_index = 0;
 
         for index in sorted_indexes_to_remove {
-            if index < index {
+            if index < current_index {
                 counts_below_index += 1;
             }
 
@@ -302,7 +304,7 @@ impl MergedTranslation {
 
         self.elected = current_index - counts_below_index;
 
-        if current_index >= self.translations.len() {
+        if self.elected >= self.translations.len() {
             warn!("Failed to elect a winner translation");
             self.exhausted = true;
         } else {
@@ -381,7 +383,7 @@ impl CacheTranslation {
     fn new(translation: Option<Arc<RwLock<dyn Translation>>>) -> Self {
         let (exhausted, cache) = match translation.as_ref().and_then(|t| t.read().ok()) {
             Some(translation) => (translation.exhausted(), translation.peek()),
-            None => (false, None),
+            None => (true, None),
         };
 
         Self {
@@ -410,17 +412,19 @@ impl Translation for CacheTranslation {
             return None;
         }
 
-        let (exhausted, cache) = match self.translation.as_ref().and_then(|t| t.write().ok()) {
-            Some(mut translation) => (translation.exhausted(), translation.next()),
+        let candidate = self.cache.take();
+        match self.translation.as_ref().and_then(|t| t.write().ok()) {
+            Some(mut translation) => {
+                translation.next();
+                self.exhausted = translation.exhausted();
+                self.cache = translation.peek();
+            }
             None => {
                 error!("Failed to acquire write lock");
-                (false, None)
+                self.exhausted = true;
             }
-        };
-
-        self.exhausted = exhausted;
-        self.cache = cache;
-        self.cache.clone()
+        }
+        candidate
     }
 
     fn peek(&self) -> Option<Arc<dyn Candidate>> {
@@ -436,13 +440,13 @@ impl Translation for CacheTranslation {
     }
 }
 
-struct DistinctTranslation {
+pub(crate) struct DistinctTranslation {
     cache_translation: CacheTranslation,
     candidate_set: HashSet<String>,
 }
 
 impl DistinctTranslation {
-    fn new(translation: Option<Arc<RwLock<dyn Translation>>>) -> Self {
+    pub(crate) fn new(translation: Option<Arc<RwLock<dyn Translation>>>) -> Self {
         Self {
             cache_translation: CacheTranslation::new(translation),
             candidate_set: HashSet::new(),
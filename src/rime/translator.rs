@@ -0,0 +1,17 @@
+use std::sync::{Arc, RwLock};
+
+use crate::rime::component::Component;
+use crate::rime::context::Context;
+use crate::rime::segmentation::Segment;
+use crate::rime::translation::Translation;
+
+pub(crate) trait Translator {
+    fn query(
+        &mut self,
+        input: &str,
+        segment: &Segment,
+        context: &Context,
+    ) -> Option<Arc<RwLock<dyn Translation>>>;
+}
+
+pub(crate) type TranslatorComponent = Component<Box<dyn Translator>>;
@@ -0,0 +1,128 @@
+mod commons;
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use librime_rust::rime::common::PathExt;
+    use librime_rust::rime::dict::dict_compiler::DictCompiler;
+    use librime_rust::rime::dict::prism::Prism;
+    use librime_rust::rime::dict::table::Table;
+
+    use crate::commons;
+
+    static DICT: &str = "---
+name: sample
+version: \"1.0\"
+import_tables:
+  - sample_extra
+min_phrase_weight: 10
+...
+
+# syllables: chang hao ma ni zhang
+你\tni\t100
+泥\tni\t50
+好\thao\t80
+你好\tni hao\t20
+拟好\tni hao\t5
+长\tchang\t100
+长\tzhang\t50%
+";
+
+    static EXTRA: &str = "---
+name: sample_extra
+version: \"1.0\"
+columns:
+  - code
+  - text
+...
+ma\t吗
+";
+
+    static SCHEMA: &str = "speller:
+  algebra:
+    - abbrev/^([a-z]).+$/$1/
+";
+
+    fn prepare(data_dir: &str) -> PathExt {
+        let data_dir = PathExt::new(data_dir);
+        fs::create_dir_all(&data_dir).unwrap();
+        fs::write(data_dir.join("sample.dict.yaml"), DICT).unwrap();
+        fs::write(data_dir.join("sample_extra.dict.yaml"), EXTRA).unwrap();
+        fs::write(data_dir.join("sample.schema.yaml"), SCHEMA).unwrap();
+        data_dir
+    }
+
+    fn load_table(data_dir: &PathExt) -> Table {
+        let mut table = Table::new(data_dir.join("build").join("sample.table.bin"));
+        assert!(table.load());
+        table
+    }
+
+    #[test]
+    fn compile() {
+        commons::enable_log();
+        let data_dir = prepare("dict_compiler_test_compile");
+        let compiler = DictCompiler::new(&data_dir, "sample", "sample");
+        assert!(compiler.compile(Some(&data_dir.join("sample.schema.yaml"))));
+
+        let mut prism = Prism::new(data_dir.join("build").join("sample.prism.bin"));
+        assert!(prism.load());
+        assert!(prism.has_key("zhang"));
+        assert!(prism.has_key("ma"));
+        // spelled by the algebra of the schema
+        assert!(prism.has_key("z"));
+        assert!(!prism.has_key("zh"));
+
+        let table = load_table(&data_dir);
+        let ni = table.query_words(3).unwrap();
+        assert_eq!(2, ni.len());
+        assert_eq!("你", ni[0].text);
+        assert_eq!("泥", ni[1].text);
+        assert!((ni[0].weight - 100f32.ln()).abs() < 1e-5);
+        assert_eq!("吗", table.query_words(2).unwrap()[0].text);
+        // half of the weight of the other entries of the text
+        let zhang = table.query_words(4).unwrap();
+        assert!((zhang[0].weight - 50f32.ln()).abs() < 1e-5);
+        // phrases below min_phrase_weight are left out
+        let ni_hao = table.query_phrases(&[3, 1]).unwrap();
+        assert_eq!(1, ni_hao.len());
+        assert_eq!("你好", ni_hao[0].text);
+    }
+
+    #[test]
+    fn skip_when_up_to_date() {
+        commons::enable_log();
+        let data_dir = prepare("dict_compiler_test_update");
+        let compiler = DictCompiler::new(&data_dir, "sample", "sample");
+        assert!(compiler.compile(None));
+        let table_path = data_dir.join("build").join("sample.table.bin");
+        let modified = fs::metadata(&table_path).unwrap().modified().unwrap();
+        // the checksum is read without loading the table
+        let mut table = Table::new(table_path.clone());
+        assert!(table.load_metadata());
+        assert!(!table.is_loaded());
+        assert_eq!(
+            load_table(&data_dir).dict_file_checksum(),
+            table.dict_file_checksum()
+        );
+
+        assert!(compiler.compile(None));
+        assert_eq!(
+            modified,
+            fs::metadata(&table_path).unwrap().modified().unwrap()
+        );
+
+        let loaded = load_table(&data_dir);
+        let dict = format!("{}妈\tma\t10\n", DICT);
+        fs::write(data_dir.join("sample.dict.yaml"), dict).unwrap();
+        assert!(compiler.compile(None));
+        // the table loaded before is not rewritten under it
+        assert_eq!("吗", loaded.query_words(2).unwrap()[0].text);
+        let ma = load_table(&data_dir).query_words(2).unwrap();
+        assert_eq!(
+            vec!["妈", "吗"],
+            ma.iter().map(|e| e.text).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod tests {
+    use librime_rust::rime::algo::dynamics::{formula_d, formula_p};
+
+    #[test]
+    fn decay() {
+        assert_eq!(1.0, formula_d(1.0, 0.0, 0.0, 0.0));
+        assert_eq!(2.0, formula_d(1.0, 10.0, 1.0, 10.0));
+        let recent = formula_d(0.0, 100.0, 1.0, 90.0);
+        let earlier = formula_d(0.0, 100.0, 1.0, 10.0);
+        assert!(recent < 1.0);
+        assert!(earlier < recent);
+    }
+
+    #[test]
+    fn probability() {
+        let once = formula_p(0.0, 0.1, 10.0, 1.0);
+        let twice = formula_p(0.0, 0.1, 10.0, 2.0);
+        assert!(0.0 < once && once < twice && twice < 1.0);
+        assert_eq!(0.0, formula_p(0.0, 0.0, 10.0, 0.0));
+        assert!(formula_p(0.0, 0.5, 1e6, 1e6) <= 1.0);
+    }
+}
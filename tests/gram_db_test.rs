@@ -0,0 +1,112 @@
+mod commons;
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use librime_rust::rime::common::PathExt;
+    use librime_rust::rime::dict::gram_db::{GramBuilder, GramDb};
+
+    use crate::commons;
+
+    static CORPUS: &str = "我 是 中国 人\n我 是 学生\n他 是 中国 人\n中国 人 很 多\n";
+
+    static ARPA: &str = "\\data\\
+ngram 1=4
+ngram 2=2
+
+\\1-grams:
+-1.0\t<s>\t-0.5
+-0.5\t天气\t-0.3
+-0.7\t很好
+-0.8\t</s>
+
+\\2-grams:
+-0.2\t<s> 天气
+-0.1\t天气 很好
+
+\\end\\
+";
+
+    fn build_from_corpus(name: &str) -> (GramBuilder, PathExt) {
+        let corpus_path = PathExt::new(format!("{}.txt", name));
+        fs::write(&corpus_path, CORPUS).unwrap();
+        let builder = GramBuilder::from_corpus(&corpus_path, 3).unwrap();
+        (builder, PathExt::new(format!("{}.gram", name)))
+    }
+
+    #[test]
+    fn build_and_load() {
+        commons::enable_log();
+        let (builder, gram_path) = build_from_corpus("gram_db_test_build");
+        assert_eq!(3, builder.max_order());
+        assert!(builder.save(gram_path.clone()));
+
+        let db = GramDb::load(gram_path.clone()).unwrap();
+        assert_eq!(3, db.max_order());
+        assert_eq!(builder.source_checksum(), db.corpus_file_checksum());
+
+        // saving again replaces the file rather than rewriting it under the
+        // loaded grammar
+        let arpa_path = PathExt::new("gram_db_test_build.arpa");
+        fs::write(&arpa_path, ARPA).unwrap();
+        assert!(GramBuilder::from_arpa(&arpa_path)
+            .unwrap()
+            .save(gram_path.clone()));
+        assert_eq!(3, db.max_order());
+        assert!(db.query("我是", "中国", false) > db.query("我是", "多", false));
+        assert_eq!(2, GramDb::load(gram_path).unwrap().max_order());
+    }
+
+    #[test]
+    fn query() {
+        commons::enable_log();
+        let (builder, gram_path) = build_from_corpus("gram_db_test_query");
+        assert!(builder.save(gram_path.clone()));
+        let db = GramDb::load(gram_path).unwrap();
+
+        let seen = db.query("我是", "中国", false);
+        let unseen = db.query("我是", "多", false);
+        assert!(seen < 0.0);
+        assert!(unseen < seen);
+        // an unknown word scores below any known one
+        assert!(db.query("我是", "老师", false) < unseen);
+        // the sentence start is taken as the history of the first word
+        assert!(db.query("", "我", false) > db.query("", "很", false));
+    }
+
+    #[test]
+    fn query_rear() {
+        commons::enable_log();
+        let (builder, gram_path) = build_from_corpus("gram_db_test_rear");
+        assert!(builder.save(gram_path.clone()));
+        let db = GramDb::load(gram_path).unwrap();
+
+        // two of the three sentences with "中国 人" end there
+        let rear = db.query("中国", "人", true);
+        let not_rear = db.query("中国", "人", false);
+        assert!((rear - not_rear - (2.0f64 / 3.0).ln()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn arpa() {
+        commons::enable_log();
+        let arpa_path = PathExt::new("gram_db_test.arpa");
+        fs::write(&arpa_path, ARPA).unwrap();
+        let builder = GramBuilder::from_arpa(&arpa_path).unwrap();
+        assert_eq!(2, builder.max_order());
+        let gram_path = PathExt::new("gram_db_test_arpa.gram");
+        assert!(builder.save(gram_path.clone()));
+        let db = GramDb::load(gram_path).unwrap();
+
+        let ln_10 = std::f64::consts::LN_10;
+        let bigram = db.query("天气", "很好", false);
+        assert!((bigram - -0.1 * ln_10).abs() < 1e-5);
+        // backs off from the history to the unigram
+        let backoff = db.query("很好", "天气", false);
+        assert!((backoff - -0.5 * ln_10).abs() < 1e-5);
+        let backoff = db.query("", "很好", false);
+        assert!((backoff - (-0.5 - 0.7) * ln_10).abs() < 1e-5);
+        assert!((db.query("", "天气", false) - -0.2 * ln_10).abs() < 1e-5);
+    }
+}
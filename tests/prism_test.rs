@@ -34,15 +34,19 @@ mod tests {
     }
 
     #[test]
-    #[ignore]
     fn save_and_load() {
         let test_obj = RimePrismTest::new();
         assert!(test_obj.prism.save());
 
-        //let mut test_prism = Prism::new(test_obj.prism.file_path());
-        //assert!(test_prism.load());
+        let mut test_prism = Prism::new(PathExt::new("prism_test.bin"));
+        assert!(test_prism.load());
 
-        //assert_eq!(test_obj.prism.array_size(), test_prism.array_size());
+        assert_eq!(
+            test_obj.prism.get_value("goodbye"),
+            test_prism.get_value("goodbye")
+        );
+        assert!(test_prism.has_key("yahoo"));
+        assert!(!test_prism.has_key("googlesoft"));
     }
 
     #[test]
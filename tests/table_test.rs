@@ -61,54 +61,107 @@ mod tests {
     }
 
     impl TableTest {
-        fn new() -> Self {
-            let mut table = Table::new(PathExt::new("table_test.bin"));
+        // Tests run in parallel, each on a file of its own.
+        fn new(file_name: &str) -> Self {
+            let mut table = Table::new(PathExt::new(file_name));
             let (syll, voc) = prepare_sample_vocabulary();
             assert!(table.build(&syll, &voc, 8, 0));
-            //assert!(table.save());
-            //table.load();
-            //TableTest { table }
-            todo!()
+            assert!(table.save());
+            assert!(table.load());
+            TableTest { table }
         }
     }
 
-    //    //#[test]
-    //    //fn integrity_test() {
-    //    //    let table_test = TableTest::new();
-    //    //    assert!(table_test.table.load());
-    //    //}
-
-    //    #[test]
-    //    fn simple_query_test() {
-    //        let table_test = TableTest::new();
-
-    //        assert_eq!(Some("0"), table_test.table.get_syllable_by_id(0));
-    //        assert_eq!(Some("3"), table_test.table.get_syllable_by_id(3));
-    //        assert_eq!(Some("4"), table_test.table.get_syllable_by_id(4));
-
-    //        if let Some(v) = table_test.table.query_words(1) {
-    //            assert_eq!(v.len(), 1);
-    //            assert_eq!(v[0].text, "yi");
-    //            assert_eq!(v[0].weight, 1.0);
-    //        }
-
-    //        if let Some(v) = table_test.table.query_words(2) {
-    //            assert_eq!(v.len(), 3);
-    //            assert_eq!(v[0].text, "er");
-    //            assert_eq!(v[1].text, "liang");
-    //            assert_eq!(v[2].text, "lia");
-    //        }
-
-    //        if let Some(v) = table_test.table.query_words(3) {
-    //            assert_eq!(v.len(), 2);
-    //            assert_eq!(v[0].text, "san");
-    //            assert_eq!(v[1].text, "sa");
-    //        }
-
-    //        let code = vec![1, 2, 3];
-    //        if let Some(v) = table_test.table.query_phrases(&code) {
-    //            assert_eq!(v.len(), 1);
-    //            assert_eq!(v[0].text, "yi-er-san");
-    //        }
-    //    }
+    #[test]
+    fn integrity_test() {
+        let mut table_test = TableTest::new("table_test_integrity.bin");
+        assert!(table_test.table.load());
+        assert!(table_test.table.is_loaded());
+    }
+
+    #[test]
+    fn simple_query_test() {
+        let table_test = TableTest::new("table_test_simple_query.bin");
+
+        assert_eq!(Some("0"), table_test.table.get_syllable_by_id(0));
+        assert_eq!(Some("3"), table_test.table.get_syllable_by_id(3));
+        assert_eq!(Some("4"), table_test.table.get_syllable_by_id(4));
+        assert_eq!(None, table_test.table.get_syllable_by_id(5));
+
+        let v = table_test.table.query_words(1).unwrap();
+        assert_eq!(v.len(), 1);
+        assert_eq!(v[0].text, "yi");
+        assert_eq!(v[0].weight, 1.0);
+
+        let v = table_test.table.query_words(2).unwrap();
+        assert_eq!(v.len(), 3);
+        assert_eq!(v[0].text, "er");
+        assert_eq!(v[1].text, "liang");
+        assert_eq!(v[2].text, "lia");
+
+        let v = table_test.table.query_words(3).unwrap();
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[0].text, "san");
+        assert_eq!(v[1].text, "sa");
+
+        assert!(table_test.table.query_words(0).is_none());
+
+        let code = vec![1, 2, 3];
+        let v = table_test.table.query_phrases(&code).unwrap();
+        assert_eq!(v.len(), 1);
+        assert_eq!(v[0].text, "yi-er-san");
+    }
+
+    #[test]
+    fn long_code_query_test() {
+        let table_test = TableTest::new("table_test_long_code_query.bin");
+
+        let v = table_test.table.query_phrases(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(v.len(), 1);
+        assert_eq!(v[0].text, "yi-er-san-si");
+
+        let v = table_test.table.query_phrases(&[1, 2, 3, 2, 1]).unwrap();
+        assert_eq!(v.len(), 1);
+        assert_eq!(v[0].text, "yi-er-san-er-yi");
+
+        assert!(table_test.table.query_phrases(&[1, 2, 3, 2]).is_none());
+    }
+
+    #[test]
+    fn rebuild_while_loaded_test() {
+        let table_test = TableTest::new("table_test_rebuild.bin");
+        // the file is replaced rather than truncated under the loaded table
+        let mut table = Table::new(PathExt::new("table_test_rebuild.bin"));
+        assert!(table.build(&Syllabary::new(), &Vocabulary::default(), 0, 1));
+        assert!(table.save());
+        assert_eq!(table_test.table.query_words(1).unwrap()[0].text, "yi");
+        assert!(table.load());
+        assert_eq!(1, table.dict_file_checksum());
+        assert!(table.query_words(1).is_none());
+    }
+
+    #[test]
+    fn prefix_query_test() {
+        let table_test = TableTest::new("table_test_prefix_query.bin");
+        let texts = |code: &[i32]| {
+            table_test
+                .table
+                .query_prefix(code)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|entry| entry.text)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            texts(&[1]),
+            ["yi", "yi-er-san", "yi-er-san-si", "yi-er-san-er-yi"]
+        );
+        assert_eq!(
+            texts(&[1, 2, 3]),
+            ["yi-er-san", "yi-er-san-si", "yi-er-san-er-yi"]
+        );
+        assert_eq!(texts(&[1, 2, 3, 2]), ["yi-er-san-er-yi"]);
+        assert!(texts(&[2, 1]).is_empty());
+    }
 }